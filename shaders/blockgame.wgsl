@@ -41,10 +41,11 @@ struct Mat { alb: vec3<f32>, r: f32, m: f32, f: f32, }; // material
 struct Light { p: vec3<f32>, c: vec3<f32>, i: f32, }; // light
 
 // sdf sampling
+const FONT_PAD = 8.; // atlas cell padding, FONT_SDF_SPREAD in font.rs
 fn sdf(p: vec2<f32>, sz: vec2<f32>, a: u32, size: f32) -> f32 {
     let uv = p / sz;
     let c = vec2<u32>(a % 16u, a / 16u);
-    let fuv = (vec2<f32>(c) * 64. + 4. + uv * 56.) / 1024.;
+    let fuv = (vec2<f32>(c) * 64. + FONT_PAD + uv * (64. - 2. * FONT_PAD)) / 1024.;
     
     if (any(fuv < vec2(0.)) || any(fuv >= vec2(1.))) { return 0.; }
     return textureLoad(t_font_atlas, vec2<i32>(fuv * 1024.), 0).a;
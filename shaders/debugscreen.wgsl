@@ -54,7 +54,7 @@ fn sample_sdf_at_position(local_pos: vec2<f32>, char_size: vec2<f32>, ascii: u32
     // Atlas dimensions (have to match Rust code! see font.rs)
     let atlas_size = 1024.0;
     let cell_size = 64.0; // Each character cell is 64x64 pixels
-    let padding = 4.0;    // Padding around each character
+    let padding = 8.0;    // Padding around each character (FONT_SDF_SPREAD)
     
     // Calculate UV coordinates in the atlas with padding
     let effective_cell_size = cell_size - padding * 2.0;
@@ -71,12 +71,30 @@ fn sample_sdf_at_position(local_pos: vec2<f32>, char_size: vec2<f32>, ascii: u32
         return 0.0;
     }
     
-    // Convert UV to pixel coordinates for textureLoad
-    let atlas_coord = vec2<i32>(i32(final_uv.x * atlas_size), i32(final_uv.y * atlas_size));
-    let pixel = textureLoad(t_font_atlas, atlas_coord, 0);
-    
-    // Since we're using direct alpha texture (not SDF), just return alpha
-    return pixel.a;
+    // RGB holds a signed distance field: 0.5 on the outline, 1/16 per atlas pixel (FONT_SDF_SPREAD = 8)
+    let distance = textureSampleLevel(t_font_atlas, s_font_atlas, final_uv, 0.0).r;
+    return sdf_fill(distance, sdf_pixel_width(size, effective_cell_size));
+}
+
+// Distance units covered by one screen pixel, for a glyph cell drawn `size` pixels wide
+fn sdf_pixel_width(size: f32, cell_pixels: f32) -> f32 {
+    return (cell_pixels / size) / 16.0;
+}
+
+// Antialiased glyph interior; `w` from sdf_pixel_width
+fn sdf_fill(distance: f32, w: f32) -> f32 {
+    return smoothstep(0.5 - w * 0.5, 0.5 + w * 0.5, distance);
+}
+
+// Band of `thickness` atlas pixels just outside the outline
+fn sdf_outline(distance: f32, w: f32, thickness: f32) -> f32 {
+    let inner = 0.5 - thickness / 16.0;
+    return smoothstep(inner - w * 0.5, inner + w * 0.5, distance) * (1.0 - sdf_fill(distance, w));
+}
+
+// Soft falloff up to the spread, for glows and drop shadows (sample at an offset for shadows)
+fn sdf_glow(distance: f32) -> f32 {
+    return smoothstep(0.0, 0.5, distance);
 }
 
 // Character rendering using modern SDF method
@@ -0,0 +1,195 @@
+// Image-based lighting precomputation, used by src/ibl.rs.
+// Cube faces follow the wgpu order: +X, -X, +Y, -Y, +Z, -Z.
+
+const PI: f32 = 3.14159265359;
+
+struct IblParams {
+    face_size: u32,
+    sample_count: u32,
+    roughness: f32,
+    env_size: f32,
+};
+
+@group(0) @binding(0) var<uniform> params: IblParams;
+@group(0) @binding(1) var equirect: texture_2d<f32>;
+@group(0) @binding(2) var linear_sampler: sampler;
+@group(0) @binding(3) var dst_cube: texture_storage_2d_array<rgba16float, write>;
+@group(0) @binding(4) var env_cube: texture_cube<f32>;
+@group(0) @binding(5) var src_mip: texture_2d_array<f32>;
+@group(0) @binding(6) var dst_lut: texture_storage_2d<rgba16float, write>;
+
+fn cube_direction(face: u32, texel: vec2<u32>, size: u32) -> vec3<f32> {
+    let uv = (vec2<f32>(texel) + 0.5) / f32(size) * 2.0 - 1.0;
+    let u = uv.x;
+    let v = uv.y;
+    var dir: vec3<f32>;
+    switch face {
+        case 0u: { dir = vec3<f32>(1.0, -v, -u); }
+        case 1u: { dir = vec3<f32>(-1.0, -v, u); }
+        case 2u: { dir = vec3<f32>(u, 1.0, v); }
+        case 3u: { dir = vec3<f32>(u, -1.0, -v); }
+        case 4u: { dir = vec3<f32>(u, -v, 1.0); }
+        default: { dir = vec3<f32>(-u, -v, -1.0); }
+    }
+    return normalize(dir);
+}
+
+// Same mapping as sample_background in pathtracing.wgsl
+fn equirect_uv(dir: vec3<f32>) -> vec2<f32> {
+    let phi = atan2(dir.z, dir.x);
+    let theta = asin(clamp(dir.y, -1.0, 1.0));
+    return vec2<f32>((phi + PI) / (2.0 * PI), 1.0 - (theta + PI / 2.0) / PI);
+}
+
+fn radical_inverse_vdc(bits_in: u32) -> f32 {
+    var bits = bits_in;
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return f32(bits) * 2.3283064365386963e-10;
+}
+
+fn hammersley(i: u32, n: u32) -> vec2<f32> {
+    return vec2<f32>(f32(i) / f32(n), radical_inverse_vdc(i));
+}
+
+fn tangent_frame(n: vec3<f32>) -> mat3x3<f32> {
+    let up = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(0.0, 0.0, 1.0), abs(n.z) < 0.999);
+    let tangent = normalize(cross(up, n));
+    let bitangent = cross(n, tangent);
+    return mat3x3<f32>(tangent, bitangent, n);
+}
+
+fn importance_sample_ggx(xi: vec2<f32>, n: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let a = roughness * roughness;
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    let h = vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+    return normalize(tangent_frame(n) * h);
+}
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+fn geometry_smith_ibl(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = roughness * roughness / 2.0;
+    let gv = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let gl = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return gv * gl;
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn equirect_to_cube(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.face_size || id.y >= params.face_size) {
+        return;
+    }
+    let dir = cube_direction(id.z, id.xy, params.face_size);
+    let color = textureSampleLevel(equirect, linear_sampler, equirect_uv(dir), 0.0);
+    textureStore(dst_cube, id.xy, id.z, vec4<f32>(color.rgb, 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn downsample_cube(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.face_size || id.y >= params.face_size) {
+        return;
+    }
+    let src = id.xy * 2u;
+    let face = i32(id.z);
+    let c = textureLoad(src_mip, src, face, 0)
+        + textureLoad(src_mip, src + vec2<u32>(1u, 0u), face, 0)
+        + textureLoad(src_mip, src + vec2<u32>(0u, 1u), face, 0)
+        + textureLoad(src_mip, src + vec2<u32>(1u, 1u), face, 0);
+    textureStore(dst_cube, id.xy, id.z, c * 0.25);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn prefilter_specular(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.face_size || id.y >= params.face_size) {
+        return;
+    }
+    let n = cube_direction(id.z, id.xy, params.face_size);
+    let roughness = params.roughness;
+    if (roughness <= 0.0) {
+        textureStore(dst_cube, id.xy, id.z, vec4<f32>(textureSampleLevel(env_cube, linear_sampler, n, 0.0).rgb, 1.0));
+        return;
+    }
+
+    // Filtered importance sampling: pick the env mip whose texel solid angle matches the sample's lobe
+    let texel_solid_angle = 4.0 * PI / (6.0 * params.env_size * params.env_size);
+    var color = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < params.sample_count; i++) {
+        let xi = hammersley(i, params.sample_count);
+        let h = importance_sample_ggx(xi, n, roughness);
+        let l = normalize(2.0 * dot(n, h) * h - n);
+        let n_dot_l = dot(n, l);
+        if (n_dot_l > 0.0) {
+            let n_dot_h = max(dot(n, h), 0.0);
+            let pdf = distribution_ggx(n_dot_h, roughness) / 4.0 + 0.0001;
+            let sample_solid_angle = 1.0 / (f32(params.sample_count) * pdf + 0.0001);
+            let lod = max(0.5 * log2(sample_solid_angle / texel_solid_angle) + 1.0, 0.0);
+            color += textureSampleLevel(env_cube, linear_sampler, l, lod).rgb * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+    textureStore(dst_cube, id.xy, id.z, vec4<f32>(color / max(weight, 0.0001), 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn irradiance(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.face_size || id.y >= params.face_size) {
+        return;
+    }
+    let n = cube_direction(id.z, id.xy, params.face_size);
+    let frame = tangent_frame(n);
+    // Cosine-weighted hemisphere sampling from a blurry env mip keeps this cheap and noise free
+    let lod = max(log2(params.env_size / 32.0), 0.0);
+    var color = vec3<f32>(0.0);
+    for (var i = 0u; i < params.sample_count; i++) {
+        let xi = hammersley(i, params.sample_count);
+        let r = sqrt(xi.y);
+        let phi = 2.0 * PI * xi.x;
+        let local = vec3<f32>(r * cos(phi), r * sin(phi), sqrt(max(1.0 - xi.y, 0.0)));
+        color += textureSampleLevel(env_cube, linear_sampler, frame * local, lod).rgb;
+    }
+    textureStore(dst_cube, id.xy, id.z, vec4<f32>(color / f32(params.sample_count), 1.0));
+}
+
+// Split-sum BRDF integration: x = N.V, y = roughness -> (scale, bias) for F0
+@compute @workgroup_size(8, 8, 1)
+fn brdf_lut(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.face_size || id.y >= params.face_size) {
+        return;
+    }
+    let n_dot_v = max((f32(id.x) + 0.5) / f32(params.face_size), 0.001);
+    let roughness = (f32(id.y) + 0.5) / f32(params.face_size);
+    let v = vec3<f32>(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    let n = vec3<f32>(0.0, 0.0, 1.0);
+
+    var a = 0.0;
+    var b = 0.0;
+    for (var i = 0u; i < params.sample_count; i++) {
+        let xi = hammersley(i, params.sample_count);
+        let h = importance_sample_ggx(xi, n, roughness);
+        let l = normalize(2.0 * dot(v, h) * h - v);
+        let n_dot_l = max(l.z, 0.0);
+        let n_dot_h = max(h.z, 0.0);
+        let v_dot_h = max(dot(v, h), 0.0);
+        if (n_dot_l > 0.0) {
+            let g = geometry_smith_ibl(n_dot_v, n_dot_l, roughness);
+            let g_vis = g * v_dot_h / (n_dot_h * n_dot_v + 0.0001);
+            let fc = pow(1.0 - v_dot_h, 5.0);
+            a += (1.0 - fc) * g_vis;
+            b += fc * g_vis;
+        }
+    }
+    let count = f32(params.sample_count);
+    textureStore(dst_lut, id.xy, vec4<f32>(a / count, b / count, 0.0, 1.0));
+}
@@ -33,6 +33,13 @@ struct PathTracingParams {
 @group(2) @binding(0) var output: texture_storage_2d<rgba16float, write>;
 @group(2) @binding(1) var background_texture: texture_2d<f32>;
 @group(2) @binding(2) var background_sampler: sampler;
+// HDRI exposure/gamma; gamma 2.2 leaves the (linear) environment unchanged
+struct BackgroundParams {
+    exposure: f32,
+    gamma: f32,
+    _padding: vec2<f32>,
+};
+@group(2) @binding(3) var<uniform> background_params: BackgroundParams;
 @group(3) @binding(0) var<storage, read_write> atomic_buffer: array<atomic<u32>>;
 
 alias v4 = vec4<f32>;
@@ -583,7 +590,8 @@ fn sample_background(dir: vec3<f32>) -> vec3<f32> {
     let theta = asin(dir.y);
     let u = (phi + pi) / (2.0 * pi);
     let v = 1.0 - (theta + pi/2.0) / pi; 
-    return textureSampleLevel(background_texture, background_sampler, vec2<f32>(u, v), 0.0).rgb;
+    let c = textureSampleLevel(background_texture, background_sampler, vec2<f32>(u, v), 0.0).rgb;
+    return pow(max(c * background_params.exposure, vec3<f32>(0.0)), vec3<f32>(2.2 / background_params.gamma));
 }
 fn trace_ray(ray: Ray, max_bounces: u32) -> v3 {
     var current_ray = ray;
@@ -0,0 +1,24 @@
+// Minimal sound shader for compute::SoundShader: a 220 Hz sine on both channels.
+struct SoundBlock {
+    start_frame: u32,
+    frames: u32,
+    channels: u32,
+    sample_rate: f32,
+    time: f32,
+    start_frame_hi: u32,
+    second_frame: u32,
+    _pad: u32,
+};
+@group(0) @binding(0) var<uniform> block: SoundBlock;
+@group(0) @binding(1) var<storage, read_write> samples: array<f32>;
+
+@compute @workgroup_size(64)
+fn main_sound(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= block.frames { return; }
+    // Whole seconds are dropped on the CPU, so the phase stays precise and continuous however long the stream runs
+    let frame = (block.second_frame + id.x) % u32(block.sample_rate);
+    let t = f32(frame) / block.sample_rate;
+    let s = 0.3 * sin(6.2831853 * 220.0 * t);
+    samples[id.x * block.channels] = s;
+    samples[id.x * block.channels + 1u] = s;
+}
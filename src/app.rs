@@ -89,6 +89,7 @@ impl<S: ShaderManager> ApplicationHandler for ShaderAppHandler<S> {
                 if !shader.handle_input(core, &event) {
                     match event {
                         WindowEvent::CloseRequested => {
+                            core.save_pipeline_cache();
                             event_loop.exit();
                         }
                         WindowEvent::Resized(size) => {
@@ -0,0 +1,279 @@
+// Rhythm and loudness features derived from the spectrum/level analysis, for shaders that
+// need to know when a beat lands rather than just how loud each band is.
+#[cfg(feature = "media")]
+use std::collections::VecDeque;
+#[cfg(feature = "media")]
+use std::time::Instant;
+
+#[cfg(feature = "media")]
+use crate::gst::analysis::{LevelData, SpectrumData};
+use crate::UniformProvider;
+
+/// Number of log-spaced bands onsets are detected in
+pub const ONSET_BANDS: usize = 8;
+
+/// Feature block for shaders:
+/// ```wgsl
+/// struct AudioFeatures {
+///     onset: array<vec4<f32>, 2>,
+///     onset_max: f32, beat_phase: f32, beat_pulse: f32, bpm: f32,
+///     rms: f32, rms_db: f32, loudness_lufs: f32, centroid_hz: f32,
+///     centroid: f32, _pad0: f32, _pad1: f32, _pad2: f32,
+/// }
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct AudioFeaturesUniform {
+    /// Onset strength per band (0..1, a pulse that decays), bass first
+    pub onset: [[f32; 4]; 2],
+    /// Strongest onset across bands
+    pub onset_max: f32,
+    /// 0..1 sawtooth locked to the tempo, 0 is on the beat
+    pub beat_phase: f32,
+    /// 1 when the phase wraps, decaying until the next beat
+    pub beat_pulse: f32,
+    pub bpm: f32,
+    /// RMS level, linear 0..1
+    pub rms: f32,
+    pub rms_db: f32,
+    /// Short-term (3 s) loudness, estimated from the K-weighted spectrum
+    pub loudness_lufs: f32,
+    /// Spectral centroid in Hz
+    pub centroid_hz: f32,
+    /// Spectral centroid relative to Nyquist, 0..1
+    pub centroid: f32,
+    pub _padding: [f32; 3],
+}
+
+impl UniformProvider for AudioFeaturesUniform {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AudioFeaturesConfig {
+    /// Rate used to give bins a frequency when the spectrum doesn't carry its negotiated rate
+    pub sample_rate: f32,
+    /// Levels at or below this count as silence
+    pub min_db: f32,
+    /// How far spectral flux must rise above its running average to count as an onset
+    pub onset_threshold: f32,
+    /// Onset and beat pulses fall off at this rate per second
+    pub pulse_decay: f32,
+    /// How strongly a bass onset pulls the beat phase towards 0 (0 = free running)
+    pub beat_sync: f32,
+}
+
+impl Default for AudioFeaturesConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100.0,
+            min_db: -60.0,
+            onset_threshold: 1.5,
+            pulse_decay: 8.0,
+            beat_sync: 0.2,
+        }
+    }
+}
+
+/// Computes `AudioFeaturesUniform` from successive spectrum/level updates.
+pub struct AudioFeatures {
+    pub config: AudioFeaturesConfig,
+    pub uniform: AudioFeaturesUniform,
+    /// Magnitudes (dB) of the last spectrum analyzed
+    #[cfg(feature = "media")]
+    prev_spectrum: Vec<f32>,
+    #[cfg(feature = "media")]
+    prev_timestamp: Option<gstreamer::ClockTime>,
+    /// Running average of the flux in each onset band
+    #[cfg(feature = "media")]
+    flux_mean: [f32; ONSET_BANDS],
+    /// (seconds since start, K-weighted power) for the short-term loudness window
+    #[cfg(feature = "media")]
+    loudness_window: VecDeque<(f32, f32)>,
+    #[cfg(feature = "media")]
+    clock: f32,
+    #[cfg(feature = "media")]
+    last_update: Option<Instant>,
+}
+
+impl Default for AudioFeatures {
+    fn default() -> Self {
+        Self::new(AudioFeaturesConfig::default())
+    }
+}
+
+impl AudioFeatures {
+    pub fn new(config: AudioFeaturesConfig) -> Self {
+        Self {
+            config,
+            uniform: AudioFeaturesUniform {
+                loudness_lufs: -70.0,
+                rms_db: config.min_db,
+                ..Default::default()
+            },
+            #[cfg(feature = "media")]
+            prev_spectrum: Vec::new(),
+            #[cfg(feature = "media")]
+            prev_timestamp: None,
+            #[cfg(feature = "media")]
+            flux_mean: [0.0; ONSET_BANDS],
+            #[cfg(feature = "media")]
+            loudness_window: VecDeque::new(),
+            #[cfg(feature = "media")]
+            clock: 0.0,
+            #[cfg(feature = "media")]
+            last_update: None,
+        }
+    }
+
+    /// Clears all state, e.g. after loading a new file
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+}
+
+/// Approximate K-weighting gain (ITU-R BS.1770) in dB: a ~38 Hz high-pass and a +4 dB shelf above ~1.5 kHz
+#[cfg(feature = "media")]
+fn k_weighting_db(hz: f32) -> f32 {
+    let high_pass = -10.0 * (1.0 + (38.0 / hz.max(1.0)).powi(4)).log10();
+    let shelf = 4.0 * (hz / 1500.0).powi(2) / (1.0 + (hz / 1500.0).powi(2));
+    high_pass + shelf
+}
+
+#[cfg(feature = "media")]
+impl AudioFeatures {
+    /// Advances the features by the time since the last call. `spectrum` and `level` may
+    /// repeat between analyzer messages; onsets are only measured on new spectra.
+    pub fn update(&mut self, spectrum: &SpectrumData, level: Option<&LevelData>, bpm: f32) {
+        let now = Instant::now();
+        let dt = self
+            .last_update
+            .map(|last| now.duration_since(last).as_secs_f32().min(0.25))
+            .unwrap_or(0.0);
+        self.last_update = Some(now);
+        self.advance(dt, spectrum, level, bpm);
+    }
+
+    /// Same as `update` with an explicit time step, for offline rendering
+    pub fn advance(&mut self, dt: f32, spectrum: &SpectrumData, level: Option<&LevelData>, bpm: f32) {
+        self.clock += dt;
+        let decay = (-self.config.pulse_decay * dt).exp();
+        for value in self.uniform.onset.iter_mut().flatten() {
+            *value *= decay;
+        }
+        self.uniform.beat_pulse *= decay;
+
+        let is_new = match (spectrum.timestamp, self.prev_timestamp) {
+            (Some(ts), Some(prev)) => ts != prev,
+            _ => spectrum.magnitudes != self.prev_spectrum,
+        };
+        if is_new && !spectrum.magnitudes.is_empty() {
+            self.analyze_spectrum(spectrum);
+        }
+
+        if let Some(level) = level.filter(|level| !level.rms.is_empty()) {
+            // Power average over channels
+            let power = level
+                .rms
+                .iter()
+                .map(|db| 10f32.powf(db / 10.0))
+                .sum::<f32>()
+                / level.rms.len() as f32;
+            self.uniform.rms = power.sqrt();
+            self.uniform.rms_db = (10.0 * power.max(1e-12).log10()).max(self.config.min_db);
+        }
+
+        self.update_beat(dt, bpm);
+    }
+
+    fn analyze_spectrum(&mut self, spectrum: &SpectrumData) {
+        let bins = spectrum.magnitudes.len();
+        let sample_rate = spectrum.sample_rate.map_or(self.config.sample_rate, |rate| rate as f32);
+        let nyquist = sample_rate / 2.0;
+        let bin_hz = nyquist / bins as f32;
+        let min_db = self.config.min_db;
+        let level = |db: f32| ((db - min_db) / -min_db).clamp(0.0, 1.0);
+
+        // Spectral flux per log-spaced band
+        if self.prev_spectrum.len() == bins {
+            let log_bins = (bins as f32).ln();
+            let edge = |band: usize| ((band as f32 / ONSET_BANDS as f32) * log_bins).exp() as usize - 1;
+            let mut onset_max: f32 = 0.0;
+            for band in 0..ONSET_BANDS {
+                let start = edge(band).min(bins - 1);
+                let end = edge(band + 1).clamp(start + 1, bins);
+                let flux = (start..end)
+                    .map(|i| (level(spectrum.magnitudes[i]) - level(self.prev_spectrum[i])).max(0.0))
+                    .sum::<f32>()
+                    / (end - start) as f32;
+                let mean = self.flux_mean[band];
+                let strength = ((flux - mean * self.config.onset_threshold) / (mean + 1e-3)).clamp(0.0, 1.0);
+                self.flux_mean[band] = mean * 0.9 + flux * 0.1;
+
+                let slot = &mut self.uniform.onset[band / 4][band % 4];
+                *slot = slot.max(strength);
+                onset_max = onset_max.max(*slot);
+            }
+            self.uniform.onset_max = onset_max;
+
+            // A strong bass onset pulls the beat phase towards the nearest beat
+            let bass_onset = self.uniform.onset[0][0].max(self.uniform.onset[0][1]);
+            if bass_onset > 0.5 && self.uniform.bpm > 0.0 {
+                let phase = self.uniform.beat_phase;
+                let error = if phase > 0.5 { phase - 1.0 } else { phase };
+                self.uniform.beat_phase = (phase - error * self.config.beat_sync).rem_euclid(1.0);
+            }
+        }
+
+        // Spectral centroid and K-weighted power, both from linear bin power
+        let mut weighted = 0.0;
+        let mut total = 0.0;
+        let mut k_power = 0.0;
+        for (i, &db) in spectrum.magnitudes.iter().enumerate() {
+            if db <= self.config.min_db {
+                continue;
+            }
+            let hz = (i as f32 + 0.5) * bin_hz;
+            let power = 10f32.powf(db / 10.0);
+            weighted += hz * power;
+            total += power;
+            k_power += 10f32.powf((db + k_weighting_db(hz)) / 10.0);
+        }
+        if total > 0.0 {
+            self.uniform.centroid_hz = weighted / total;
+            self.uniform.centroid = self.uniform.centroid_hz / nyquist;
+        }
+
+        self.loudness_window.push_back((self.clock, k_power));
+        while let Some(&(time, _)) = self.loudness_window.front() {
+            if self.clock - time <= 3.0 {
+                break;
+            }
+            self.loudness_window.pop_front();
+        }
+        let mean_power = self.loudness_window.iter().map(|&(_, p)| p).sum::<f32>()
+            / self.loudness_window.len() as f32;
+        self.uniform.loudness_lufs = if mean_power > 0.0 {
+            (-0.691 + 10.0 * mean_power.log10()).max(-70.0)
+        } else {
+            -70.0
+        };
+
+        self.prev_spectrum.clone_from(&spectrum.magnitudes);
+        self.prev_timestamp = spectrum.timestamp;
+    }
+
+    fn update_beat(&mut self, dt: f32, bpm: f32) {
+        self.uniform.bpm = bpm;
+        if bpm <= 0.0 {
+            return;
+        }
+        let phase = self.uniform.beat_phase + dt * bpm / 60.0;
+        if phase >= 1.0 {
+            self.uniform.beat_pulse = 1.0;
+        }
+        self.uniform.beat_phase = phase.fract();
+    }
+}
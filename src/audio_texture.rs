@@ -0,0 +1,165 @@
+// Shadertoy-style audio input: a 512x2 texture with the spectrum in row 0 and the
+// waveform in row 1, for oscilloscope and XY-scope shaders.
+use crate::gst::analysis::SpectrumData;
+
+pub const AUDIO_TEXTURE_WIDTH: u32 = 512;
+
+/// `Rgba16Float`, `AUDIO_TEXTURE_WIDTH` x 2. Every texel holds r = mono mix, g = left,
+/// b = right, a = 1, all in 0..1 like Shadertoy: row 0 is the spectrum (0 = `min_db`,
+/// 1 = `max_db`), row 1 the waveform (0.5 = silence). Bound with the regular texture
+/// layout, so `@binding(0)` is the texture and `@binding(1)` a linear sampler.
+pub struct AudioTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub bind_group: wgpu::BindGroup,
+    pub min_db: f32,
+    pub max_db: f32,
+    /// Frequency at the right edge of the spectrum row
+    pub max_hz: f32,
+    /// Rate the analyzer runs at, used to map Hz onto its bins. `update_audio_spectrum`
+    /// sets it from the caps of the tapped stream.
+    pub sample_rate: f32,
+    texels: Vec<[u16; 4]>,
+}
+
+impl AudioTexture {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Audio Texture"),
+            size: wgpu::Extent3d {
+                width: AUDIO_TEXTURE_WIDTH,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("Audio Texture Bind Group"),
+        });
+        let silence = [0.0, 0.0, 0.0, 1.0].map(|v: f32| half::f16::from_f32(v).to_bits());
+        let wave = [0.5, 0.5, 0.5, 1.0].map(|v: f32| half::f16::from_f32(v).to_bits());
+        let mut texels = vec![silence; AUDIO_TEXTURE_WIDTH as usize];
+        texels.resize(2 * AUDIO_TEXTURE_WIDTH as usize, wave);
+        Self {
+            texture,
+            view,
+            sampler,
+            bind_group,
+            min_db: -60.0,
+            max_db: 0.0,
+            max_hz: 11025.0,
+            sample_rate: 44100.0,
+            texels,
+        }
+    }
+
+    fn write(&self, queue: &wgpu::Queue) {
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(&self.texels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(AUDIO_TEXTURE_WIDTH * 8),
+                rows_per_image: Some(2),
+            },
+            wgpu::Extent3d {
+                width: AUDIO_TEXTURE_WIDTH,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Rebuilds both rows and uploads them. `left`/`right` are the most recent samples,
+    /// oldest first; the newest `AUDIO_TEXTURE_WIDTH` of them are used.
+    pub fn update(&mut self, queue: &wgpu::Queue, spectrum: Option<&SpectrumData>, left: &[f32], right: &[f32]) {
+        let width = AUDIO_TEXTURE_WIDTH as usize;
+        let to_bits = |v: f32| half::f16::from_f32(v).to_bits();
+        let one = to_bits(1.0);
+
+        let (min_db, max_hz, nyquist) = (self.min_db, self.max_hz, self.sample_rate / 2.0);
+        let range = (self.max_db - min_db).max(1.0);
+        // dB at `x` in a channel's bins, interpolated between neighbours
+        let sample_db = |bins: &[f32], x: usize| -> f32 {
+            if bins.is_empty() {
+                return min_db;
+            }
+            let hz = (x as f32 + 0.5) / width as f32 * max_hz;
+            let position = (hz / nyquist * bins.len() as f32 - 0.5).max(0.0);
+            let i = (position as usize).min(bins.len() - 1);
+            let next = (i + 1).min(bins.len() - 1);
+            let t = position - i as f32;
+            bins[i] * (1.0 - t) + bins[next] * t
+        };
+        let level = |db: f32| ((db - min_db) / range).clamp(0.0, 1.0);
+
+        let empty = Vec::new();
+        let (mix, left_bins, right_bins) = match spectrum {
+            Some(data) => {
+                let left_bins = data.channel_magnitudes.first().unwrap_or(&data.magnitudes);
+                let right_bins = data.channel_magnitudes.get(1).unwrap_or(left_bins);
+                (&data.magnitudes, left_bins, right_bins)
+            }
+            None => (&empty, &empty, &empty),
+        };
+        for x in 0..width {
+            self.texels[x] = [
+                to_bits(level(sample_db(mix, x))),
+                to_bits(level(sample_db(left_bins, x))),
+                to_bits(level(sample_db(right_bins, x))),
+                one,
+            ];
+        }
+
+        let count = left.len().min(right.len()).min(width);
+        let left = &left[left.len() - count..];
+        let right = &right[right.len() - count..];
+        let offset = width - left.len();
+        for x in 0..width {
+            let (l, r) = if x >= offset {
+                (left[x - offset], right[x - offset])
+            } else {
+                (0.0, 0.0)
+            };
+            self.texels[width + x] = [
+                to_bits(0.5 + 0.25 * (l + r)),
+                to_bits(0.5 + 0.5 * l),
+                to_bits(0.5 + 0.5 * r),
+                one,
+            ];
+        }
+
+        self.write(queue);
+    }
+}
@@ -233,6 +233,7 @@ impl ShaderManager for Shader {
 
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -266,7 +267,8 @@ impl ShaderManager for Shader {
                 new_fs,
                 core.config.format,
                 &pipeline_layout,
-                None, 
+                None,
+                core.pipeline_cache.as_ref(),
             );
         }
     
@@ -130,6 +130,9 @@ impl AudioVis {
 
     fn handle_export(&mut self, core: &Core) {
         if let Some((frame, time)) = self.base.export_manager.try_get_next_frame() {
+            // Decode exactly the frame (and spectrum) for this export time
+            self.base.step_video_to(core, time);
+            self.base.update_audio_spectrum(&core.queue);
             if let Ok(data) = self.capture_frame(core, time) {
                 let settings = self.base.export_manager.settings();
                 if let Err(e) = cuneus::save_frame(data, frame, settings) {
@@ -138,6 +141,7 @@ impl AudioVis {
             }
         } else {
             self.base.export_manager.complete_export();
+            self.base.end_video_stepping();
         }
     }
 }
@@ -244,6 +248,7 @@ impl ShaderManager for AudioVis {
 
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -286,6 +291,7 @@ impl ShaderManager for AudioVis {
                 core.config.format,
                 &pipeline_layout,
                 None,
+                core.pipeline_cache.as_ref(),
             );
         }
         if self.base.export_manager.is_exporting() {
@@ -300,7 +306,7 @@ impl ShaderManager for AudioVis {
         let _video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let _webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
@@ -373,6 +379,7 @@ impl ShaderManager for AudioVis {
         
         if should_start_export {
             self.base.export_manager.start_export();
+            self.base.render_media_export_audio();
         }
         
         let mut encoder = core.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
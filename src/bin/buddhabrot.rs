@@ -317,6 +317,7 @@ impl ShaderManager for BuddhabrotShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/buddhabrot.wgsl"),
             cs_module.clone(),
             "Splat",
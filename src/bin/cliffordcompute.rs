@@ -284,6 +284,7 @@ impl ShaderManager for CliffordShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/cliffordcompute.wgsl"),
             cs_module.clone(),
             "Splat",
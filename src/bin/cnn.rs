@@ -593,13 +593,11 @@ impl ShaderManager for CNNDigitRecognizer {
         });
         
         let shader_source = include_str!("../../shaders/cnn.wgsl");
-        let cs_module = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("CNN Compute Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-        });
+        let cs_module = core.create_shader_module(shader_source, "CNN Compute Shader");
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/cnn.wgsl"),
             cs_module.clone(),
             "main_image",
@@ -623,7 +621,7 @@ impl ShaderManager for CNNDigitRecognizer {
             module: &cs_module,
             entry_point: Some("canvas_update"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let conv_layer1_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -632,7 +630,7 @@ impl ShaderManager for CNNDigitRecognizer {
             module: &cs_module,
             entry_point: Some("conv_layer1"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let conv_layer2_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -641,7 +639,7 @@ impl ShaderManager for CNNDigitRecognizer {
             module: &cs_module,
             entry_point: Some("conv_layer2"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let fully_connected_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -650,7 +648,7 @@ impl ShaderManager for CNNDigitRecognizer {
             module: &cs_module,
             entry_point: Some("fully_connected"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let visualization_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -659,7 +657,7 @@ impl ShaderManager for CNNDigitRecognizer {
             module: &cs_module,
             entry_point: Some("main_image"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
 
@@ -715,7 +713,7 @@ impl ShaderManager for CNNDigitRecognizer {
                 module: &new_shader,
                 entry_point: Some("canvas_update"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.conv_layer1_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -724,7 +722,7 @@ impl ShaderManager for CNNDigitRecognizer {
                 module: &new_shader,
                 entry_point: Some("conv_layer1"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.conv_layer2_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -733,7 +731,7 @@ impl ShaderManager for CNNDigitRecognizer {
                 module: &new_shader,
                 entry_point: Some("conv_layer2"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.fully_connected_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -742,7 +740,7 @@ impl ShaderManager for CNNDigitRecognizer {
                 module: &new_shader,
                 entry_point: Some("fully_connected"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.visualization_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -751,7 +749,7 @@ impl ShaderManager for CNNDigitRecognizer {
                 module: &new_shader,
                 entry_point: Some("main_image"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
         }
         
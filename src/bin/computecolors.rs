@@ -345,6 +345,7 @@ impl ShaderManager for ColorProjection {
         // Set up hot reload
         let hot_reload = ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/computecolors.wgsl"),
             cs_module.clone(),
             "project_colors", // Main entry point
@@ -504,7 +505,7 @@ impl ShaderManager for ColorProjection {
         let video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
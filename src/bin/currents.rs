@@ -8,7 +8,7 @@ use winit::event::WindowEvent;
 use std::path::PathBuf;
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, serde::Serialize, serde::Deserialize)]
 struct CurrentsParams {
     sphere_radius: f32,
     sphere_pos_x: f32,
@@ -386,6 +386,7 @@ impl ShaderManager for CurrentsShader {
 
         let hot_reload = ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/currents.wgsl"),
             cs_module.clone(),
             "buffer_a",
@@ -466,13 +467,17 @@ impl ShaderManager for CurrentsShader {
             cache: None,
         });
         
-        let base = RenderKit::new(
+        let mut base = RenderKit::new(
             core,
             include_str!("../../shaders/vertex.wgsl"),
             include_str!("../../shaders/blit.wgsl"),
             &[&texture_bind_group_layout],
             None,
         );
+        // Tune values in shaders/currents.toml while the app is running
+        if let Err(e) = base.enable_params_hot_reload(std::path::Path::new("shaders/currents.wgsl")) {
+            eprintln!("Failed to watch currents parameters: {}", e);
+        }
         
         Self {
             base,
@@ -501,6 +506,7 @@ impl ShaderManager for CurrentsShader {
     }
     
     fn update(&mut self, core: &Core) {
+        self.base.sync_params(&core.queue, &mut self.params_uniform);
         if let Some(new_shader) = self.hot_reload.reload_compute_shader() {
             println!("Reloading Currents shader at time: {:.2}s", self.base.start_time.elapsed().as_secs_f32());
             
@@ -1030,6 +1036,7 @@ impl ShaderManager for CurrentsShader {
         if changed {
             self.params_uniform.data = params;
             self.params_uniform.update(&core.queue);
+            self.base.save_params(&params);
         }
 
         if should_start_export {
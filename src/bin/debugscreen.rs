@@ -1,6 +1,6 @@
 use cuneus::{Core, ShaderApp, ShaderManager, RenderKit, ShaderControls};
 use cuneus::compute::{ComputeShaderConfig, COMPUTE_TEXTURE_FORMAT_RGBA16};
-use cuneus::audio::SynthesisManager;
+use cuneus::audio::{SynthesisManager, SynthConfig, AdsrConfig};
 use winit::event::*;
 use std::path::PathBuf;
 
@@ -115,6 +115,8 @@ impl ShaderManager for DebugScreen {
         
         let audio_synthesis = match SynthesisManager::new() {
             Ok(mut synth) => {
+                // The shader shapes the notes; the synth's own envelope would stack on top
+                synth.set_synth_config(SynthConfig { envelope: AdsrConfig::pass_through(), ..Default::default() });
                 if let Err(_e) = synth.start_gpu_synthesis() {
                     None
                 } else {
@@ -248,6 +248,7 @@ impl ShaderManager for Shader {
 
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -281,7 +282,8 @@ impl ShaderManager for Shader {
                 new_fs,
                 core.config.format,
                 &pipeline_layout,
-                None, 
+                None,
+                core.pipeline_cache.as_ref(),
             );
         }
     
@@ -251,6 +251,7 @@ impl ShaderManager for Droste {
 
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -293,6 +294,7 @@ impl ShaderManager for Droste {
                 core.config.format,
                 &pipeline_layout,
                 None,
+                core.pipeline_cache.as_ref(),
             );
         }
         if self.base.export_manager.is_exporting() {
@@ -308,7 +310,7 @@ impl ShaderManager for Droste {
         let _video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let _webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
@@ -52,6 +52,9 @@ struct FFTShader {
     hot_reload: cuneus::ShaderHotReload,
     
     should_initialize: bool,
+    
+    // Export frame stepped in update() and captured after the next render()
+    export_frame: Option<(u32, f32)>,
 }
 
 impl FFTShader {
@@ -229,16 +232,25 @@ impl FFTShader {
         Ok(unpadded_data)
     }
     
-    fn handle_export(&mut self, core: &Core) {
+    fn prepare_export_frame(&mut self, core: &Core) {
         if let Some((frame, time)) = self.base.export_manager.try_get_next_frame() {
-            if let Ok(data) = self.capture_frame(core, time) {
-                let settings = self.base.export_manager.settings();
-                if let Err(e) = cuneus::save_frame(data, frame, settings) {
-                    eprintln!("Error saving frame: {:?}", e);
-                }
+            // The FFT has to run on the exact video frame for this time before capturing
+            if self.base.step_video_to(core, time) {
+                self.recreate_compute_resources(core);
             }
+            self.export_frame = Some((frame, time));
         } else {
             self.base.export_manager.complete_export();
+            self.base.end_video_stepping();
+        }
+    }
+    
+    fn save_export_frame(&mut self, core: &Core, frame: u32, time: f32) {
+        if let Ok(data) = self.capture_frame(core, time) {
+            let settings = self.base.export_manager.settings();
+            if let Err(e) = cuneus::save_frame(data, frame, settings) {
+                eprintln!("Error saving frame: {:?}", e);
+            }
         }
     }
 }
@@ -424,10 +436,7 @@ impl ShaderManager for FFTShader {
         });
         
         let shader_source = include_str!("../../shaders/fft.wgsl");
-        let shader_module = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("FFT Compute Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-        });
+        let shader_module = core.create_shader_module(shader_source, "FFT Compute Shader");
         
         let compute_pipeline_layout = core.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("FFT Compute Pipeline Layout"),
@@ -446,7 +455,7 @@ impl ShaderManager for FFTShader {
             module: &shader_module,
             entry_point: Some("initialize_data"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let fft_horizontal_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -455,7 +464,7 @@ impl ShaderManager for FFTShader {
             module: &shader_module,
             entry_point: Some("fft_horizontal"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let fft_vertical_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -464,7 +473,7 @@ impl ShaderManager for FFTShader {
             module: &shader_module,
             entry_point: Some("fft_vertical"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let modify_freqs_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -473,7 +482,7 @@ impl ShaderManager for FFTShader {
             module: &shader_module,
             entry_point: Some("modify_frequencies"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let ifft_horizontal_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -482,7 +491,7 @@ impl ShaderManager for FFTShader {
             module: &shader_module,
             entry_point: Some("ifft_horizontal"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let ifft_vertical_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -491,7 +500,7 @@ impl ShaderManager for FFTShader {
             module: &shader_module,
             entry_point: Some("ifft_vertical"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let render_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -500,11 +509,12 @@ impl ShaderManager for FFTShader {
             module: &shader_module,
             entry_point: Some("main_image"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/fft.wgsl"),
             shader_module.clone(),
             "main_image",
@@ -532,6 +542,7 @@ impl ShaderManager for FFTShader {
             frame_count: 0,
             hot_reload,
             should_initialize: true,
+            export_frame: None,
         };
         
         result.recreate_compute_resources(core);
@@ -563,7 +574,7 @@ impl ShaderManager for FFTShader {
                 module: &new_shader,
                 entry_point: Some("initialize_data"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.fft_horizontal_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -572,7 +583,7 @@ impl ShaderManager for FFTShader {
                 module: &new_shader,
                 entry_point: Some("fft_horizontal"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.fft_vertical_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -581,7 +592,7 @@ impl ShaderManager for FFTShader {
                 module: &new_shader,
                 entry_point: Some("fft_vertical"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.modify_freqs_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -590,7 +601,7 @@ impl ShaderManager for FFTShader {
                 module: &new_shader,
                 entry_point: Some("modify_frequencies"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.ifft_horizontal_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -599,7 +610,7 @@ impl ShaderManager for FFTShader {
                 module: &new_shader,
                 entry_point: Some("ifft_horizontal"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.ifft_vertical_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -608,7 +619,7 @@ impl ShaderManager for FFTShader {
                 module: &new_shader,
                 entry_point: Some("ifft_vertical"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.render_pipeline = core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
@@ -617,7 +628,7 @@ impl ShaderManager for FFTShader {
                 module: &new_shader,
                 entry_point: Some("main_image"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             // We need to reinitialize the data after shader reload
@@ -627,7 +638,7 @@ impl ShaderManager for FFTShader {
         let video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
@@ -641,7 +652,7 @@ impl ShaderManager for FFTShader {
         
         // Handle export if needed
         if self.base.export_manager.is_exporting() {
-            self.handle_export(core);
+            self.prepare_export_frame(core);
         }
         
         self.base.fps_tracker.update();
@@ -786,7 +797,10 @@ impl ShaderManager for FFTShader {
         if self.base.handle_hdri_requests(core, &controls_request) {
             self.recreate_compute_resources(core);
         }
-        let current_time = self.base.controls.get_time(&self.base.start_time);
+        let current_time = match self.export_frame {
+            Some((_, time)) => time,
+            None => self.base.controls.get_time(&self.base.start_time),
+        };
         
         self.base.time_uniform.data.time = current_time;
         self.base.time_uniform.data.frame = self.frame_count;
@@ -955,6 +969,10 @@ impl ShaderManager for FFTShader {
         output.present();
         self.frame_count = self.frame_count.wrapping_add(1);
         
+        if let Some((frame, time)) = self.export_frame.take() {
+            self.save_export_frame(core, frame, time);
+        }
+        
         Ok(())
     }
     
@@ -253,6 +253,7 @@ impl ShaderManager for FluidShader {
         ];
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -264,6 +265,7 @@ impl ShaderManager for FluidShader {
             core.config.format,
             &pipeline_layout,
             Some("fs_pass2"),
+            core.pipeline_cache.as_ref(),
         );
         let base = RenderKit::new(
             core,
@@ -312,6 +314,7 @@ impl ShaderManager for FluidShader {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass2"),
+                core.pipeline_cache.as_ref(),
             );
             self.base.renderer = Renderer::new(
                 &core.device,
@@ -320,6 +323,7 @@ impl ShaderManager for FluidShader {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass1"),
+                core.pipeline_cache.as_ref(),
             );
         }
         if self.base.export_manager.is_exporting() {
@@ -356,7 +360,7 @@ impl ShaderManager for FluidShader {
         let _video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let _webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
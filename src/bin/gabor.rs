@@ -294,6 +294,7 @@ impl ShaderManager for GaborShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/gabor.wgsl"),
             cs_module.clone(),
             "Splat",
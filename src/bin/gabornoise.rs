@@ -259,6 +259,7 @@ impl ShaderManager for Gabor {
 
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -301,6 +302,7 @@ impl ShaderManager for Gabor {
                 core.config.format,
                 &pipeline_layout,
                 None,
+                core.pipeline_cache.as_ref(),
             );
         }
         if self.base.export_manager.is_exporting() {
@@ -314,7 +316,7 @@ impl ShaderManager for Gabor {
         let _video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let _webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
@@ -345,6 +345,7 @@ impl ShaderManager for JfaShader {
 
         let hot_reload = ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/jfa.wgsl"),
             cs_module.clone(),
             "buffer_a",
@@ -335,6 +335,7 @@ impl ShaderManager for Lich {
         ];
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -346,6 +347,7 @@ impl ShaderManager for Lich {
             core.config.format,
             &pipeline_layout,
             Some("fs_pass2"),
+            core.pipeline_cache.as_ref(),
         );
 
         let renderer_pass3 = Renderer::new(
@@ -355,6 +357,7 @@ impl ShaderManager for Lich {
             core.config.format,
             &pipeline_layout,
             Some("fs_pass3"),
+            core.pipeline_cache.as_ref(),
         );
 
         Self {
@@ -394,6 +397,7 @@ impl ShaderManager for Lich {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass1"),
+                core.pipeline_cache.as_ref(),
             );
     
             self.renderer_pass2 = Renderer::new(
@@ -403,6 +407,7 @@ impl ShaderManager for Lich {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass2"),
+                core.pipeline_cache.as_ref(),
             );
     
             self.renderer_pass3 = Renderer::new(
@@ -412,6 +417,7 @@ impl ShaderManager for Lich {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass3"),
+                core.pipeline_cache.as_ref(),
             );
         }
     
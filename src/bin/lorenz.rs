@@ -433,6 +433,7 @@ impl ShaderManager for LorenzShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/lorenz.wgsl"),
             cs_module.clone(),
             "Splat",
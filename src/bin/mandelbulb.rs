@@ -417,13 +417,11 @@ impl ShaderManager for MandelbulbShader {
         let shader_source = std::fs::read_to_string("shaders/mandelbulb.wgsl")
             .unwrap_or_else(|_| include_str!("../../shaders/mandelbulb.wgsl").to_string());
         
-        let cs_module = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Mandelbulb Compute Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-        });
+        let cs_module = core.create_shader_module(&shader_source, "Mandelbulb Compute Shader");
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/mandelbulb.wgsl"),
             cs_module.clone(),
             "main",
@@ -465,7 +463,7 @@ impl ShaderManager for MandelbulbShader {
             module: &cs_module,
             entry_point: Some("main"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let view_output = output_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -524,7 +522,7 @@ impl ShaderManager for MandelbulbShader {
                 module: &new_shader,
                 entry_point: Some("main"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.should_reset_accumulation = true;
@@ -243,6 +243,7 @@ impl ShaderManager for MatrixShader {
 
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -285,6 +286,7 @@ impl ShaderManager for MatrixShader {
                 core.config.format,
                 &pipeline_layout,
                 None,
+                core.pipeline_cache.as_ref(),
             );
         }
         if self.base.export_manager.is_exporting() {
@@ -299,7 +301,7 @@ impl ShaderManager for MatrixShader {
         let _video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let _webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
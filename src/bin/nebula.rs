@@ -326,6 +326,7 @@ impl ShaderManager for NebulaShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/nebula.wgsl"),
             shader_module.clone(),
             "volumetric_render",
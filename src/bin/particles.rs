@@ -252,6 +252,7 @@ impl ShaderManager for ParticleShader {
         });
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/particles.wgsl"),
             cs_module.clone(),
             "main_image",
@@ -1,4 +1,5 @@
-use cuneus::{Core, ShaderManager, UniformProvider, UniformBinding, RenderKit, ShaderControls, ExportManager};
+use cuneus::{Core, ShaderManager, UniformProvider, UniformBinding, RenderKit, ShaderControls, ExportManager, HdriUniform};
+use wgpu::util::DeviceExt;
 use cuneus::compute::{create_bind_group_layout, BindGroupLayoutType};
 use winit::event::WindowEvent;
 use std::path::PathBuf;
@@ -209,6 +210,9 @@ struct PathTracingShader {
     // Bind groups
     compute_bind_group: wgpu::BindGroup,
     
+    // Exposure/gamma for non-HDRI backgrounds (pass-through)
+    default_background_params: wgpu::Buffer,
+    
     // Atomic buffer for accumulation
     atomic_buffer: cuneus::AtomicBuffer,
     
@@ -248,6 +252,7 @@ impl PathTracingShader {
         
         let background_view;
         let background_sampler;
+        let mut background_params = &self.default_background_params;
         
         if self.base.using_video_texture {
             if let Some(ref video_manager) = self.base.video_texture_manager {
@@ -271,6 +276,11 @@ impl PathTracingShader {
             } else {
                 panic!("No texture available for background");
             }
+        } else if let Some(ref hdri) = self.base.hdri_texture {
+            // Linear float data so the environment keeps its full dynamic range
+            background_view = &hdri.view;
+            background_sampler = &hdri.sampler;
+            background_params = &hdri.uniform.buffer;
         } else if let Some(ref texture_manager) = self.base.texture_manager {
             background_view = &texture_manager.view;
             background_sampler = &texture_manager.sampler;
@@ -296,6 +306,10 @@ impl PathTracingShader {
                     binding: 2,
                     resource: wgpu::BindingResource::Sampler(background_sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: background_params.as_entire_binding(),
+                },
             ],
         });
         
@@ -477,10 +491,27 @@ impl ShaderManager for PathTracingShader {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
             label: Some("pathtracing_compute_output_layout"),
         });
         
+        // gamma 2.2 is the identity in the shader, so plain textures are sampled as-is
+        let default_background_params = core.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Path Tracing Background Params"),
+            contents: HdriUniform::default().as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        
         let buffer_size = core.config.width * core.config.height * 3;
         
         let atomic_buffer = cuneus::AtomicBuffer::new(
@@ -534,13 +565,11 @@ impl ShaderManager for PathTracingShader {
         let shader_source = std::fs::read_to_string("shaders/pathtracing.wgsl")
             .unwrap_or_else(|_| include_str!("../../shaders/pathtracing.wgsl").to_string());
         
-        let cs_module = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Path Tracing Compute Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-        });
+        let cs_module = core.create_shader_module(&shader_source, "Path Tracing Compute Shader");
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/pathtracing.wgsl"),
             cs_module.clone(),
             "main",
@@ -585,7 +614,7 @@ impl ShaderManager for PathTracingShader {
             module: &cs_module,
             entry_point: Some("main"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
+            cache: core.pipeline_cache.as_ref(),
         });
         
         let view_output = output_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -604,6 +633,10 @@ impl ShaderManager for PathTracingShader {
                     binding: 2,
                     resource: wgpu::BindingResource::Sampler(&base.texture_manager.as_ref().unwrap().sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: default_background_params.as_entire_binding(),
+                },
             ],
             label: Some("Path Tracing Compute Bind Group"),
         });
@@ -619,6 +652,7 @@ impl ShaderManager for PathTracingShader {
             time_bind_group_layout,
             params_bind_group_layout,
             compute_bind_group,
+            default_background_params,
             atomic_buffer,
             frame_count: 0,
             hot_reload,
@@ -652,7 +686,7 @@ impl ShaderManager for PathTracingShader {
                 module: &new_shader,
                 entry_point: Some("main"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             
             self.should_reset_accumulation = true;
@@ -661,7 +695,7 @@ impl ShaderManager for PathTracingShader {
         let video_updated = if self.base.using_video_texture {
             self.base.update_video_texture(core, &core.queue)
         } else {
-            false
+            self.base.update_image_sequence(core)
         };
         let webcam_updated = if self.base.using_webcam_texture {
             self.base.update_webcam_texture(core, &core.queue)
@@ -335,6 +335,7 @@ impl ShaderManager for Neural2Shader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/plasma.wgsl"),
             cs_module.clone(),
             "Splat",
@@ -433,6 +433,7 @@ impl ShaderManager for QuadraticBulbShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/quadraticbulb.wgsl"),
             cs_module.clone(),
             "main",
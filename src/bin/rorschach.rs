@@ -305,6 +305,7 @@ impl ShaderManager for Shader {
         });
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/rorschach.wgsl"),
             shader_module.clone(),
             "Splat",
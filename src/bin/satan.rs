@@ -294,6 +294,7 @@ impl ShaderManager for Satan {
         ];
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module,
             fs_module,
@@ -305,6 +306,7 @@ impl ShaderManager for Satan {
             core.config.format,
             &pipeline_layout,
             Some("fs_pass2"),
+            core.pipeline_cache.as_ref(),
         );
 
         let renderer_pass3 = Renderer::new(
@@ -314,6 +316,7 @@ impl ShaderManager for Satan {
             core.config.format,
             &pipeline_layout,
             Some("fs_pass3"),
+            core.pipeline_cache.as_ref(),
         );
 
         Self {
@@ -350,6 +353,7 @@ impl ShaderManager for Satan {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass1"),
+                core.pipeline_cache.as_ref(),
             );
     
             self.renderer_pass2 = Renderer::new(
@@ -359,6 +363,7 @@ impl ShaderManager for Satan {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass2"),
+                core.pipeline_cache.as_ref(),
             );
     
             self.renderer_pass3 = Renderer::new(
@@ -368,6 +373,7 @@ impl ShaderManager for Satan {
                 core.config.format,
                 &pipeline_layout,
                 Some("fs_pass3"),
+                core.pipeline_cache.as_ref(),
             );
         }
     
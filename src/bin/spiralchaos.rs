@@ -288,6 +288,7 @@ impl ShaderManager for SpiralShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/spiralchaos.wgsl"),
             cs_module.clone(),
             "Splat",
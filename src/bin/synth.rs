@@ -1,6 +1,6 @@
 // This example demonstrates a how to generate audio using cunes via compute shaders
-use cuneus::{Core, ShaderApp, ShaderManager, RenderKit, UniformProvider, UniformBinding, ShaderControls};
-use cuneus::audio::SynthesisManager;
+use cuneus::{Core, ShaderApp, ShaderManager, RenderKit, UniformProvider, UniformBinding, ShaderControls, ExportManager};
+use cuneus::audio::{SynthesisManager, SynthConfig, AdsrConfig, FilterConfig, UnisonConfig, DelayConfig, ReverbConfig};
 use cuneus::compute::{ComputeShaderConfig, COMPUTE_TEXTURE_FORMAT_RGBA16};
 use winit::event::*;
 use std::path::PathBuf;
@@ -36,10 +36,7 @@ struct SynthParams {
     decay_time: f32,
     sustain_level: f32,
     release_time: f32,
-    fade_speed_initial: f32,
-    fade_speed_sustain: f32,
-    fade_speed_tail: f32,
-    _padding1: u32,
+    _padding: [u32; 3],
     key_states: [[f32; 4]; 3],
     key_decay: [[f32; 4]; 3],
 }
@@ -55,10 +52,215 @@ struct SynthManager {
     params_uniform: UniformBinding<SynthParams>,
     gpu_synthesis: Option<SynthesisManager>,
     key_press_times: [Option<std::time::Instant>; 9],
+    // Latest GPU-computed frequency per key
+    key_frequencies: [f32; 9],
 }
 
+// Synth voice used for the background beat, after the 9 key voices
+const BEAT_VOICE: usize = 9;
+
 impl SynthManager {
     fn update_synthesis_visualization(&mut self, _queue: &wgpu::Queue) {}
+
+    fn read_gpu_samples(&self, core: &Core) -> Option<Vec<f32>> {
+        let compute_shader = self.base.compute_shader.as_ref()?;
+        pollster::block_on(compute_shader.read_audio_samples(&core.device, &core.queue)).ok()
+    }
+
+    /// Follows the shader's frequencies, waveform and background beat
+    fn apply_gpu_samples(&mut self, gpu_samples: &[f32]) {
+        if gpu_samples.len() < 30 { // Need at least 30 values (3 base + 9 frequencies + 9 envelopes + 9 effects)
+            return;
+        }
+        let waveform_type = gpu_samples[2] as u32;
+        
+        // Extract all 9 GPU-computed frequencies (indices 3-11)
+        let mut shader_frequencies = [440.0; 9];
+        shader_frequencies.copy_from_slice(&gpu_samples[3..12]);
+        
+        let beat_amplitude = gpu_samples[21];
+        let beat_frequency = gpu_samples[22];
+        let amplitude = self.params_uniform.data.volume * 0.4;
+        self.key_frequencies = shader_frequencies;
+        
+        if let Some(ref mut synth) = self.gpu_synthesis {
+            // Update global waveform type from GPU shader
+            synth.update_waveform(waveform_type);
+            
+            // Follow the SHADER-GENERATED frequencies on held keys; note on/off
+            // happens on key events and the synth's envelopes handle the fades
+            for i in 0..9 {
+                if self.key_press_times[i].is_some() {
+                    synth.set_voice(i, shader_frequencies[i], amplitude, true);
+                }
+            }
+            
+            // Background beat with GPU-generated frequency
+            let beat_active = beat_amplitude > 0.01;
+            let beat_amp = if beat_active { beat_amplitude * 0.5 } else { 0.0 };
+            synth.set_voice(BEAT_VOICE, beat_frequency, beat_amp, beat_active);
+        }
+    }
+
+    /// Runs the compute shader at export time `time` without presenting anything
+    fn dispatch_at(&mut self, core: &Core, time: f32) {
+        let fps = self.base.export_manager.settings().fps.max(1);
+        self.base.update_compute_shader_time(time, 1.0 / fps as f32, &core.queue);
+        self.params_uniform.update(&core.queue);
+        let mut encoder = core.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Synth Export Encoder"),
+        });
+        self.base.dispatch_compute_shader(&mut encoder, core);
+        core.queue.submit(Some(encoder.finish()));
+    }
+
+    fn capture_frame(&mut self, core: &Core) -> Result<Vec<u8>, wgpu::SurfaceError> {
+        let settings = self.base.export_manager.settings();
+        let (capture_texture, output_buffer) = self.base.create_capture_texture(
+            &core.device,
+            settings.width,
+            settings.height
+        );
+        
+        let align = 256;
+        let unpadded_bytes_per_row = settings.width * 4;
+        let padding = (align - unpadded_bytes_per_row % align) % align;
+        let padded_bytes_per_row = unpadded_bytes_per_row + padding;
+        
+        let capture_view = capture_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = core.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+        
+        {
+            let mut render_pass = cuneus::Renderer::begin_render_pass(
+                &mut encoder,
+                &capture_view,
+                wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                Some("Capture Pass"),
+            );
+            
+            if let Some(compute_texture) = self.base.get_compute_output_texture() {
+                render_pass.set_pipeline(&self.base.renderer.render_pipeline);
+                render_pass.set_vertex_buffer(0, self.base.renderer.vertex_buffer.slice(..));
+                render_pass.set_bind_group(0, &compute_texture.bind_group, &[]);
+                render_pass.draw(0..4, 0..1);
+            }
+        }
+        
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &capture_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &output_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(settings.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: settings.width,
+                height: settings.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        
+        core.queue.submit(Some(encoder.finish()));
+        
+        let buffer_slice = output_buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).unwrap();
+        });
+        
+        let _ = core.device.poll(wgpu::PollType::Wait).unwrap();
+        rx.recv().unwrap().unwrap();
+        
+        let padded_data = buffer_slice.get_mapped_range().to_vec();
+        let mut unpadded_data = Vec::with_capacity((settings.width * settings.height * 4) as usize);
+        
+        for chunk in padded_data.chunks(padded_bytes_per_row as usize) {
+            unpadded_data.extend_from_slice(&chunk[..unpadded_bytes_per_row as usize]);
+        }
+        
+        Ok(unpadded_data)
+    }
+
+    /// Exports one frame together with the audio that plays during it. A single dispatch
+    /// sets the voices and draws the captured image.
+    fn handle_export(&mut self, core: &Core) {
+        if let Some((frame, time)) = self.base.export_manager.try_get_next_frame() {
+            self.dispatch_at(core, time);
+            if let Some(gpu_samples) = self.read_gpu_samples(core) {
+                self.apply_gpu_samples(&gpu_samples);
+            }
+            if let Some(ref mut synth) = self.gpu_synthesis {
+                self.base.push_synth_export_audio(synth, frame);
+            }
+            if let Ok(data) = self.capture_frame(core) {
+                let settings = self.base.export_manager.settings();
+                if let Err(e) = cuneus::save_frame(data, frame, settings) {
+                    eprintln!("Error saving frame: {:?}", e);
+                }
+            }
+        } else {
+            self.base.export_manager.complete_export();
+            if let Some(ref mut synth) = self.gpu_synthesis {
+                let _ = synth.start_gpu_synthesis();
+            }
+        }
+    }
+
+    // Mirror the UI parameters into the sample-rate envelope, filter and effects
+    fn sync_synth_config(&mut self) {
+        let params = self.params_uniform.data;
+        if let Some(ref mut synth) = self.gpu_synthesis {
+            synth.set_synth_config(SynthConfig {
+                envelope: AdsrConfig {
+                    attack: params.attack_time,
+                    decay: params.decay_time,
+                    sustain: params.sustain_level,
+                    release: params.release_time,
+                },
+                filter: FilterConfig {
+                    enabled: params.filter_cutoff < 0.95,
+                    // 20 Hz .. 20 kHz
+                    cutoff_hz: 20.0 * 1000f32.powf(params.filter_cutoff),
+                    resonance: params.filter_resonance,
+                    envelope_octaves: 1.0,
+                },
+                unison: UnisonConfig {
+                    voices: if params.chorus_depth > 0.01 { 3 } else { 1 },
+                    detune_cents: params.chorus_depth * 100.0,
+                    stereo_spread: 0.6,
+                },
+                delay: DelayConfig {
+                    time: params.delay_time,
+                    feedback: params.delay_feedback,
+                    mix: 0.3,
+                },
+                reverb: ReverbConfig {
+                    room_size: 0.8,
+                    damping: 0.5,
+                    mix: params.reverb_mix,
+                },
+            });
+        }
+    }
+
+    fn key_pressed(&mut self, index: usize, pressed: bool) {
+        let amplitude = self.params_uniform.data.volume * 0.4;
+        let frequency = self.key_frequencies[index];
+        if let Some(ref mut synth) = self.gpu_synthesis {
+            synth.set_voice(index, frequency, amplitude, pressed);
+        }
+    }
     
     fn set_key_state(&mut self, key_index: usize, state: f32) {
         if key_index < 9 {
@@ -163,10 +365,7 @@ impl ShaderManager for SynthManager {
                 decay_time: 0.6,
                 sustain_level: 0.6,
                 release_time: 1.2,
-                fade_speed_initial: 0.92,
-                fade_speed_sustain: 0.96,
-                fade_speed_tail: 0.98,
-                _padding1: 0,
+                _padding: [0; 3],
                 key_states: [[0.0; 4]; 3],
                 key_decay: [[0.0; 4]; 3],
             },
@@ -199,6 +398,8 @@ impl ShaderManager for SynthManager {
         
         let gpu_synthesis = match SynthesisManager::new() {
             Ok(mut synth) => {
+                // The shader shapes the beat itself; the key envelope's release would smear it
+                synth.set_voice_envelope(BEAT_VOICE, Some(AdsrConfig::pass_through()));
                 if let Err(_e) = synth.start_gpu_synthesis() {
                     None
                 } else {
@@ -211,12 +412,16 @@ impl ShaderManager for SynthManager {
         };
         
         
-        Self {
+        let mut synth = Self {
             base,
             params_uniform,
             gpu_synthesis,
             key_press_times: [None; 9],
-        }
+            // Octave 4 until the first GPU read
+            key_frequencies: [261.63, 293.66, 329.63, 349.23, 392.00, 440.00, 493.88, 523.25, 587.33],
+        };
+        synth.sync_synth_config();
+        synth
     }
     
     fn update(&mut self, core: &Core) {
@@ -226,90 +431,28 @@ impl ShaderManager for SynthManager {
         let delta = 1.0 / 60.0;
         self.base.update_compute_shader_time(current_time, delta, &core.queue);
         
-        // Update key states for GPU shader envelope computation
-        let mut keys_updated = false;
+        // Envelopes run at sample rate in the synth; the shader just follows their level
         for i in 0..9 {
-            if let Some(_press_time) = self.key_press_times[i] {
-                // Key is currently held - let GPU handle envelope progression
-                self.set_key_decay(i, 1.0);
-                keys_updated = true;
-            } else {
-                // Key released - fade to silence
-                let current_decay = self.params_uniform.data.key_decay[i / 4][i % 4];
-                if current_decay > 0.005 {
-                    // Use customizable piano fade speeds see egui:
-                    let fade_speed = if current_decay > 0.8 { 
-                        self.params_uniform.data.fade_speed_initial 
-                    } else if current_decay > 0.4 {
-                        self.params_uniform.data.fade_speed_sustain 
-                    } else {
-                        self.params_uniform.data.fade_speed_tail
-                    };
-                    let new_decay = current_decay * fade_speed;
-                    self.set_key_decay(i, new_decay);
-                    keys_updated = true;
-                } else {
-                    // Fade complete
-                    self.set_key_state(i, 0.0);
-                    self.set_key_decay(i, 0.0);
-                }
-            }
+            let level = match self.gpu_synthesis {
+                Some(ref synth) => synth.voice_envelope(i),
+                None => if self.key_press_times[i].is_some() { 1.0 } else { 0.0 },
+            };
+            self.set_key_decay(i, level);
         }
+        self.params_uniform.update(&core.queue);
         
-        if keys_updated {
-            self.params_uniform.update(&core.queue);
-        }
         
+        if self.base.export_manager.is_exporting() {
+            self.handle_export(core);
+            // The export drives the voices until it completes
+            return;
+        }
         
         // Read GPU shader-generated audio parameters with per-voice envelope amplitudes
         // Check every "X" (in here 5) frames responsiveness
         if self.base.time_uniform.data.frame % 5 == 0 {
-            if let Some(compute_shader) = &self.base.compute_shader {
-                if let Ok(gpu_samples) = pollster::block_on(compute_shader.read_audio_samples(&core.device, &core.queue)) {
-                    if gpu_samples.len() >= 30 { // Need at least 30 values (3 base + 9 frequencies + 9 envelopes + 9 effects)
-                        let waveform_type = gpu_samples[2] as u32;
-                        
-                        // Extract all 9 GPU-computed frequencies (indices 3-11)
-                        let mut shader_frequencies = [440.0; 9];
-                        for i in 0..9 {
-                            shader_frequencies[i] = gpu_samples[3 + i];
-                        }
-                        
-                        // Extract all 9 GPU-computed envelope amplitudes (indices 12-20)
-                        let mut envelope_amplitudes = [0.0; 9];
-                        for i in 0..9 {
-                            envelope_amplitudes[i] = gpu_samples[12 + i];
-                        }
-                        
-                        let beat_amplitude = gpu_samples[21];
-                        let beat_frequency = gpu_samples[22];
-                        
-                        if let Some(ref mut synth) = self.gpu_synthesis {
-                            // Update global waveform type from GPU shader
-                            synth.update_waveform(waveform_type);
-                            
-                            // Control individual voices using SHADER-GENERATED frequencies
-                            for i in 0..9 {
-                                let frequency = shader_frequencies[i];
-                                let gpu_envelope_amplitude = envelope_amplitudes[i];
-                                
-                                // Check if voice should be active based on GPU envelope
-                                let active = gpu_envelope_amplitude > 0.001;
-                                
-                                // Use GPU-computed envelope amplitude directly for fades
-                                synth.set_voice(i, frequency, gpu_envelope_amplitude, active);
-                            }
-                            
-                            // Background beat with GPU-generated frequency
-                            let beat_active = beat_amplitude > 0.01;
-                            let beat_amp = if beat_active { beat_amplitude * 0.5 } else { 0.0 };
-                            // Use a separate voice slot for beat (voice 8 is still available)
-                            if shader_frequencies.len() > 8 {
-                                synth.set_voice(8, beat_frequency, beat_amp, beat_active);
-                            }
-                        }
-                    }
-                }
+            if let Some(gpu_samples) = self.read_gpu_samples(core) {
+                self.apply_gpu_samples(&gpu_samples);
             }
         }
         
@@ -332,6 +475,8 @@ impl ShaderManager for SynthManager {
         let mut changed = false;
         let mut controls_request = self.base.controls.get_ui_request(&self.base.start_time, &core.size);
         controls_request.current_fps = Some(self.base.fps_tracker.fps());
+        let mut should_start_export = false;
+        let mut export_request = self.base.export_manager.get_ui_request();
         
         let full_output = if self.base.key_handler.show_ui {
             self.base.render_ui(core, |ctx| {
@@ -396,21 +541,6 @@ impl ShaderManager for SynthManager {
                                 changed |= ui.add(egui::Slider::new(&mut params.release_time, 0.01..=5.0).logarithmic(true).text("Release")).changed();
                             });
                         
-                        egui::CollapsingHeader::new("Response")
-                            .default_open(false)
-                            .show(ui, |ui| {
-                                changed |= ui.add(egui::Slider::new(&mut params.fade_speed_initial, 0.5..=0.999).text("Initial").custom_formatter(|n, _| format!("{:.3}", n))).changed();
-                                changed |= ui.add(egui::Slider::new(&mut params.fade_speed_sustain, 0.5..=0.999).text("Sustain").custom_formatter(|n, _| format!("{:.3}", n))).changed();
-                                changed |= ui.add(egui::Slider::new(&mut params.fade_speed_tail, 0.5..=0.999).text("Tail").custom_formatter(|n, _| format!("{:.3}", n))).changed();
-                                
-                                if ui.small_button("Reset").clicked() {
-                                    params.fade_speed_initial = 0.92;
-                                    params.fade_speed_sustain = 0.96;
-                                    params.fade_speed_tail = 0.98;
-                                    changed = true;
-                                }
-                            });
-                        
                         egui::CollapsingHeader::new("Filter")
                             .default_open(false)
                             .show(ui, |ui| {
@@ -432,6 +562,9 @@ impl ShaderManager for SynthManager {
                         
                         ui.separator();
                         ShaderControls::render_controls_widget(ui, &mut controls_request);
+                        
+                        ui.separator();
+                        should_start_export = ExportManager::render_export_ui_widget(ui, &mut export_request);
                     });
             })
         } else {
@@ -441,6 +574,7 @@ impl ShaderManager for SynthManager {
         if changed {
             self.params_uniform.data = params;
             self.params_uniform.update(&core.queue);
+            self.sync_synth_config();
         }
         
         let current_time = self.base.controls.get_time(&self.base.start_time);
@@ -475,7 +609,17 @@ impl ShaderManager for SynthManager {
             }
         }
         
+        self.base.export_manager.apply_ui_request(export_request);
         self.base.apply_control_request(controls_request);
+        
+        if should_start_export {
+            // Render the voices offline at the synth's own rate while the export runs
+            if let Some(ref mut synth) = self.gpu_synthesis {
+                self.base.export_manager.settings_mut().audio_sample_rate = synth.sample_rate();
+                let _ = synth.pause();
+            }
+            self.base.export_manager.start_export();
+        }
         self.base.handle_render_output(core, &view, full_output, &mut encoder);
         core.queue.submit(Some(encoder.finish()));
         output.present();
@@ -503,7 +647,7 @@ impl ShaderManager for SynthManager {
                             if self.key_press_times[index].is_none() {
                                 self.key_press_times[index] = Some(std::time::Instant::now());
                                 self.set_key_state(index, 1.0);
-                                self.set_key_decay(index, 1.0);
+                                self.key_pressed(index, true);
                                 self.params_uniform.update(&core.queue);
                             }
                             return true;
@@ -517,11 +661,11 @@ impl ShaderManager for SynthManager {
                         if key_index >= 1 && key_index <= 9 {
                             let index = (key_index - 1) as usize;
                             
-                            // Start fade-out process
+                            // Start the release stage of the voice's envelope
                             if self.key_press_times[index].is_some() {
-                                self.key_press_times[index] = None; // This triggers fade-out in update()
+                                self.key_press_times[index] = None;
                                 self.set_key_state(index, 0.0);
-                                // Keep current decay value for smooth fade
+                                self.key_pressed(index, false);
                                 self.params_uniform.update(&core.queue);
                             }
                             return true;
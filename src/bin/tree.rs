@@ -439,6 +439,7 @@ impl ShaderManager for Shader {
 
         let hot_reload = ShaderHotReload::new(
             core.device.clone(),
+            core.shader_cache.clone(),
             shader_paths,
             vs_module.clone(),
             fs_module.clone(),
@@ -451,6 +452,7 @@ impl ShaderManager for Shader {
             core.config.format,
             &standard_pipeline_layout,
             Some("fs_pass2"),
+            core.pipeline_cache.as_ref(),
         );
 
         // Pass 3 uses multi-texture layout
@@ -461,6 +463,7 @@ impl ShaderManager for Shader {
             core.config.format,
             &multi_texture_pipeline_layout,
             Some("fs_pass3"),
+            core.pipeline_cache.as_ref(),
         );
 
         // Pass 4 final 
@@ -471,6 +474,7 @@ impl ShaderManager for Shader {
             core.config.format,
             &standard_pipeline_layout,
             Some("fs_pass4"),
+            core.pipeline_cache.as_ref(),
         );
 
         Self {
@@ -525,6 +529,7 @@ impl ShaderManager for Shader {
                 core.config.format,
                 &standard_pipeline_layout,
                 Some("fs_pass1"),
+                core.pipeline_cache.as_ref(),
             );
     
             self.renderer_pass2 = Renderer::new(
@@ -534,6 +539,7 @@ impl ShaderManager for Shader {
                 core.config.format,
                 &standard_pipeline_layout,
                 Some("fs_pass2"),
+                core.pipeline_cache.as_ref(),
             );
     
             // Pass 3 with multi-texture layout
@@ -544,6 +550,7 @@ impl ShaderManager for Shader {
                 core.config.format,
                 &multi_texture_pipeline_layout,
                 Some("fs_pass3"),
+                core.pipeline_cache.as_ref(),
             );
 
             // Pass 4 for final output
@@ -554,6 +561,7 @@ impl ShaderManager for Shader {
                 core.config.format,
                 &standard_pipeline_layout,
                 Some("fs_pass4"),
+                core.pipeline_cache.as_ref(),
             );
         }
     
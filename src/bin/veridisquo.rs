@@ -1,6 +1,6 @@
-use cuneus::{Core, ShaderApp, ShaderManager, RenderKit, ShaderControls, UniformProvider, UniformBinding};
+use cuneus::{Core, ShaderApp, ShaderManager, RenderKit, ShaderControls, UniformProvider, UniformBinding, ExportManager};
 use cuneus::compute::{ComputeShaderConfig, COMPUTE_TEXTURE_FORMAT_RGBA16};
-use cuneus::audio::SynthesisManager;
+use cuneus::audio::{SynthesisManager, SynthConfig, AdsrConfig};
 use winit::event::*;
 use std::path::PathBuf;
 
@@ -33,6 +33,157 @@ struct VeridisQuo {
     song_params_uniform: UniformBinding<SongParams>,
 }
 
+impl VeridisQuo {
+    fn read_gpu_samples(&self, core: &Core) -> Option<Vec<f32>> {
+        let compute_shader = self.base.compute_shader.as_ref()?;
+        pollster::block_on(compute_shader.read_audio_samples(&core.device, &core.queue)).ok()
+    }
+
+    /// Sets the voices from the shader's audio buffer
+    fn apply_gpu_samples(&mut self, gpu_samples: &[f32]) {
+        if gpu_samples.len() < 3 + NUM_VOICES * 2 {
+            return;
+        }
+        let waveform_type = self.song_params_uniform.data.waveform_type;
+        
+        if let Some(ref mut synth) = self.audio_synthesis {
+            // Update the waveform type for all voices
+            synth.update_waveform(waveform_type);
+            
+            // Read melody and bass frequencies from shader's specific audio_buffer indices
+            // Melody: frequency at index 3, amplitude at index 4  
+            // Bass: frequency at index 5, amplitude at index 6
+            let melody_freq = gpu_samples[3];
+            let melody_amp = gpu_samples[4];
+            let bass_freq = gpu_samples[5]; 
+            let bass_amp = gpu_samples[6];
+            
+            // Voice 0: Melody
+            let melody_active = melody_amp > 0.01 && melody_freq > 10.0;
+            synth.set_voice(0, melody_freq, melody_amp, melody_active);
+            
+            // Voice 1: Bass  
+            let bass_active = bass_amp > 0.01 && bass_freq > 10.0;
+            synth.set_voice(1, bass_freq, bass_amp, bass_active);
+        }
+    }
+
+    /// Runs the compute shader at export time `time` without presenting anything
+    fn dispatch_at(&mut self, core: &Core, time: f32) {
+        let fps = self.base.export_manager.settings().fps.max(1);
+        self.base.update_compute_shader_time(time, 1.0 / fps as f32, &core.queue);
+        self.song_params_uniform.update(&core.queue);
+        let mut encoder = core.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Veridis Quo Export Encoder"),
+        });
+        self.base.dispatch_compute_shader(&mut encoder, core);
+        core.queue.submit(Some(encoder.finish()));
+    }
+
+    fn capture_frame(&mut self, core: &Core) -> Result<Vec<u8>, wgpu::SurfaceError> {
+        let settings = self.base.export_manager.settings();
+        let (capture_texture, output_buffer) = self.base.create_capture_texture(
+            &core.device,
+            settings.width,
+            settings.height
+        );
+        
+        let align = 256;
+        let unpadded_bytes_per_row = settings.width * 4;
+        let padding = (align - unpadded_bytes_per_row % align) % align;
+        let padded_bytes_per_row = unpadded_bytes_per_row + padding;
+        
+        let capture_view = capture_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = core.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+        
+        {
+            let mut render_pass = cuneus::Renderer::begin_render_pass(
+                &mut encoder,
+                &capture_view,
+                wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                Some("Capture Pass"),
+            );
+            
+            if let Some(compute_texture) = self.base.get_compute_output_texture() {
+                render_pass.set_pipeline(&self.base.renderer.render_pipeline);
+                render_pass.set_vertex_buffer(0, self.base.renderer.vertex_buffer.slice(..));
+                render_pass.set_bind_group(0, &compute_texture.bind_group, &[]);
+                render_pass.draw(0..4, 0..1);
+            }
+        }
+        
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &capture_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &output_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(settings.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: settings.width,
+                height: settings.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        
+        core.queue.submit(Some(encoder.finish()));
+        
+        let buffer_slice = output_buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).unwrap();
+        });
+        
+        let _ = core.device.poll(wgpu::PollType::Wait).unwrap();
+        rx.recv().unwrap().unwrap();
+        
+        let padded_data = buffer_slice.get_mapped_range().to_vec();
+        let mut unpadded_data = Vec::with_capacity((settings.width * settings.height * 4) as usize);
+        
+        for chunk in padded_data.chunks(padded_bytes_per_row as usize) {
+            unpadded_data.extend_from_slice(&chunk[..unpadded_bytes_per_row as usize]);
+        }
+        
+        Ok(unpadded_data)
+    }
+
+    /// Exports one frame together with the audio that plays during it. A single dispatch
+    /// sets the voices and draws the captured image.
+    fn handle_export(&mut self, core: &Core) {
+        if let Some((frame, time)) = self.base.export_manager.try_get_next_frame() {
+            self.dispatch_at(core, time);
+            if let Some(gpu_samples) = self.read_gpu_samples(core) {
+                self.apply_gpu_samples(&gpu_samples);
+            }
+            if let Some(ref mut synth) = self.audio_synthesis {
+                self.base.push_synth_export_audio(synth, frame);
+            }
+            if let Ok(data) = self.capture_frame(core) {
+                let settings = self.base.export_manager.settings();
+                if let Err(e) = cuneus::save_frame(data, frame, settings) {
+                    eprintln!("Error saving frame: {:?}", e);
+                }
+            }
+        } else {
+            self.base.export_manager.complete_export();
+            if let Some(ref mut synth) = self.audio_synthesis {
+                let _ = synth.start_gpu_synthesis();
+            }
+        }
+    }
+}
+
 impl ShaderManager for VeridisQuo {
     fn init(core: &Core) -> Self {
         let texture_bind_group_layout = core.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -142,6 +293,8 @@ impl ShaderManager for VeridisQuo {
         
         let audio_synthesis = match SynthesisManager::new() {
             Ok(mut synth) => {
+                // The shader shapes the notes; the synth's own envelope would stack on top
+                synth.set_synth_config(SynthConfig { envelope: AdsrConfig::pass_through(), ..Default::default() });
                 if let Err(_e) = synth.start_gpu_synthesis() {
                     None
                 } else {
@@ -168,34 +321,14 @@ impl ShaderManager for VeridisQuo {
         self.base.fps_tracker.update();
         
         self.song_params_uniform.update(&core.queue);
+        if self.base.export_manager.is_exporting() {
+            self.handle_export(core);
+            // The export drives the voices until it completes
+            return;
+        }
         if self.base.time_uniform.data.frame % 2 == 0 {
-            if let Some(compute_shader) = &self.base.compute_shader {
-                if let Ok(gpu_samples) = pollster::block_on(compute_shader.read_audio_samples(&core.device, &core.queue)) {
-                    if gpu_samples.len() >= 3 + NUM_VOICES * 2 {
-                        let waveform_type = self.song_params_uniform.data.waveform_type;
-                        
-                        if let Some(ref mut synth) = self.audio_synthesis {
-                            // Update the waveform type for all voices
-                            synth.update_waveform(waveform_type);
-                            
-                            // Read melody and bass frequencies from shader's specific audio_buffer indices
-                            // Melody: frequency at index 3, amplitude at index 4  
-                            // Bass: frequency at index 5, amplitude at index 6
-                            let melody_freq = gpu_samples[3];
-                            let melody_amp = gpu_samples[4];
-                            let bass_freq = gpu_samples[5]; 
-                            let bass_amp = gpu_samples[6];
-                            
-                            // Voice 0: Melody
-                            let melody_active = melody_amp > 0.01 && melody_freq > 10.0;
-                            synth.set_voice(0, melody_freq, melody_amp, melody_active);
-                            
-                            // Voice 1: Bass  
-                            let bass_active = bass_amp > 0.01 && bass_freq > 10.0;
-                            synth.set_voice(1, bass_freq, bass_amp, bass_active);
-                        }
-                    }
-                }
+            if let Some(gpu_samples) = self.read_gpu_samples(core) {
+                self.apply_gpu_samples(&gpu_samples);
             }
         }
     }
@@ -209,7 +342,8 @@ impl ShaderManager for VeridisQuo {
             &core.size
         );
         controls_request.current_fps = Some(self.base.fps_tracker.fps());
-        
+        let mut should_start_export = false;
+        let mut export_request = self.base.export_manager.get_ui_request();
         
         let full_output = if self.base.key_handler.show_ui {
             self.base.render_ui(core, |ctx| {
@@ -256,14 +390,27 @@ impl ShaderManager for VeridisQuo {
                             });
                         
                         ShaderControls::render_controls_widget(ui, &mut controls_request);
+                        
+                        ui.separator();
+                        should_start_export = ExportManager::render_export_ui_widget(ui, &mut export_request);
                     });
             })
         } else {
             self.base.render_ui(core, |_ctx| {})
         };
         
+        self.base.export_manager.apply_ui_request(export_request);
         self.base.apply_control_request(controls_request);
         
+        if should_start_export {
+            // Render the voices offline at the synth's own rate while the export runs
+            if let Some(ref mut synth) = self.audio_synthesis {
+                self.base.export_manager.settings_mut().audio_sample_rate = synth.sample_rate();
+                let _ = synth.pause();
+            }
+            self.base.export_manager.start_export();
+        }
+        
         let mut encoder = core.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Veridis Quo Render Encoder"),
         });
@@ -168,6 +168,7 @@ impl ShaderManager for VolumeShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/volumepassage.wgsl"),
             cs_module.clone(),
             "main",
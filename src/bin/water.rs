@@ -473,6 +473,7 @@ impl ShaderManager for WaterShader {
         
         let hot_reload = cuneus::ShaderHotReload::new_compute(
             core.device.clone(),
+            core.shader_cache.clone(),
             PathBuf::from("shaders/water.wgsl"),
             cs_module.clone(),
             "main",
@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+
+/// Environment variable used to override the cache directory.
+/// Set it to `off` (or `0`) to disable on-disk caching entirely.
+pub const CACHE_DIR_ENV: &str = "CUNEUS_CACHE_DIR";
+pub const DEFAULT_CACHE_DIR: &str = ".cuneus_cache";
+
+// Bumped whenever the serialized module layout may change (naga upgrades etc.)
+// so stale entries are never deserialized.
+const SHADER_CACHE_VERSION: &str = "naga-25";
+const SHADER_CACHE_EXTENSION: &str = "naga";
+
+/// Stable 64-bit FNV-1a hash, used to key cache entries by shader content.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// On-disk cache of parsed and validated naga modules keyed by WGSL content.
+///
+/// Large shaders (pathtracers, CNNs, FFTs...) spend a noticeable amount of
+/// startup time in the WGSL front-end. With a warm cache the module is handed
+/// to wgpu as naga IR and WGSL parsing is skipped entirely.
+#[derive(Debug, Clone)]
+pub struct ShaderCache {
+    dir: Option<PathBuf>,
+}
+
+impl Default for ShaderCache {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl ShaderCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: Some(dir.into()) }
+    }
+
+    pub fn disabled() -> Self {
+        Self { dir: None }
+    }
+
+    /// Uses `CUNEUS_CACHE_DIR` when set, otherwise `.cuneus_cache` in the working directory.
+    pub fn from_env() -> Self {
+        match std::env::var(CACHE_DIR_ENV) {
+            Ok(value) if value.eq_ignore_ascii_case("off") || value == "0" => Self::disabled(),
+            Ok(value) if !value.is_empty() => Self::new(value),
+            _ => Self::new(DEFAULT_CACHE_DIR),
+        }
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    fn module_path(&self, source: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let mut hasher_input = Vec::with_capacity(SHADER_CACHE_VERSION.len() + source.len());
+        hasher_input.extend_from_slice(SHADER_CACHE_VERSION.as_bytes());
+        hasher_input.extend_from_slice(source.as_bytes());
+        let hash = content_hash(&hasher_input);
+        Some(dir.join("shaders").join(format!("{:016x}.{}", hash, SHADER_CACHE_EXTENSION)))
+    }
+
+    fn load_module(path: &Path) -> Option<naga::Module> {
+        let bytes = fs::read(path).ok()?;
+        match bincode::deserialize(&bytes) {
+            Ok(module) => Some(module),
+            Err(e) => {
+                warn!("Discarding corrupt shader cache entry {}: {}", path.display(), e);
+                let _ = fs::remove_file(path);
+                None
+            }
+        }
+    }
+
+    fn store_module(path: &Path, module: &naga::Module) {
+        let bytes = match bincode::serialize(module) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to serialize shader module: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = write_atomic(path, &bytes) {
+            warn!("Failed to write shader cache entry {}: {}", path.display(), e);
+        }
+    }
+
+    fn parse_and_validate(source: &str, label: &str) -> Option<naga::Module> {
+        let module = match naga::front::wgsl::parse_str(source) {
+            Ok(module) => module,
+            Err(e) => {
+                warn!("{}: {}", label, e.emit_to_string(source));
+                return None;
+            }
+        };
+        let mut validator = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        );
+        if let Err(e) = validator.validate(&module) {
+            warn!("{}: {}", label, e.emit_to_string(source));
+            return None;
+        }
+        Some(module)
+    }
+
+    /// Returns the shader source to hand to wgpu: cached naga IR when available,
+    /// freshly parsed IR (which is then cached) otherwise. Sources that fail to
+    /// parse or validate are passed through as WGSL so wgpu reports the error
+    /// exactly as it would without the cache.
+    pub fn shader_source(&self, source: &str, label: &str) -> wgpu::ShaderSource<'static> {
+        let Some(path) = self.module_path(source) else {
+            return wgpu::ShaderSource::Wgsl(Cow::Owned(source.to_string()));
+        };
+
+        if let Some(module) = Self::load_module(&path) {
+            return wgpu::ShaderSource::Naga(Cow::Owned(module));
+        }
+
+        match Self::parse_and_validate(source, label) {
+            Some(module) => {
+                Self::store_module(&path, &module);
+                wgpu::ShaderSource::Naga(Cow::Owned(module))
+            }
+            None => wgpu::ShaderSource::Wgsl(Cow::Owned(source.to_string())),
+        }
+    }
+
+    pub fn create_shader_module(
+        &self,
+        device: &wgpu::Device,
+        source: &str,
+        label: &str,
+    ) -> wgpu::ShaderModule {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: self.shader_source(source, label),
+        })
+    }
+
+    /// Removes every cached shader module. The pipeline cache blob is left alone.
+    pub fn clear(&self) -> std::io::Result<()> {
+        if let Some(dir) = &self.dir {
+            let shaders_dir = dir.join("shaders");
+            if shaders_dir.exists() {
+                fs::remove_dir_all(&shaders_dir)?;
+                info!("Cleared shader cache at {}", shaders_dir.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Path of the wgpu pipeline cache blob for the given adapter, if the backend supports one.
+pub fn pipeline_cache_path(cache: &ShaderCache, adapter_info: &wgpu::AdapterInfo) -> Option<PathBuf> {
+    let dir = cache.dir()?;
+    let key = wgpu::util::pipeline_cache_key(adapter_info)?;
+    Some(dir.join(key))
+}
+
+// Write to a temporary file first so a crash mid-write never leaves a truncated entry.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, bytes)?;
+    fs::rename(&temp_path, path)
+}
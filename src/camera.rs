@@ -0,0 +1,742 @@
+// Reusable camera controllers (orbit, first-person fly, 2D pan/zoom) driven by `MouseTracker`
+// and `KeyboardState`, with damping, path recording/replay and a shared `CameraUniform`.
+use std::f32::consts::PI;
+use std::path::Path;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::KeyCode;
+use crate::{KeyboardState, MouseTracker, UniformProvider};
+
+const MAX_PITCH: f32 = PI * 0.49;
+const WORLD_UP: [f32; 3] = [0.0, 1.0, 0.0];
+
+/// View, projection, their inverses and camera parameters, all matrices column-major.
+/// ```wgsl
+/// struct Camera {
+///     view: mat4x4<f32>, projection: mat4x4<f32>,
+///     inv_view: mat4x4<f32>, inv_projection: mat4x4<f32>,
+///     position: vec3<f32>, fov: f32,   // fov: vertical, radians (0 for orthographic)
+///     aspect: f32, near: f32, far: f32, ortho_height: f32,  // ortho_height: 0 for perspective
+/// }
+/// ```
+/// A primary ray for `uv` in -1..1 (y up) is
+/// `normalize((inv_view * vec4(((inv_projection * vec4(uv, 1.0, 1.0)).xyz), 0.0)).xyz)` from `position`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view: [[f32; 4]; 4],
+    pub projection: [[f32; 4]; 4],
+    pub inv_view: [[f32; 4]; 4],
+    pub inv_projection: [[f32; 4]; 4],
+    pub position: [f32; 3],
+    pub fov: f32,
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+    pub ortho_height: f32,
+}
+
+impl UniformProvider for CameraUniform {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        CameraUniform::new(&CameraPose::default(), CameraProjection::default(), 1.0)
+    }
+}
+
+/// How a camera projects; depth maps to 0..1 like wgpu expects.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CameraProjection {
+    /// Vertical field of view comes from `CameraPose::fov` (radians)
+    Perspective { near: f32, far: f32 },
+    /// Visible height in world units comes from `CameraPose::fov`
+    Orthographic { near: f32, far: f32 },
+}
+
+impl Default for CameraProjection {
+    fn default() -> Self {
+        CameraProjection::Perspective { near: 0.01, far: 1000.0 }
+    }
+}
+
+/// Where a camera is and what it looks at. This is what paths record, so any controller
+/// can replay any path.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraPose {
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    /// Vertical field of view in radians; for `PanZoomCamera` the visible height in world units
+    pub fov: f32,
+}
+
+impl Default for CameraPose {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0, 5.0],
+            target: [0.0, 0.0, 0.0],
+            fov: 45f32.to_radians(),
+        }
+    }
+}
+
+impl CameraUniform {
+    pub fn new(pose: &CameraPose, projection: CameraProjection, aspect: f32) -> Self {
+        let eye = pose.position;
+        let forward = direction(eye, pose.target).unwrap_or([0.0, 0.0, -1.0]);
+        let side = normalize(cross(forward, WORLD_UP)).unwrap_or([1.0, 0.0, 0.0]);
+        let up = cross(side, forward);
+        let view = [
+            [side[0], up[0], -forward[0], 0.0],
+            [side[1], up[1], -forward[1], 0.0],
+            [side[2], up[2], -forward[2], 0.0],
+            [-dot(side, eye), -dot(up, eye), dot(forward, eye), 1.0],
+        ];
+        let inv_view = [
+            [side[0], side[1], side[2], 0.0],
+            [up[0], up[1], up[2], 0.0],
+            [-forward[0], -forward[1], -forward[2], 0.0],
+            [eye[0], eye[1], eye[2], 1.0],
+        ];
+
+        let aspect = aspect.max(1e-6);
+        let (projection, inv_projection, fov, near, far, ortho_height) = match projection {
+            CameraProjection::Perspective { near, far } => {
+                let g = 1.0 / (pose.fov * 0.5).tan();
+                let a = far / (near - far);
+                let b = near * far / (near - far);
+                (
+                    [[g / aspect, 0.0, 0.0, 0.0], [0.0, g, 0.0, 0.0], [0.0, 0.0, a, -1.0], [0.0, 0.0, b, 0.0]],
+                    [[aspect / g, 0.0, 0.0, 0.0], [0.0, 1.0 / g, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0 / b], [0.0, 0.0, -1.0, a / b]],
+                    pose.fov,
+                    near,
+                    far,
+                    0.0,
+                )
+            }
+            CameraProjection::Orthographic { near, far } => {
+                let (h, w) = (pose.fov, pose.fov * aspect);
+                (
+                    [[2.0 / w, 0.0, 0.0, 0.0], [0.0, 2.0 / h, 0.0, 0.0], [0.0, 0.0, 1.0 / (near - far), 0.0], [0.0, 0.0, near / (near - far), 1.0]],
+                    [[w / 2.0, 0.0, 0.0, 0.0], [0.0, h / 2.0, 0.0, 0.0], [0.0, 0.0, near - far, 0.0], [0.0, 0.0, -near, 1.0]],
+                    0.0,
+                    near,
+                    far,
+                    h,
+                )
+            }
+        };
+
+        Self {
+            view,
+            projection,
+            inv_view,
+            inv_projection,
+            position: eye,
+            fov,
+            aspect,
+            near,
+            far,
+            ortho_height,
+        }
+    }
+}
+
+/// Shared interface of the controllers. Call `update` once per frame, then `uniform`.
+pub trait CameraController {
+    /// Reads mouse/keyboard input and advances the damping by `dt` seconds.
+    /// Returns true if the camera moved (e.g. to reset accumulation).
+    fn update(&mut self, mouse: &MouseTracker, keyboard: &KeyboardState, aspect: f32, dt: f32) -> bool;
+
+    /// Current (smoothed) pose
+    fn pose(&self) -> CameraPose;
+
+    /// Jumps to `pose` without smoothing, e.g. when replaying a `CameraPath`
+    fn set_pose(&mut self, pose: &CameraPose);
+
+    fn projection(&self) -> CameraProjection;
+
+    fn uniform(&self, aspect: f32) -> CameraUniform {
+        CameraUniform::new(&self.pose(), self.projection(), aspect)
+    }
+}
+
+/// Mouse deltas between frames, from the tracker's absolute position and wheel travel.
+#[derive(Default)]
+pub(crate) struct MouseDelta {
+    last_position: Option<[f32; 2]>,
+    last_pixels: [f32; 2],
+    last_wheel: f32,
+}
+
+impl MouseDelta {
+    /// (pixel delta, normalized delta, wheel delta, buttons)
+    pub(crate) fn read(&mut self, mouse: &MouseTracker) -> ([f32; 2], [f32; 2], f32, u32) {
+        let position = mouse.uniform.position;
+        let pixels = mouse.raw_position;
+        let (moved, pixel_moved) = match self.last_position {
+            Some(last) => (
+                [position[0] - last[0], position[1] - last[1]],
+                [pixels[0] - self.last_pixels[0], pixels[1] - self.last_pixels[1]],
+            ),
+            None => ([0.0; 2], [0.0; 2]),
+        };
+        self.last_position = Some(position);
+        self.last_pixels = pixels;
+        let wheel = mouse.wheel_travel - self.last_wheel;
+        self.last_wheel = mouse.wheel_travel;
+        (pixel_moved, moved, wheel, mouse.uniform.buttons[0])
+    }
+}
+
+pub(crate) const LEFT_BUTTON: u32 = 1;
+const RIGHT_BUTTON: u32 = 2;
+const MIDDLE_BUTTON: u32 = 4;
+
+/// Orbits `target`: left drag rotates, right or middle drag pans, the wheel zooms.
+/// Arrow keys rotate and `=`/`-` zoom.
+pub struct OrbitCamera {
+    pub target: [f32; 3],
+    pub distance: f32,
+    /// Radians; 0 looks down -Z
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Radians per pixel dragged
+    pub rotate_speed: f32,
+    /// Radians per second for the arrow keys
+    pub key_rotate_speed: f32,
+    /// Distance factor per wheel step
+    pub zoom_speed: f32,
+    /// Seconds to cover most of the way to the goal; 0 disables smoothing
+    pub damping: f32,
+    pub projection: CameraProjection,
+    current: OrbitState,
+    mouse: MouseDelta,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct OrbitState {
+    target: [f32; 3],
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        let mut camera = Self {
+            target: [0.0; 3],
+            distance: 5.0,
+            yaw: 0.0,
+            pitch: -0.3,
+            fov: 45f32.to_radians(),
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            rotate_speed: 0.005,
+            key_rotate_speed: 1.5,
+            zoom_speed: 0.1,
+            damping: 0.1,
+            projection: CameraProjection::default(),
+            current: OrbitState { target: [0.0; 3], distance: 5.0, yaw: 0.0, pitch: -0.3 },
+            mouse: MouseDelta::default(),
+        };
+        camera.snap();
+        camera
+    }
+}
+
+impl OrbitCamera {
+    pub fn new(target: [f32; 3], distance: f32) -> Self {
+        let mut camera = Self { target, distance, ..Default::default() };
+        camera.snap();
+        camera
+    }
+
+    fn goal(&self) -> OrbitState {
+        OrbitState { target: self.target, distance: self.distance, yaw: self.yaw, pitch: self.pitch }
+    }
+
+    /// Skips the remaining smoothing
+    pub fn snap(&mut self) {
+        self.current = self.goal();
+    }
+}
+
+impl CameraController for OrbitCamera {
+    fn update(&mut self, mouse: &MouseTracker, keyboard: &KeyboardState, _aspect: f32, dt: f32) -> bool {
+        let (pixels, moved, wheel, buttons) = self.mouse.read(mouse);
+        if buttons & LEFT_BUTTON != 0 {
+            self.yaw += pixels[0] * self.rotate_speed;
+            self.pitch -= pixels[1] * self.rotate_speed;
+        } else if buttons & (RIGHT_BUTTON | MIDDLE_BUTTON) != 0 {
+            let (side, up) = orbit_axes(self.yaw, self.pitch);
+            let scale = self.distance;
+            for i in 0..3 {
+                self.target[i] -= side[i] * moved[0] * scale - up[i] * moved[1] * scale;
+            }
+        }
+
+        self.yaw += key_axis(keyboard, KeyCode::ArrowRight, KeyCode::ArrowLeft) * self.key_rotate_speed * dt;
+        self.pitch += key_axis(keyboard, KeyCode::ArrowDown, KeyCode::ArrowUp) * self.key_rotate_speed * dt;
+        let zoom = wheel + key_axis(keyboard, KeyCode::Equal, KeyCode::Minus) * 4.0 * dt;
+        self.distance = (self.distance * (1.0 - self.zoom_speed).powf(zoom)).clamp(self.min_distance, self.max_distance);
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        let t = smoothing(self.damping, dt);
+        let before = self.current;
+        let goal = self.goal();
+        for i in 0..3 {
+            approach(&mut self.current.target[i], goal.target[i], t);
+        }
+        approach(&mut self.current.distance, goal.distance, t);
+        approach(&mut self.current.yaw, goal.yaw, t);
+        approach(&mut self.current.pitch, goal.pitch, t);
+        self.current != before
+    }
+
+    fn pose(&self) -> CameraPose {
+        let forward = forward_from_angles(self.current.yaw, self.current.pitch);
+        let target = self.current.target;
+        CameraPose {
+            position: [
+                target[0] - forward[0] * self.current.distance,
+                target[1] - forward[1] * self.current.distance,
+                target[2] - forward[2] * self.current.distance,
+            ],
+            target,
+            fov: self.fov,
+        }
+    }
+
+    fn set_pose(&mut self, pose: &CameraPose) {
+        let offset = sub(pose.target, pose.position);
+        self.target = pose.target;
+        self.distance = length(offset).max(self.min_distance);
+        if let Some(forward) = normalize(offset) {
+            (self.yaw, self.pitch) = angles_from_forward(forward);
+        }
+        self.fov = pose.fov;
+        self.snap();
+    }
+
+    fn projection(&self) -> CameraProjection {
+        self.projection
+    }
+}
+
+/// First-person camera: WASD moves, `E`/`Q` go up/down, Shift moves faster. Dragging with
+/// the left button looks around, or just moving the mouse when `mouse_look` is set.
+pub struct FlyCamera {
+    pub position: [f32; 3],
+    /// Radians; 0 looks down -Z
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+    /// Units per second
+    pub speed: f32,
+    /// Speed multiplier while Shift is held
+    pub boost: f32,
+    /// Radians per pixel
+    pub look_speed: f32,
+    /// Look with every mouse move instead of only while dragging
+    pub mouse_look: bool,
+    /// Seconds to cover most of the way to the goal; 0 disables smoothing
+    pub damping: f32,
+    pub projection: CameraProjection,
+    current: FlyState,
+    mouse: MouseDelta,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct FlyState {
+    position: [f32; 3],
+    yaw: f32,
+    pitch: f32,
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 1.0, 6.0],
+            yaw: 0.0,
+            pitch: 0.0,
+            fov: 60f32.to_radians(),
+            speed: 2.0,
+            boost: 4.0,
+            look_speed: 0.005,
+            mouse_look: false,
+            damping: 0.08,
+            projection: CameraProjection::default(),
+            current: FlyState { position: [0.0, 1.0, 6.0], yaw: 0.0, pitch: 0.0 },
+            mouse: MouseDelta::default(),
+        }
+    }
+}
+
+impl FlyCamera {
+    pub fn new(position: [f32; 3], yaw: f32, pitch: f32) -> Self {
+        let mut camera = Self { position, yaw, pitch, ..Default::default() };
+        camera.snap();
+        camera
+    }
+
+    fn goal(&self) -> FlyState {
+        FlyState { position: self.position, yaw: self.yaw, pitch: self.pitch }
+    }
+
+    /// Skips the remaining smoothing
+    pub fn snap(&mut self) {
+        self.current = self.goal();
+    }
+
+    pub fn toggle_mouse_look(&mut self) {
+        self.mouse_look = !self.mouse_look;
+    }
+}
+
+impl CameraController for FlyCamera {
+    fn update(&mut self, mouse: &MouseTracker, keyboard: &KeyboardState, _aspect: f32, dt: f32) -> bool {
+        let (pixels, _, _, buttons) = self.mouse.read(mouse);
+        if self.mouse_look || buttons & LEFT_BUTTON != 0 {
+            self.yaw += pixels[0] * self.look_speed;
+            self.pitch = (self.pitch - pixels[1] * self.look_speed).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+
+        let forward = forward_from_angles(self.yaw, self.pitch);
+        let side = normalize(cross(forward, WORLD_UP)).unwrap_or([1.0, 0.0, 0.0]);
+        let boost = if keyboard.is_down(KeyCode::ShiftLeft) { self.boost } else { 1.0 };
+        let step = self.speed * boost * dt;
+        let (ahead, across, rise) = (
+            key_axis(keyboard, KeyCode::KeyW, KeyCode::KeyS),
+            key_axis(keyboard, KeyCode::KeyD, KeyCode::KeyA),
+            key_axis(keyboard, KeyCode::KeyE, KeyCode::KeyQ),
+        );
+        for i in 0..3 {
+            self.position[i] += (forward[i] * ahead + side[i] * across + WORLD_UP[i] * rise) * step;
+        }
+
+        let t = smoothing(self.damping, dt);
+        let before = self.current;
+        let goal = self.goal();
+        for i in 0..3 {
+            approach(&mut self.current.position[i], goal.position[i], t);
+        }
+        approach(&mut self.current.yaw, goal.yaw, t);
+        approach(&mut self.current.pitch, goal.pitch, t);
+        self.current != before
+    }
+
+    fn pose(&self) -> CameraPose {
+        let forward = forward_from_angles(self.current.yaw, self.current.pitch);
+        let position = self.current.position;
+        CameraPose {
+            position,
+            target: [position[0] + forward[0], position[1] + forward[1], position[2] + forward[2]],
+            fov: self.fov,
+        }
+    }
+
+    fn set_pose(&mut self, pose: &CameraPose) {
+        self.position = pose.position;
+        if let Some(forward) = direction(pose.position, pose.target) {
+            (self.yaw, self.pitch) = angles_from_forward(forward);
+        }
+        self.fov = pose.fov;
+        self.snap();
+    }
+
+    fn projection(&self) -> CameraProjection {
+        self.projection
+    }
+}
+
+/// 2D camera looking down -Z at the XY plane: left drag pans, the wheel zooms around the
+/// cursor. Arrow keys pan and `=`/`-` zoom.
+pub struct PanZoomCamera {
+    pub center: [f32; 2],
+    /// Visible height in world units
+    pub height: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+    /// Height factor per wheel step
+    pub zoom_speed: f32,
+    /// Screen heights per second for the arrow keys
+    pub key_pan_speed: f32,
+    /// Seconds to cover most of the way to the goal; 0 disables smoothing
+    pub damping: f32,
+    current: ([f32; 2], f32),
+    mouse: MouseDelta,
+}
+
+impl Default for PanZoomCamera {
+    fn default() -> Self {
+        Self {
+            center: [0.0, 0.0],
+            height: 2.0,
+            min_height: 1e-5,
+            max_height: 1e5,
+            zoom_speed: 0.1,
+            key_pan_speed: 0.5,
+            damping: 0.08,
+            current: ([0.0, 0.0], 2.0),
+            mouse: MouseDelta::default(),
+        }
+    }
+}
+
+impl PanZoomCamera {
+    pub fn new(center: [f32; 2], height: f32) -> Self {
+        Self { center, height, current: (center, height), ..Default::default() }
+    }
+
+    /// Skips the remaining smoothing
+    pub fn snap(&mut self) {
+        self.current = (self.center, self.height);
+    }
+
+    /// World position under normalized window coordinates (0..1, y down), using the goal view
+    pub fn screen_to_world(&self, position: [f32; 2], aspect: f32) -> [f32; 2] {
+        [
+            self.center[0] + (position[0] - 0.5) * self.height * aspect,
+            self.center[1] - (position[1] - 0.5) * self.height,
+        ]
+    }
+}
+
+impl CameraController for PanZoomCamera {
+    fn update(&mut self, mouse: &MouseTracker, keyboard: &KeyboardState, aspect: f32, dt: f32) -> bool {
+        let (_, moved, wheel, buttons) = self.mouse.read(mouse);
+        if buttons & LEFT_BUTTON != 0 {
+            self.center[0] -= moved[0] * self.height * aspect;
+            self.center[1] += moved[1] * self.height;
+        }
+
+        let pan = self.key_pan_speed * self.height * dt;
+        self.center[0] += key_axis(keyboard, KeyCode::ArrowRight, KeyCode::ArrowLeft) * pan;
+        self.center[1] += key_axis(keyboard, KeyCode::ArrowUp, KeyCode::ArrowDown) * pan;
+
+        let zoom = wheel + key_axis(keyboard, KeyCode::Equal, KeyCode::Minus) * 4.0 * dt;
+        if zoom != 0.0 {
+            // Keep the point under the cursor fixed
+            let anchor = self.screen_to_world(mouse.uniform.position, aspect);
+            let height = (self.height * (1.0 - self.zoom_speed).powf(zoom)).clamp(self.min_height, self.max_height);
+            let scale = height / self.height;
+            self.center[0] = anchor[0] + (self.center[0] - anchor[0]) * scale;
+            self.center[1] = anchor[1] + (self.center[1] - anchor[1]) * scale;
+            self.height = height;
+        }
+
+        let t = smoothing(self.damping, dt);
+        let before = self.current;
+        approach(&mut self.current.0[0], self.center[0], t);
+        approach(&mut self.current.0[1], self.center[1], t);
+        // Zoom is smoothed in log space so deep zooms ease at a constant rate
+        let mut log_height = self.current.1.ln();
+        approach(&mut log_height, self.height.ln(), t);
+        self.current.1 = log_height.exp();
+        self.current != before
+    }
+
+    fn pose(&self) -> CameraPose {
+        let ([x, y], height) = self.current;
+        CameraPose {
+            position: [x, y, 1.0],
+            target: [x, y, 0.0],
+            fov: height,
+        }
+    }
+
+    fn set_pose(&mut self, pose: &CameraPose) {
+        self.center = [pose.target[0], pose.target[1]];
+        self.height = pose.fov.clamp(self.min_height, self.max_height);
+        self.snap();
+    }
+
+    fn projection(&self) -> CameraProjection {
+        CameraProjection::Orthographic { near: 0.0, far: 2.0 }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraKeyframe {
+    /// Seconds
+    pub time: f32,
+    pub pose: CameraPose,
+}
+
+/// Recorded or hand-placed camera poses over time. Positions and targets are interpolated
+/// with Catmull-Rom splines, so sparse keyframes give smooth motion. Saved as TOML.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read camera path {}", path.display()))?;
+        let mut camera_path: CameraPath = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse camera path {}", path.display()))?;
+        camera_path.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(camera_path)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let content = toml::to_string_pretty(self)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write camera path {}", path.display()))
+    }
+
+    /// Appends a keyframe; times must increase, anything at or before the last one is ignored.
+    /// Call every frame while recording, e.g. with `controller.pose()`.
+    pub fn record(&mut self, time: f32, pose: CameraPose) {
+        if self.keyframes.last().is_some_and(|last| time <= last.time) {
+            return;
+        }
+        self.keyframes.push(CameraKeyframe { time, pose });
+    }
+
+    /// Inserts a keyframe at any time, replacing one at exactly the same time
+    pub fn insert(&mut self, time: f32, pose: CameraPose) {
+        let index = self.keyframes.partition_point(|k| k.time < time);
+        match self.keyframes.get_mut(index) {
+            Some(existing) if existing.time == time => existing.pose = pose,
+            _ => self.keyframes.insert(index, CameraKeyframe { time, pose }),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Time of the last keyframe relative to the first
+    pub fn duration(&self) -> f32 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    /// Pose at `time`, clamped to the ends of the path. None if the path is empty.
+    pub fn sample(&self, time: f32) -> Option<CameraPose> {
+        let keys = &self.keyframes;
+        let last = keys.len().checked_sub(1)?;
+        let next = keys.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return Some(keys[0].pose);
+        }
+        if next > last {
+            return Some(keys[last].pose);
+        }
+        let (i0, i1, i2, i3) = (next.saturating_sub(2), next - 1, next, (next + 1).min(last));
+        let (k1, k2) = (&keys[i1], &keys[i2]);
+        let t = ((time - k1.time) / (k2.time - k1.time).max(1e-6)).clamp(0.0, 1.0);
+        let spline = |get: fn(&CameraPose) -> [f32; 3]| {
+            let (p0, p1, p2, p3) = (get(&keys[i0].pose), get(&k1.pose), get(&k2.pose), get(&keys[i3].pose));
+            std::array::from_fn(|axis| catmull_rom(p0[axis], p1[axis], p2[axis], p3[axis], t))
+        };
+        Some(CameraPose {
+            position: spline(|pose| pose.position),
+            target: spline(|pose| pose.target),
+            fov: k1.pose.fov + (k2.pose.fov - k1.pose.fov) * t,
+        })
+    }
+
+    /// Sets `controller` to the pose at `time`; returns false if the path is empty
+    pub fn apply<C: CameraController + ?Sized>(&self, time: f32, controller: &mut C) -> bool {
+        match self.sample(time) {
+            Some(pose) => {
+                controller.set_pose(&pose);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// 1 while `positive` is held, -1 for `negative`, 0 for both or neither
+fn key_axis(keyboard: &KeyboardState, positive: KeyCode, negative: KeyCode) -> f32 {
+    f32::from(u8::from(keyboard.is_down(positive))) - f32::from(u8::from(keyboard.is_down(negative)))
+}
+
+/// Fraction of the remaining distance to cover this frame, independent of frame rate
+pub(crate) fn smoothing(damping: f32, dt: f32) -> f32 {
+    if damping <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-dt / damping).exp()
+    }
+}
+
+fn approach(current: &mut f32, goal: f32, t: f32) {
+    *current += (goal - *current) * t;
+    if (goal - *current).abs() <= 1e-6 * goal.abs().max(1.0) {
+        *current = goal;
+    }
+}
+
+fn forward_from_angles(yaw: f32, pitch: f32) -> [f32; 3] {
+    [pitch.cos() * yaw.sin(), pitch.sin(), -pitch.cos() * yaw.cos()]
+}
+
+fn angles_from_forward(forward: [f32; 3]) -> (f32, f32) {
+    (forward[0].atan2(-forward[2]), forward[1].clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH))
+}
+
+/// Screen-right and screen-up of an orbit camera
+fn orbit_axes(yaw: f32, pitch: f32) -> ([f32; 3], [f32; 3]) {
+    let forward = forward_from_angles(yaw, pitch);
+    let side = normalize(cross(forward, WORLD_UP)).unwrap_or([1.0, 0.0, 0.0]);
+    (side, cross(side, forward))
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn length(a: [f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: [f32; 3]) -> Option<[f32; 3]> {
+    let len = length(a);
+    (len > 1e-8).then(|| [a[0] / len, a[1] / len, a[2] / len])
+}
+
+fn direction(from: [f32; 3], to: [f32; 3]) -> Option<[f32; 3]> {
+    normalize(sub(to, from))
+}
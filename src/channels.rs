@@ -0,0 +1,418 @@
+use std::path::Path;
+use log::info;
+#[cfg(feature = "media")]
+use log::warn;
+use wgpu::util::DeviceExt;
+#[cfg(feature = "media")]
+use crate::gst::video::VideoTextureManager;
+#[cfg(feature = "media")]
+use crate::gst::webcam::{WebcamTextureManager, WebcamConfig};
+use crate::{Core, TextureManager, UniformProvider, HdriTexture};
+use crate::sequence::{ImageSequence, is_animated};
+
+/// Number of independent input slots, matching Shadertoy's iChannel0..3.
+pub const MAX_CHANNELS: usize = 4;
+
+/// Per-channel uniform, the equivalent of `iChannelResolution[i]` and `iChannelTime[i]`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChannelUniform {
+    pub resolution: [f32; 3],
+    pub time: f32,
+}
+
+impl Default for ChannelUniform {
+    fn default() -> Self {
+        Self {
+            resolution: [1.0, 1.0, 1.0],
+            time: 0.0,
+        }
+    }
+}
+
+impl UniformProvider for ChannelUniform {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelSamplerConfig {
+    pub filter: wgpu::FilterMode,
+    pub address_mode: wgpu::AddressMode,
+}
+
+impl Default for ChannelSamplerConfig {
+    fn default() -> Self {
+        Self {
+            filter: wgpu::FilterMode::Linear,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+        }
+    }
+}
+
+impl ChannelSamplerConfig {
+    pub fn repeat() -> Self {
+        Self {
+            address_mode: wgpu::AddressMode::Repeat,
+            ..Default::default()
+        }
+    }
+
+    pub fn nearest() -> Self {
+        Self {
+            filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        }
+    }
+
+    fn create_sampler(&self, device: &wgpu::Device, label: &str) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            address_mode_w: self.address_mode,
+            mag_filter: self.filter,
+            min_filter: self.filter,
+            mipmap_filter: self.filter,
+            ..Default::default()
+        })
+    }
+}
+
+/// What a channel currently samples from.
+pub enum ChannelSource {
+    Empty,
+    Image(TextureManager),
+    /// Bound as linear `Rgba16Float`; exposure/gamma are available in `HdriTexture::uniform`.
+    Hdri(Box<HdriTexture>),
+    /// Numbered files, a directory of frames, or an animated GIF/WebP.
+    Sequence(ImageSequence),
+    #[cfg(feature = "media")]
+    Video(VideoTextureManager),
+    #[cfg(feature = "media")]
+    Webcam(WebcamTextureManager),
+    /// Output of another pass (e.g. a compute shader's output texture). The view must be filterable.
+    Pass {
+        view: wgpu::TextureView,
+        size: (u32, u32),
+    },
+}
+
+impl ChannelSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChannelSource::Empty => "Empty",
+            ChannelSource::Image(_) => "Image",
+            ChannelSource::Hdri(_) => "HDRI",
+            ChannelSource::Sequence(_) => "Sequence",
+            #[cfg(feature = "media")]
+            ChannelSource::Video(_) => "Video",
+            #[cfg(feature = "media")]
+            ChannelSource::Webcam(_) => "Webcam",
+            ChannelSource::Pass { .. } => "Pass",
+        }
+    }
+}
+
+pub struct MediaChannel {
+    pub source: ChannelSource,
+    pub sampler_config: ChannelSamplerConfig,
+    pub uniform: ChannelUniform,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    bound_view: wgpu::TextureView,
+    sampler_dirty: bool,
+}
+
+/// A fixed array of input channels, each with its own texture, sampler, uniform and bind group.
+///
+/// Every channel binds the same layout:
+/// - `@binding(0)`: `texture_2d<f32>`
+/// - `@binding(1)`: `sampler`
+/// - `@binding(2)`: `ChannelUniform` (resolution + time)
+pub struct ChannelSet {
+    channels: Vec<MediaChannel>,
+    layout: wgpu::BindGroupLayout,
+    texture_layout: wgpu::BindGroupLayout,
+    placeholder: TextureManager,
+}
+
+impl ChannelSet {
+    /// `texture_layout` is the layout used by the per-source `TextureManager`s (texture + sampler).
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, texture_layout: &wgpu::BindGroupLayout) -> Self {
+        let layout = Self::create_bind_group_layout(device);
+        let placeholder = TextureManager::new(device, queue, &image::RgbaImage::new(1, 1), texture_layout);
+
+        let channels = (0..MAX_CHANNELS)
+            .map(|index| {
+                let sampler_config = ChannelSamplerConfig::default();
+                let sampler = sampler_config.create_sampler(device, &format!("iChannel{} Sampler", index));
+                let uniform = ChannelUniform::default();
+                let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("iChannel{} Uniform", index)),
+                    contents: uniform.as_bytes(),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+                let bind_group = Self::create_bind_group(
+                    device, &layout, &placeholder.view, &sampler, &uniform_buffer, index,
+                );
+                MediaChannel {
+                    source: ChannelSource::Empty,
+                    sampler_config,
+                    uniform,
+                    sampler,
+                    uniform_buffer,
+                    bind_group,
+                    bound_view: placeholder.view.clone(),
+                    sampler_dirty: false,
+                }
+            })
+            .collect();
+
+        Self {
+            channels,
+            layout,
+            texture_layout: texture_layout.clone(),
+            placeholder,
+        }
+    }
+
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let visibility = wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE;
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Channel Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+        uniform_buffer: &wgpu::Buffer,
+        index: usize,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("iChannel{} Bind Group", index)),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    pub fn channel(&self, index: usize) -> Option<&MediaChannel> {
+        self.channels.get(index)
+    }
+
+    pub fn channel_mut(&mut self, index: usize) -> Option<&mut MediaChannel> {
+        self.channels.get_mut(index)
+    }
+
+    /// Bind group for `iChannel{index}`. Panics if `index >= MAX_CHANNELS`.
+    pub fn bind_group(&self, index: usize) -> &wgpu::BindGroup {
+        &self.channels[index].bind_group
+    }
+
+    pub fn resolution(&self, index: usize) -> [f32; 3] {
+        self.channels.get(index).map(|c| c.uniform.resolution).unwrap_or([1.0, 1.0, 1.0])
+    }
+
+    fn slot(&mut self, index: usize) -> anyhow::Result<&mut MediaChannel> {
+        self.channels
+            .get_mut(index)
+            .ok_or_else(|| anyhow::anyhow!("Channel index {} out of range (max {})", index, MAX_CHANNELS - 1))
+    }
+
+    /// Loads an image, HDRI or (with the `media` feature) video into the given channel.
+    pub fn load_media<P: AsRef<Path>>(&mut self, core: &Core, index: usize, path: P) -> anyhow::Result<()> {
+        let path_ref = path.as_ref();
+        let extension = path_ref.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+
+        let source = match extension {
+            _ if path_ref.is_dir() || path_ref.to_string_lossy().contains('%') || is_animated(path_ref) => {
+                info!("Loading image sequence into iChannel{}: {:?}", index, path_ref);
+                ChannelSource::Sequence(ImageSequence::new(&core.device, &core.queue, &self.texture_layout, path_ref, None)?)
+            }
+            Some(ext) if ["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"].contains(&ext.as_str()) => {
+                info!("Loading image into iChannel{}: {:?}", index, path_ref);
+                let rgba_image = image::open(path_ref)?.into_rgba8();
+                ChannelSource::Image(TextureManager::new(&core.device, &core.queue, &rgba_image, &self.texture_layout))
+            }
+            Some(ext) if ["hdr", "exr"].contains(&ext.as_str()) => {
+                info!("Loading HDRI into iChannel{}: {:?}", index, path_ref);
+                let file_data = std::fs::read(path_ref)?;
+                let hdri = HdriTexture::new(
+                    core, &file_data, &self.texture_layout, 1.0, wgpu::TextureFormat::Rgba16Float,
+                ).map_err(|e| anyhow::anyhow!("Failed to load HDRI: {}", e))?;
+                ChannelSource::Hdri(Box::new(hdri))
+            }
+            #[cfg(feature = "media")]
+            Some(ext) if ["mp4", "avi", "mkv", "mov", "webm"].contains(&ext.as_str()) => {
+                info!("Loading video into iChannel{}: {:?}", index, path_ref);
+                let mut video_manager = VideoTextureManager::new(core, &self.texture_layout, path_ref)?;
+                video_manager.set_loop(true);
+                video_manager.play()?;
+                ChannelSource::Video(video_manager)
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported media format: {:?}", path_ref)),
+        };
+
+        self.slot(index)?.source = source;
+        Ok(())
+    }
+
+    pub fn set_image(&mut self, core: &Core, index: usize, image: &image::RgbaImage) -> anyhow::Result<()> {
+        let texture = TextureManager::new(&core.device, &core.queue, image, &self.texture_layout);
+        self.slot(index)?.source = ChannelSource::Image(texture);
+        Ok(())
+    }
+
+    #[cfg(feature = "media")]
+    pub fn start_webcam(&mut self, core: &Core, index: usize, device_index: Option<u32>) -> anyhow::Result<()> {
+        let mut webcam_manager = WebcamTextureManager::new(core, &self.texture_layout, device_index)?;
+        webcam_manager.start()?;
+        self.slot(index)?.source = ChannelSource::Webcam(webcam_manager);
+        Ok(())
+    }
+
+    /// Like `start_webcam`, with an explicit source, capture mode and mirror/crop
+    #[cfg(feature = "media")]
+    pub fn start_webcam_with_config(&mut self, core: &Core, index: usize, config: WebcamConfig) -> anyhow::Result<()> {
+        let mut webcam_manager = WebcamTextureManager::with_config(core, &self.texture_layout, config)?;
+        webcam_manager.start()?;
+        self.slot(index)?.source = ChannelSource::Webcam(webcam_manager);
+        Ok(())
+    }
+
+    /// Feeds another pass's output texture into the channel. The view is kept alive by the channel.
+    pub fn set_pass_output(&mut self, index: usize, view: &wgpu::TextureView, size: (u32, u32)) -> anyhow::Result<()> {
+        self.slot(index)?.source = ChannelSource::Pass { view: view.clone(), size };
+        Ok(())
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        if let Some(channel) = self.channels.get_mut(index) {
+            channel.source = ChannelSource::Empty;
+        }
+    }
+
+    pub fn set_sampler(&mut self, index: usize, config: ChannelSamplerConfig) {
+        if let Some(channel) = self.channels.get_mut(index) {
+            if channel.sampler_config != config {
+                channel.sampler_config = config;
+                channel.sampler_dirty = true;
+            }
+        }
+    }
+
+    /// Pulls new video/webcam frames, refreshes the per-channel uniforms and rebuilds bind groups
+    /// whose texture changed. Call once per frame before rendering.
+    pub fn update(&mut self, core: &Core, time: f32) {
+        let placeholder_view = &self.placeholder.view;
+        for (index, channel) in self.channels.iter_mut().enumerate() {
+            let (view, size, channel_time) = match &mut channel.source {
+                ChannelSource::Empty => (placeholder_view, (1, 1), time),
+                ChannelSource::Image(texture) => {
+                    let size = texture.texture.size();
+                    (&texture.view, (size.width, size.height), time)
+                }
+                ChannelSource::Hdri(hdri) => {
+                    (&hdri.view, (hdri.metadata.width, hdri.metadata.height), time)
+                }
+                ChannelSource::Sequence(sequence) => {
+                    sequence.update(&core.device, &core.queue, &self.texture_layout);
+                    (&sequence.texture_manager().view, sequence.dimensions(), sequence.position())
+                }
+                #[cfg(feature = "media")]
+                ChannelSource::Video(video_manager) => {
+                    if let Err(e) = video_manager.update_texture(&core.device, &core.queue, &self.texture_layout) {
+                        warn!("Failed to update video in iChannel{}: {}", index, e);
+                    }
+                    let position = video_manager.position().mseconds() as f32 / 1000.0;
+                    (&video_manager.texture_manager().view, video_manager.dimensions(), position)
+                }
+                #[cfg(feature = "media")]
+                ChannelSource::Webcam(webcam_manager) => {
+                    if let Err(e) = webcam_manager.update_texture(&core.device, &core.queue, &self.texture_layout) {
+                        warn!("Failed to update webcam in iChannel{}: {}", index, e);
+                    }
+                    (&webcam_manager.texture_manager().view, webcam_manager.dimensions(), time)
+                }
+                ChannelSource::Pass { view, size } => (&*view, *size, time),
+            };
+
+            if channel.sampler_dirty {
+                channel.sampler = channel.sampler_config.create_sampler(&core.device, &format!("iChannel{} Sampler", index));
+            }
+            if channel.sampler_dirty || channel.bound_view != *view {
+                channel.bind_group = Self::create_bind_group(
+                    &core.device, &self.layout, view, &channel.sampler, &channel.uniform_buffer, index,
+                );
+                channel.bound_view = view.clone();
+                channel.sampler_dirty = false;
+            }
+
+            channel.uniform = ChannelUniform {
+                resolution: [size.0.max(1) as f32, size.1.max(1) as f32, 1.0],
+                time: channel_time,
+            };
+            core.queue.write_buffer(&channel.uniform_buffer, 0, channel.uniform.as_bytes());
+        }
+    }
+}
@@ -1,8 +1,11 @@
-use crate::{Core, UniformProvider, UniformBinding, TextureManager, ShaderHotReload, AtomicBuffer, FontSystem};
+use crate::{Core, UniformProvider, UniformBinding, TextureManager, ShaderHotReload, HotReloadConfig, AtomicBuffer, FontSystem, ShaderCache};
 use std::sync::Arc;
 use std::path::PathBuf;
 use log::{info, warn};
 
+mod sound;
+pub use sound::{SoundShader, SoundShaderConfig, SoundBlockUniform};
+
 pub const COMPUTE_TEXTURE_FORMAT_RGBA16: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
 pub const COMPUTE_TEXTURE_FORMAT_RGBA8: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
 
@@ -413,6 +416,9 @@ pub struct ComputeShader {
     pub audio_bind_group: Option<wgpu::BindGroup>,
     pub audio_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub audio_staging_buffer: Option<wgpu::Buffer>,
+    pub pipeline_cache: Option<wgpu::PipelineCache>,
+    /// `core.shader_cache`, handed to the hot reloader
+    pub shader_cache: ShaderCache,
 }
 
 impl ComputeShader {
@@ -626,10 +632,7 @@ impl ComputeShader {
         };
         
         // Create the shader module
-        let shader_module = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some(&format!("{} Module", config.label)),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-        });
+        let shader_module = core.create_shader_module(shader_source, &format!("{} Module", config.label));
         
         // Create pipeline layout
         let mut bind_group_layouts: Vec<&wgpu::BindGroupLayout> = vec![&time_bind_group_layout, &storage_texture_layout];
@@ -669,7 +672,7 @@ impl ComputeShader {
                 module: &shader_module,
                 entry_point: Some(entry_point),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                cache: None,
+                cache: core.pipeline_cache.as_ref(),
             });
             pipelines.push(pipeline);
         }
@@ -745,6 +748,8 @@ impl ComputeShader {
             audio_bind_group: final_audio_bind_group,
             audio_bind_group_layout,
             audio_staging_buffer,
+            pipeline_cache: core.pipeline_cache.clone(),
+            shader_cache: core.shader_cache.clone(),
         }
     }
     pub fn add_mouse_uniform_binding(
@@ -847,13 +852,24 @@ impl ComputeShader {
         device: Arc<wgpu::Device>, 
         shader_path: PathBuf, 
         shader_module: wgpu::ShaderModule,
+    ) -> Result<(), notify::Error> {
+        self.enable_hot_reload_with_config(device, shader_path, shader_module, HotReloadConfig::default())
+    }
+
+    pub fn enable_hot_reload_with_config(&mut self,
+        device: Arc<wgpu::Device>,
+        shader_path: PathBuf,
+        shader_module: wgpu::ShaderModule,
+        config: HotReloadConfig,
     ) -> Result<(), notify::Error> {
         let entry_point = self.entry_points.first().cloned().unwrap_or_else(|| "main".to_string());
-        let hot_reload = ShaderHotReload::new_compute(
+        let hot_reload = ShaderHotReload::new_compute_with_config(
             device,
+            self.shader_cache.clone(),
             shader_path,
             shader_module,
             &entry_point,
+            config,
         )?;
         
         self.hot_reload = Some(hot_reload);
@@ -882,7 +898,7 @@ impl ComputeShader {
                         module: &new_module,
                         entry_point: Some(entry_point),
                         compilation_options: wgpu::PipelineCompilationOptions::default(),
-                        cache: None,
+                        cache: self.pipeline_cache.as_ref(),
                     });
                     new_pipelines.push(new_pipeline);
                 }
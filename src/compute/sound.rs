@@ -0,0 +1,344 @@
+// Sample-level "sound shaders": a compute entry point fills blocks of interleaved PCM
+// which are read back without stalling the render loop and handed to an audio sink.
+use crate::Core;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use log::warn;
+
+const BLOCK_IDLE: u8 = 0;
+const BLOCK_PENDING: u8 = 1;
+const BLOCK_MAPPED: u8 = 2;
+const BLOCK_FAILED: u8 = 3;
+
+/// Bound at `@group(0) @binding(0)`; the output is `@group(0) @binding(1)`:
+/// ```wgsl
+/// struct SoundBlock { start_frame: u32, frames: u32, channels: u32, sample_rate: f32, time: f32, start_frame_hi: u32, second_frame: u32, _pad: u32 }
+/// @group(0) @binding(1) var<storage, read_write> samples: array<f32>; // interleaved, frames * channels
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SoundBlockUniform {
+    /// Index of the block's first frame since the stream started, modulo 2^32: it wraps to 0
+    /// after about 27 hours at 44.1 kHz. `start_frame_hi` holds the bits above.
+    pub start_frame: u32,
+    pub frames: u32,
+    pub channels: u32,
+    pub sample_rate: f32,
+    /// `start_frame` in seconds. Loses precision after a few hours; use `second_frame` for long-running phases
+    pub time: f32,
+    /// High 32 bits of the 64-bit frame index
+    pub start_frame_hi: u32,
+    /// The 64-bit frame index modulo `sample_rate`, for phases that must not click when
+    /// `start_frame` wraps: `(second_frame + id.x) % u32(sample_rate)`
+    pub second_frame: u32,
+    pub _padding: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SoundShaderConfig {
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// Frames generated per dispatch
+    pub block_frames: u32,
+    /// Readback buffers, i.e. how many blocks may be on the GPU at once
+    pub blocks_in_flight: usize,
+    /// Must be declared with `@workgroup_size(workgroup_size)`; one invocation per frame
+    pub workgroup_size: u32,
+    pub entry_point: String,
+    pub label: String,
+}
+
+impl Default for SoundShaderConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            channels: 2,
+            block_frames: 512,
+            blocks_in_flight: 8,
+            workgroup_size: 64,
+            entry_point: "main_sound".to_string(),
+            label: "Sound Shader".to_string(),
+        }
+    }
+}
+
+struct ReadbackBlock {
+    buffer: wgpu::Buffer,
+    state: Arc<AtomicU8>,
+}
+
+/// Runs a sound entry point block by block. `fill` keeps enough blocks queued on the GPU
+/// to cover what the consumer asks for and returns whichever have finished mapping, in order,
+/// so audio is produced ahead of playback instead of waiting on the GPU every frame.
+pub struct SoundShader {
+    pub config: SoundShaderConfig,
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    block_buffer: wgpu::Buffer,
+    output_buffer: wgpu::Buffer,
+    user_bind_group: Option<wgpu::BindGroup>,
+    readback: Vec<ReadbackBlock>,
+    /// Readback indices in submission order
+    pending: VecDeque<usize>,
+    next_frame: u64,
+    /// Samples returned by the GPU beyond what `render` was asked for
+    carry: Vec<f32>,
+}
+
+impl SoundShader {
+    /// `user_layout` is bound at group 1 (e.g. synth parameters); set its bind group with `set_user_bind_group`.
+    pub fn new(
+        core: &Core,
+        shader_source: &str,
+        config: SoundShaderConfig,
+        user_layout: Option<&wgpu::BindGroupLayout>,
+    ) -> Self {
+        let device = &core.device;
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(&format!("{} Layout", config.label)),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let block_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{} Block Uniform", config.label)),
+            size: std::mem::size_of::<SoundBlockUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let block_bytes = Self::block_bytes(&config);
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{} Output", config.label)),
+            size: block_bytes,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{} Bind Group", config.label)),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: block_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: output_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut layouts = vec![&layout];
+        layouts.extend(user_layout);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{} Pipeline Layout", config.label)),
+            bind_group_layouts: &layouts,
+            push_constant_ranges: &[],
+        });
+        let shader_module = core.create_shader_module(shader_source, &config.label);
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(&format!("{} Pipeline - {}", config.label, config.entry_point)),
+            layout: Some(&pipeline_layout),
+            module: &shader_module,
+            entry_point: Some(&config.entry_point),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: core.pipeline_cache.as_ref(),
+        });
+
+        let readback = (0..config.blocks_in_flight.max(1))
+            .map(|i| ReadbackBlock {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("{} Readback {}", config.label, i)),
+                    size: block_bytes,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                state: Arc::new(AtomicU8::new(BLOCK_IDLE)),
+            })
+            .collect();
+
+        Self {
+            config,
+            pipeline,
+            bind_group,
+            block_buffer,
+            output_buffer,
+            user_bind_group: None,
+            readback,
+            pending: VecDeque::new(),
+            next_frame: 0,
+            carry: Vec::new(),
+        }
+    }
+
+    fn block_bytes(config: &SoundShaderConfig) -> u64 {
+        (config.block_frames * config.channels) as u64 * std::mem::size_of::<f32>() as u64
+    }
+
+    pub fn set_user_bind_group(&mut self, bind_group: wgpu::BindGroup) {
+        self.user_bind_group = Some(bind_group);
+    }
+
+    /// Frames submitted to the GPU but not yet returned by `fill`
+    pub fn frames_in_flight(&self) -> usize {
+        self.pending.len() * self.config.block_frames as usize
+    }
+
+    /// Frame index the next submitted block starts at
+    pub fn next_frame(&self) -> u64 {
+        self.next_frame
+    }
+
+    /// Restarts the stream at frame 0. Blocks still on the GPU are discarded when they complete.
+    pub fn reset(&mut self) {
+        self.seek(0);
+    }
+
+    /// Restarts the stream at `frame`, e.g. the first frame of an export
+    pub fn seek(&mut self, frame: u64) {
+        self.next_frame = frame;
+        self.pending.clear();
+        self.carry.clear();
+    }
+
+    /// A readback buffer that isn't waiting to be returned, reclaiming ones orphaned by `reset`
+    fn free_readback(&self) -> Option<usize> {
+        (0..self.readback.len()).find(|&i| {
+            if self.pending.contains(&i) {
+                return false;
+            }
+            let block = &self.readback[i];
+            match block.state.load(Ordering::Acquire) {
+                BLOCK_PENDING => false,
+                state => {
+                    if state == BLOCK_MAPPED {
+                        block.buffer.unmap();
+                    }
+                    block.state.store(BLOCK_IDLE, Ordering::Release);
+                    true
+                }
+            }
+        })
+    }
+
+    /// Dispatches one block and starts mapping its readback buffer. Returns false when
+    /// every readback buffer is still busy.
+    fn submit_block(&mut self, core: &Core) -> bool {
+        let Some(index) = self.free_readback() else {
+            return false;
+        };
+
+        let block = SoundBlockUniform {
+            // Split explicitly so the wrap of the low word is intended, not a silent truncation
+            start_frame: (self.next_frame & u32::MAX as u64) as u32,
+            frames: self.config.block_frames,
+            channels: self.config.channels,
+            sample_rate: self.config.sample_rate as f32,
+            time: (self.next_frame as f64 / self.config.sample_rate as f64) as f32,
+            start_frame_hi: (self.next_frame >> 32) as u32,
+            second_frame: (self.next_frame % self.config.sample_rate as u64) as u32,
+            _padding: 0,
+        };
+        // Staged writes land before the submit below, so each block sees its own uniform
+        core.queue.write_buffer(&self.block_buffer, 0, bytemuck::bytes_of(&block));
+
+        let mut encoder = core.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(&format!("{} Encoder", self.config.label)),
+        });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some(&format!("{} Pass", self.config.label)),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            if let Some(user_bind_group) = &self.user_bind_group {
+                pass.set_bind_group(1, user_bind_group, &[]);
+            }
+            let workgroups = self.config.block_frames.div_ceil(self.config.workgroup_size.max(1));
+            pass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        let readback = &self.readback[index];
+        encoder.copy_buffer_to_buffer(&self.output_buffer, 0, &readback.buffer, 0, Self::block_bytes(&self.config));
+        core.queue.submit(std::iter::once(encoder.finish()));
+
+        readback.state.store(BLOCK_PENDING, Ordering::Release);
+        let state = readback.state.clone();
+        readback.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            state.store(if result.is_ok() { BLOCK_MAPPED } else { BLOCK_FAILED }, Ordering::Release);
+        });
+        self.pending.push_back(index);
+        self.next_frame += self.config.block_frames as u64;
+        true
+    }
+
+    /// Queues blocks until `frames_wanted` frames are either in flight or returned, then
+    /// returns the interleaved samples of every block that has finished, oldest first.
+    /// Never blocks on the GPU; call it once per frame with the consumer's shortfall.
+    pub fn fill(&mut self, core: &Core, frames_wanted: usize) -> Vec<f32> {
+        let _ = core.device.poll(wgpu::PollType::Poll);
+
+        let mut samples = Vec::new();
+        while let Some(&index) = self.pending.front() {
+            let block = &self.readback[index];
+            match block.state.load(Ordering::Acquire) {
+                BLOCK_MAPPED => {
+                    samples.extend_from_slice(bytemuck::cast_slice(&block.buffer.slice(..).get_mapped_range()));
+                    block.buffer.unmap();
+                }
+                BLOCK_FAILED => {
+                    warn!("{}: failed to map sound block, inserting silence", self.config.label);
+                    samples.resize(samples.len() + (self.config.block_frames * self.config.channels) as usize, 0.0);
+                }
+                _ => break,
+            }
+            block.state.store(BLOCK_IDLE, Ordering::Release);
+            self.pending.pop_front();
+        }
+
+        let returned = samples.len() / self.config.channels.max(1) as usize;
+        while self.frames_in_flight() + returned < frames_wanted {
+            if !self.submit_block(core) {
+                break;
+            }
+        }
+        samples
+    }
+
+    /// Blocks until exactly `frames` frames following the previous call are generated.
+    /// For offline rendering, where every frame must be produced regardless of timing.
+    pub fn render(&mut self, core: &Core, frames: usize) -> Vec<f32> {
+        let channels = self.config.channels.max(1) as usize;
+        let wanted = frames * channels;
+        while self.carry.len() < wanted {
+            let missing = (wanted - self.carry.len()).div_ceil(channels);
+            let samples = self.fill(core, missing);
+            if samples.is_empty() {
+                let _ = core.device.poll(wgpu::PollType::Wait);
+            }
+            self.carry.extend(samples);
+        }
+        self.carry.drain(..wanted).collect()
+    }
+}
@@ -33,6 +33,7 @@ pub struct ControlsRequest {
     pub start_webcam: bool,
     pub stop_webcam: bool,
     pub webcam_device_index: Option<u32>,
+    pub toggle_webcam_mirror: bool,
 }
 impl Default for ControlsRequest {
     fn default() -> Self {
@@ -78,6 +79,7 @@ impl Default for ControlsRequest {
             start_webcam: false,
             stop_webcam: false,
             webcam_device_index: None,
+            toggle_webcam_mirror: false,
         }
     }
 }
@@ -171,6 +173,7 @@ impl ShaderControls {
             start_webcam: false,
             stop_webcam: false,
             webcam_device_index: None,
+            toggle_webcam_mirror: false,
         }
     }
     
@@ -243,12 +246,19 @@ impl ShaderControls {
                         }
                     }
                     
+                    if ui.button("Sequence").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                            request.load_media_path = Some(path);
+                        }
+                    }
+                    
                     if ui.button("Load").clicked() {
                         if let Some(path) = rfd::FileDialog::new()
-                            .add_filter("Media Files", &["png", "jpg", "jpeg", "mp4", "avi", "mkv", "webm", "mov"])
-                            .add_filter("Images", &["png", "jpg", "jpeg", "webp", "bmp", "tiff"])
+                            .add_filter("Media Files", &["png", "jpg", "jpeg", "gif", "mp4", "avi", "mkv", "webm", "mov", "wav", "flac", "mp3", "ogg"])
+                            .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp", "bmp", "tiff"])
                             .add_filter("Videos", &["mp4", "avi", "mkv", "webm", "mov"])
                             .add_filter("HDRI", &["hdr","exr"])
+                            .add_filter("Audio", &["wav", "flac", "mp3", "ogg"])
                             .pick_file() 
                         {
                             request.load_media_path = Some(path);
@@ -258,7 +268,8 @@ impl ShaderControls {
             });
             
             // Only show video controls if we're using a video texture
-            if using_video_texture{
+            // Image sequences report through video_info and share these controls
+            if using_video_texture || video_info.is_some() {
                 ui.collapsing("Controls", |ui| {
                     // Main video controls
                     ui.horizontal(|ui| {
@@ -315,7 +326,10 @@ impl ShaderControls {
                         ui.separator();
                         
                         ui.collapsing("Properties", |ui| {
-                            ui.label(format!("Dimensions: {}x{}", dimensions.0, dimensions.1));
+                            // (0, 0) for audio-only input
+                            if dimensions != (0, 0) {
+                                ui.label(format!("Dimensions: {}x{}", dimensions.0, dimensions.1));
+                            }
                             
                             if let Some(fps) = framerate_opt {
                                 ui.label(format!("Framerate: {:.2} fps", fps));
@@ -365,6 +379,9 @@ impl ShaderControls {
                     ui.label(format!("Resolution: {}x{}", width, height));
                     ui.label("Type: Live Camera Feed");
                     ui.label("Status: Active");
+                    if ui.button("↔ Mirror").clicked() {
+                        request.toggle_webcam_mirror = true;
+                    }
                 } else {
                     ui.label("Webcam information not available");
                 }
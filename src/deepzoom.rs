@@ -0,0 +1,493 @@
+// Deep-zoom 2D viewport for fractals. The centre lives on the CPU in double-double
+// precision (~32 significant digits); shaders get it either as a double-single split
+// (hi + lo f32) or as a perturbation reference orbit in a storage buffer.
+use std::ops::{Add, Mul, Neg, Sub};
+use crate::camera::{smoothing, MouseDelta, LEFT_BUTTON};
+use crate::MouseTracker;
+
+/// Unevaluated sum `hi + lo` of two f64s, with about 106 bits of mantissa.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    pub const ZERO: DoubleDouble = DoubleDouble { hi: 0.0, lo: 0.0 };
+
+    pub fn new(value: f64) -> Self {
+        Self { hi: value, lo: 0.0 }
+    }
+
+    fn quick_two_sum(a: f64, b: f64) -> Self {
+        let hi = a + b;
+        Self { hi, lo: b - (hi - a) }
+    }
+
+    fn two_sum(a: f64, b: f64) -> Self {
+        let hi = a + b;
+        let v = hi - a;
+        Self { hi, lo: (a - (hi - v)) + (b - v) }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    /// Double-single split: `hi + lo` as two f32s, ~48 bits of mantissa
+    pub fn to_f32_pair(self) -> [f32; 2] {
+        let hi = self.hi as f32;
+        let lo = (self - DoubleDouble::new(hi as f64)).to_f64() as f32;
+        [hi, lo]
+    }
+
+    pub fn square(self) -> Self {
+        self * self
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        DoubleDouble::new(value)
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let s = Self::two_sum(self.hi, other.hi);
+        Self::quick_two_sum(s.hi, s.lo + self.lo + other.lo)
+    }
+}
+
+impl Add<f64> for DoubleDouble {
+    type Output = Self;
+    fn add(self, other: f64) -> Self {
+        self + DoubleDouble::new(other)
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self { hi: -self.hi, lo: -self.lo }
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        let p = self.hi * other.hi;
+        let e = self.hi.mul_add(other.hi, -p) + (self.hi * other.lo + self.lo * other.hi);
+        Self::quick_two_sum(p, e)
+    }
+}
+
+impl Mul<f64> for DoubleDouble {
+    type Output = Self;
+    fn mul(self, other: f64) -> Self {
+        self * DoubleDouble::new(other)
+    }
+}
+
+/// Matches `DeepZoom` in `DEEP_ZOOM_WGSL`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DeepZoomUniform {
+    pub center_hi: [f32; 2],
+    pub center_lo: [f32; 2],
+    /// Visible height in world units
+    pub scale: f32,
+    pub aspect: f32,
+    /// Entries in the reference orbit buffer (0 until one is computed)
+    pub orbit_len: u32,
+    pub max_iterations: u32,
+}
+
+/// Double-single arithmetic and a perturbation Mandelbrot for `DeepZoomViewport`. Paste it
+/// into a shader that declares, with its own group numbers:
+/// ```wgsl
+/// @group(1) @binding(0) var<uniform> deep_zoom: DeepZoom;
+/// @group(1) @binding(1) var<storage, read> reference_orbit: array<vec2<f32>>;
+/// ```
+/// `deep_zoom_coord_ds(uv)` gives the absolute coordinate as `vec4(x_hi, x_lo, y_hi, y_lo)`
+/// (good to ~1e-12 with `ds_add`/`ds_mul`); `deep_zoom_mandelbrot(uv)` iterates against the
+/// reference orbit and works down to ~1e-30, falling back to plain f32 iteration until an orbit
+/// of at least two points is uploaded. `uv` is 0..1 with y down, like `MouseUniform`.
+pub const DEEP_ZOOM_WGSL: &str = r#"
+struct DeepZoom {
+    center_hi: vec2<f32>,
+    center_lo: vec2<f32>,
+    scale: f32,
+    aspect: f32,
+    orbit_len: u32,
+    max_iterations: u32,
+}
+
+fn ds_two_sum(a: f32, b: f32) -> vec2<f32> {
+    let s = a + b;
+    let v = s - a;
+    return vec2<f32>(s, (a - (s - v)) + (b - v));
+}
+
+fn ds_normalize(hi: f32, lo: f32) -> vec2<f32> {
+    let s = hi + lo;
+    return vec2<f32>(s, lo - (s - hi));
+}
+
+fn ds_add(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    let s = ds_two_sum(a.x, b.x);
+    return ds_normalize(s.x, s.y + a.y + b.y);
+}
+
+fn ds_split(a: f32) -> vec2<f32> {
+    let t = 4097.0 * a;
+    let hi = t - (t - a);
+    return vec2<f32>(hi, a - hi);
+}
+
+fn ds_mul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    let p = a.x * b.x;
+    let sa = ds_split(a.x);
+    let sb = ds_split(b.x);
+    let err = ((sa.x * sb.x - p) + sa.x * sb.y + sa.y * sb.x) + sa.y * sb.y;
+    return ds_normalize(p, err + (a.x * b.y + a.y * b.x));
+}
+
+// Offset from the centre in world units
+fn deep_zoom_offset(uv: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>((uv.x - 0.5) * deep_zoom.scale * deep_zoom.aspect, (0.5 - uv.y) * deep_zoom.scale);
+}
+
+fn deep_zoom_coord_ds(uv: vec2<f32>) -> vec4<f32> {
+    let d = deep_zoom_offset(uv);
+    let x = ds_add(vec2<f32>(deep_zoom.center_hi.x, deep_zoom.center_lo.x), vec2<f32>(d.x, 0.0));
+    let y = ds_add(vec2<f32>(deep_zoom.center_hi.y, deep_zoom.center_lo.y), vec2<f32>(d.y, 0.0));
+    return vec4<f32>(x, y);
+}
+
+// Plain f32 iteration, for when there is no reference orbit to perturb around
+fn deep_zoom_mandelbrot_f32(uv: vec2<f32>) -> f32 {
+    let c = deep_zoom.center_hi + deep_zoom_offset(uv);
+    var z = vec2<f32>(0.0);
+    for (var n = 1u; n <= deep_zoom.max_iterations; n++) {
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+        let r2 = dot(z, z);
+        if r2 > 256.0 {
+            return f32(n) + 1.0 - log2(0.5 * log(r2));
+        }
+    }
+    return -1.0;
+}
+
+// Smooth iteration count, or -1 inside the set. The pixel's orbit is tracked as a delta
+// from the reference orbit (computed at the centre), rebasing when the delta dominates.
+fn deep_zoom_mandelbrot(uv: vec2<f32>) -> f32 {
+    // Each step reads the next orbit point, so perturbation needs at least two
+    if deep_zoom.orbit_len < 2u {
+        return deep_zoom_mandelbrot_f32(uv);
+    }
+    let dc = deep_zoom_offset(uv);
+    var dz = vec2<f32>(0.0);
+    var ref_i = 0u;
+    for (var n = 1u; n <= deep_zoom.max_iterations; n++) {
+        let zr = reference_orbit[ref_i];
+        dz = vec2<f32>(
+            2.0 * (zr.x * dz.x - zr.y * dz.y) + dz.x * dz.x - dz.y * dz.y,
+            2.0 * (zr.x * dz.y + zr.y * dz.x) + 2.0 * dz.x * dz.y,
+        ) + dc;
+        ref_i += 1u;
+        let z = reference_orbit[ref_i] + dz;
+        let r2 = dot(z, z);
+        if r2 > 256.0 {
+            return f32(n) + 1.0 - log2(0.5 * log(r2));
+        }
+        if r2 < dot(dz, dz) || ref_i + 1u >= deep_zoom.orbit_len {
+            dz = z;
+            ref_i = 0u;
+        }
+    }
+    return -1.0;
+}
+"#;
+
+pub fn create_deep_zoom_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: Some("deep_zoom_bind_group_layout"),
+    })
+}
+
+/// Mandelbrot orbit of `c` (z -> z² + c from z = 0) in double-double, stopping once
+/// |z|² passes `bailout` or after `max_iterations` steps. Entry `n` is z_n, so the first is 0.
+pub fn mandelbrot_reference_orbit(c: [DoubleDouble; 2], max_iterations: u32, bailout: f64) -> Vec<[f32; 2]> {
+    let [cx, cy] = c;
+    let (mut x, mut y) = (DoubleDouble::ZERO, DoubleDouble::ZERO);
+    let mut orbit = Vec::with_capacity(max_iterations as usize + 1);
+    orbit.push([0.0, 0.0]);
+    for _ in 0..max_iterations {
+        let xy = x * y;
+        x = x.square() - y.square() + cx;
+        y = xy + xy + cy;
+        let (fx, fy) = (x.to_f64(), y.to_f64());
+        orbit.push([fx as f32, fy as f32]);
+        if fx * fx + fy * fy > bailout {
+            break;
+        }
+    }
+    orbit
+}
+
+/// Pan/zoom state for fractals: left drag pans, the wheel zooms around the cursor
+/// (`MouseUniform.wheel`), with zooms down to ~1e-30 of the unit square.
+pub struct DeepZoomViewport {
+    /// Where input is heading; `center` and `scale` ease towards it
+    goal_center: [DoubleDouble; 2],
+    goal_scale: f64,
+    center: [DoubleDouble; 2],
+    /// Visible height in world units
+    scale: f64,
+    pub min_scale: f64,
+    pub max_scale: f64,
+    /// Scale factor per wheel step
+    pub zoom_speed: f64,
+    /// Seconds to cover most of the way to the goal; 0 disables smoothing
+    pub damping: f32,
+    /// Iteration cap for the shader and the reference orbit
+    pub max_iterations: u32,
+    mouse: MouseDelta,
+    /// Reference orbit for `center`, rebuilt lazily when the centre moves
+    orbit: Vec<[f32; 2]>,
+    orbit_center: Option<([DoubleDouble; 2], u32)>,
+}
+
+impl Default for DeepZoomViewport {
+    fn default() -> Self {
+        Self::new(-0.5, 0.0, 3.0)
+    }
+}
+
+impl DeepZoomViewport {
+    pub fn new(center_x: f64, center_y: f64, scale: f64) -> Self {
+        let center = [DoubleDouble::new(center_x), DoubleDouble::new(center_y)];
+        Self {
+            goal_center: center,
+            goal_scale: scale,
+            center,
+            scale,
+            min_scale: 1e-30,
+            max_scale: 10.0,
+            zoom_speed: 0.15,
+            damping: 0.08,
+            max_iterations: 1000,
+            mouse: MouseDelta::default(),
+            orbit: Vec::new(),
+            orbit_center: None,
+        }
+    }
+
+    /// Current (smoothed) centre
+    pub fn center(&self) -> [DoubleDouble; 2] {
+        self.center
+    }
+
+    /// Current (smoothed) visible height
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Jumps to a view without smoothing
+    pub fn set_view(&mut self, center: [DoubleDouble; 2], scale: f64) {
+        self.goal_center = center;
+        self.goal_scale = scale.clamp(self.min_scale, self.max_scale);
+        self.center = self.goal_center;
+        self.scale = self.goal_scale;
+    }
+
+    /// World offset from the goal centre of normalized window coordinates (0..1, y down)
+    fn offset(&self, position: [f32; 2], aspect: f32) -> [f64; 2] {
+        [
+            (position[0] as f64 - 0.5) * self.goal_scale * aspect as f64,
+            (0.5 - position[1] as f64) * self.goal_scale,
+        ]
+    }
+
+    /// Multiplies the visible height by `factor`, keeping the point under `position` fixed
+    pub fn zoom_at(&mut self, position: [f32; 2], factor: f64, aspect: f32) {
+        let scale = (self.goal_scale * factor).clamp(self.min_scale, self.max_scale);
+        let offset = self.offset(position, aspect);
+        let k = 1.0 - scale / self.goal_scale;
+        self.goal_center = [self.goal_center[0] + offset[0] * k, self.goal_center[1] + offset[1] * k];
+        self.goal_scale = scale;
+    }
+
+    /// Moves the view by a normalized window delta (0..1 per window, y down)
+    pub fn pan(&mut self, delta: [f32; 2], aspect: f32) {
+        let dx = -(delta[0] as f64) * self.goal_scale * aspect as f64;
+        let dy = delta[1] as f64 * self.goal_scale;
+        self.goal_center = [self.goal_center[0] + dx, self.goal_center[1] + dy];
+    }
+
+    /// Reads mouse input and advances the smoothing. Returns true if the view moved.
+    pub fn update(&mut self, mouse: &MouseTracker, aspect: f32, dt: f32) -> bool {
+        let (_, moved, wheel, buttons) = self.mouse.read(mouse);
+        if buttons & LEFT_BUTTON != 0 {
+            self.pan(moved, aspect);
+        }
+        if wheel != 0.0 {
+            self.zoom_at(mouse.uniform.position, (1.0 - self.zoom_speed).powf(wheel as f64), aspect);
+        }
+
+        let t = smoothing(self.damping, dt) as f64;
+        let before = (self.center, self.scale);
+        for (current, goal) in self.center.iter_mut().zip(self.goal_center) {
+            let remaining = goal - *current;
+            *current = if remaining.to_f64().abs() <= self.goal_scale * 1e-6 {
+                goal
+            } else {
+                *current + remaining * t
+            };
+        }
+        // Zoom eases in log space so deep zooms move at a constant rate
+        let log_scale = self.scale.ln() + (self.goal_scale.ln() - self.scale.ln()) * t;
+        self.scale = if (log_scale - self.goal_scale.ln()).abs() < 1e-6 {
+            self.goal_scale
+        } else {
+            log_scale.exp()
+        };
+        (self.center, self.scale) != before
+    }
+
+    /// Reference orbit for the current centre, recomputed only when it or `max_iterations` changed
+    pub fn reference_orbit(&mut self) -> &[[f32; 2]] {
+        let key = (self.center, self.max_iterations);
+        if self.orbit_center != Some(key) {
+            self.orbit = mandelbrot_reference_orbit(self.center, self.max_iterations, 1e6);
+            self.orbit_center = Some(key);
+        }
+        &self.orbit
+    }
+
+    pub fn uniform(&self, aspect: f32) -> DeepZoomUniform {
+        let [x_hi, x_lo] = self.center[0].to_f32_pair();
+        let [y_hi, y_lo] = self.center[1].to_f32_pair();
+        DeepZoomUniform {
+            center_hi: [x_hi, y_hi],
+            center_lo: [x_lo, y_lo],
+            scale: self.scale as f32,
+            aspect,
+            orbit_len: if self.orbit_center.is_some() { self.orbit.len() as u32 } else { 0 },
+            max_iterations: self.max_iterations,
+        }
+    }
+}
+
+/// GPU side of a `DeepZoomViewport`: the `DeepZoomUniform` at `@binding(0)` and the reference
+/// orbit at `@binding(1)`, laid out by `create_deep_zoom_bind_group_layout`.
+pub struct DeepZoomBuffers {
+    pub uniform_buffer: wgpu::Buffer,
+    pub orbit_buffer: wgpu::Buffer,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    orbit_capacity: usize,
+}
+
+impl DeepZoomBuffers {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Deep Zoom Uniform Buffer"),
+            size: std::mem::size_of::<DeepZoomUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = create_deep_zoom_bind_group_layout(device);
+        let orbit_capacity = 1024;
+        let orbit_buffer = Self::create_orbit_buffer(device, orbit_capacity);
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &uniform_buffer, &orbit_buffer);
+        Self {
+            uniform_buffer,
+            orbit_buffer,
+            bind_group_layout,
+            bind_group,
+            orbit_capacity,
+        }
+    }
+
+    fn create_orbit_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Deep Zoom Reference Orbit"),
+            size: (capacity * std::mem::size_of::<[f32; 2]>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        orbit_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: orbit_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Deep Zoom Bind Group"),
+        })
+    }
+
+    /// Uploads the viewport's uniform and (when it changed) reference orbit. Returns true if
+    /// the orbit buffer grew, which replaces `bind_group`.
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, viewport: &mut DeepZoomViewport, aspect: f32) -> bool {
+        let stale = viewport.orbit_center != Some((viewport.center, viewport.max_iterations));
+        let orbit = viewport.reference_orbit();
+        let mut recreated = false;
+        if orbit.len() > self.orbit_capacity {
+            self.orbit_capacity = orbit.len().next_power_of_two();
+            self.orbit_buffer = Self::create_orbit_buffer(device, self.orbit_capacity);
+            self.bind_group = Self::create_bind_group(device, &self.bind_group_layout, &self.uniform_buffer, &self.orbit_buffer);
+            recreated = true;
+        }
+        if stale || recreated {
+            queue.write_buffer(&self.orbit_buffer, 0, bytemuck::cast_slice(orbit));
+        }
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&viewport.uniform(aspect)));
+        recreated
+    }
+}
@@ -1,4 +1,6 @@
-use std::path::PathBuf;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use image::ImageError;
 
@@ -28,6 +30,10 @@ pub struct ExportSettings {
     pub total_time: f32,
     pub fps: u32,
     pub is_exporting: bool,
+    /// Write the audio pushed during the export to `audio.wav` next to the frames
+    pub export_audio: bool,
+    pub audio_sample_rate: u32,
+    pub audio_channels: u16,
 }
 
 impl Default for ExportSettings {
@@ -40,6 +46,9 @@ impl Default for ExportSettings {
             total_time: 5.0,
             fps: 60,
             is_exporting: false,
+            export_audio: false,
+            audio_sample_rate: 44100,
+            audio_channels: 2,
         }
     }
 }
@@ -52,6 +61,7 @@ pub struct ExportUiRequest {
     pub fps: u32,
     pub path: PathBuf,
     pub is_exporting: bool,
+    pub export_audio: bool,
 }
 #[derive(Default)]
 pub struct ExportUiState {
@@ -69,6 +79,8 @@ pub struct ExportManager {
     export_channel: Option<mpsc::Receiver<(u32, f32)>>,
     ui_state: ExportUiState,
     temp_state: TempExportState,
+    /// Interleaved audio collected for the current export
+    audio_samples: Vec<f32>,
 }
 
 #[derive(Clone)]
@@ -79,6 +91,7 @@ struct TempExportState {
     total_time: f32,
     fps: u32,
     path: PathBuf,
+    export_audio: bool,
 }
 
 impl ExportManager {
@@ -92,6 +105,7 @@ impl ExportManager {
             total_time: settings.total_time,
             fps: settings.fps,
             path: settings.export_path.clone(),
+            export_audio: settings.export_audio,
         };
         
         Self {
@@ -99,6 +113,7 @@ impl ExportManager {
             export_channel: None,
             ui_state,
             temp_state,
+            audio_samples: Vec::new(),
         }
     }
     pub fn get_ui_request(&self) -> ExportUiRequest {
@@ -110,6 +125,7 @@ impl ExportManager {
             fps: self.temp_state.fps,
             path: self.temp_state.path.clone(),
             is_exporting: self.settings.is_exporting,
+            export_audio: self.temp_state.export_audio,
         }
     }
     pub fn apply_ui_request(&mut self, request: ExportUiRequest) {
@@ -119,6 +135,7 @@ impl ExportManager {
         self.temp_state.total_time = request.total_time;
         self.temp_state.fps = request.fps;
         self.temp_state.path = request.path;
+        self.temp_state.export_audio = request.export_audio;
     }
     /// Returns a reference to the current export settings
     pub fn settings(&self) -> &ExportSettings {
@@ -149,6 +166,8 @@ impl ExportManager {
         self.settings.total_time = self.temp_state.total_time;
         self.settings.fps = self.temp_state.fps;
         self.settings.export_path = self.temp_state.path.clone();
+        self.settings.export_audio = self.temp_state.export_audio;
+        self.audio_samples.clear();
         
         // Then start the export process
         self.settings.is_exporting = true;
@@ -170,8 +189,39 @@ impl ExportManager {
     }
 
 
-    /// Completes the export process
+    /// Number of video frames in the current export
+    pub fn total_frames(&self) -> u32 {
+        (self.settings.total_time * self.settings.fps as f32) as u32
+    }
+
+    /// Audio frames (at `audio_sample_rate`, counted from the export start) covering video
+    /// frame `frame`. Consecutive ranges tile exactly, so the audio never drifts from the frames.
+    pub fn audio_frames_for(&self, frame: u32) -> Range<u64> {
+        let rate = self.settings.audio_sample_rate as u64;
+        let fps = self.settings.fps.max(1) as u64;
+        (frame as u64 * rate / fps)..((frame as u64 + 1) * rate / fps)
+    }
+
+    /// Appends interleaved samples (`audio_channels` per frame) to the export's audio
+    pub fn push_audio(&mut self, samples: &[f32]) {
+        if self.settings.export_audio {
+            self.audio_samples.extend_from_slice(samples);
+        }
+    }
+
+    /// Completes the export process, writing `audio.wav` if audio was collected
     pub fn complete_export(&mut self) {
+        if self.settings.is_exporting && self.settings.export_audio && !self.audio_samples.is_empty() {
+            // Pad or trim to exactly the length of the exported frames
+            let channels = self.settings.audio_channels as usize;
+            let frames = self.audio_frames_for(self.total_frames()).start as usize;
+            self.audio_samples.resize(frames * channels, 0.0);
+            let path = self.settings.export_path.join("audio.wav");
+            if let Err(e) = write_wav(&path, &self.audio_samples, self.settings.audio_sample_rate, self.settings.audio_channels) {
+                eprintln!("Error saving audio: {:?}", e);
+            }
+        }
+        self.audio_samples = Vec::new();
         self.settings.is_exporting = false;
         self.export_channel = None;
     }
@@ -233,6 +283,7 @@ impl ExportManager {
                                 .color(egui::Color32::from_rgb(150, 150, 150))
                         ));
                     });
+                    ui.checkbox(&mut request.export_audio, "Export audio (audio.wav)");
                     if !request.path.exists() {
                         ui.horizontal(|ui| {
                             ui.label(
@@ -274,6 +325,70 @@ impl ExportManager {
             self.complete_export();
         }
     }
+
+    /// Like `handle_export`, also asking `audio_fn(frame, time, audio_frames)` for exactly
+    /// the interleaved audio that plays during each frame
+    pub fn handle_export_with_audio<F, A, E>(&mut self, capture_fn: F, audio_fn: A)
+    where
+        F: FnMut(u32, f32) -> Result<Vec<u8>, E>,
+        A: FnMut(u32, f32, usize) -> Vec<f32>,
+        E: std::fmt::Debug
+    {
+        let mut capture_fn = capture_fn;
+        let mut audio_fn = audio_fn;
+        if let Some((frame, time)) = self.try_get_next_frame() {
+            if self.settings.export_audio {
+                let range = self.audio_frames_for(frame);
+                let samples = audio_fn(frame, time, (range.end - range.start) as usize);
+                self.push_audio(&samples);
+            }
+            match capture_fn(frame, time) {
+                Ok(data) => {
+                    if let Err(e) = save_frame(data, frame, self.settings()) {
+                        eprintln!("Error saving frame: {:?}", e);
+                    }
+                },
+                Err(e) => {
+                    eprintln!("Error capturing frame: {:?}", e);
+                }
+            }
+        } else {
+            self.complete_export();
+        }
+    }
+}
+
+/// Writes interleaved samples as a 32-bit float WAV file
+pub fn write_wav(path: &Path, samples: &[f32], sample_rate: u32, channels: u16) -> Result<(), ExportError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let data_len = (samples.len() * 4) as u32;
+    let block_align = channels * 4;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    file.write_all(b"RIFF")?;
+    // fmt (18 + 8) + fact (4 + 8) + data header (8) + "WAVE"
+    file.write_all(&(4 + 26 + 12 + 8 + data_len).to_le_bytes())?;
+    file.write_all(b"WAVEfmt ")?;
+    file.write_all(&18u32.to_le_bytes())?;
+    // WAVE_FORMAT_IEEE_FLOAT
+    file.write_all(&3u16.to_le_bytes())?;
+    file.write_all(&channels.to_le_bytes())?;
+    file.write_all(&sample_rate.to_le_bytes())?;
+    file.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    file.write_all(&block_align.to_le_bytes())?;
+    file.write_all(&32u16.to_le_bytes())?;
+    file.write_all(&0u16.to_le_bytes())?;
+    file.write_all(b"fact")?;
+    file.write_all(&4u32.to_le_bytes())?;
+    file.write_all(&((samples.len() / channels.max(1) as usize) as u32).to_le_bytes())?;
+    file.write_all(b"data")?;
+    file.write_all(&data_len.to_le_bytes())?;
+    for sample in samples {
+        file.write_all(&sample.to_le_bytes())?;
+    }
+    file.flush()?;
+    Ok(())
 }
 #[allow(unused_mut)]
 pub fn save_frame(mut data: Vec<u8>, frame: u32, settings: &ExportSettings) -> Result<(), ExportError> {
@@ -1,13 +1,18 @@
-use crate::{Core, UniformProvider, UniformBinding, TextureManager};
+use crate::{Core, UniformProvider, UniformBinding, TextureManager, GlyphAtlas, GlyphInstance};
 use fontdue::{Font, FontSettings};
 use std::collections::HashMap;
 use bytemuck::{Pod, Zeroable};
+use crate::sdf::coverage_to_sdf;
 
 //note that: I always use following:
 // _ATLAS_SIZE: u32 = 1024;
 // _CELL_SIZE: u32 = 64;
 // _GRID_SIZE: u32 = 16;
 
+/// Distance in atlas pixels over which the SDF in the atlas' RGB channels
+/// falls from 0.5 on the outline to 0; alpha keeps plain coverage for older shaders.
+pub const FONT_SDF_SPREAD: f32 = 8.0;
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct FontUniforms {
@@ -38,6 +43,9 @@ pub struct FontSystem {
     pub char_map: HashMap<char, CharInfo>,
     pub font_uniforms: UniformBinding<FontUniforms>,
     pub font_bind_group_layout: wgpu::BindGroupLayout,
+    /// Any character, packed on demand and addressed by glyph ID; the ASCII grid above stays
+    /// for shaders indexing `ascii % 16`.
+    pub glyph_atlas: GlyphAtlas,
 }
 
 
@@ -63,7 +71,8 @@ impl FontSystem {
                 continue;
             }
             
-            let padding = 4u32;
+            // At least the spread, so the distance field fades out before the cell edge
+            let padding = FONT_SDF_SPREAD.ceil() as u32;
             let available_width = cell_size - padding * 2;
             let available_height = cell_size - padding * 2;
             
@@ -74,35 +83,38 @@ impl FontSystem {
             let scaled_width = (metrics.width as f32 * scale) as u32;
             let scaled_height = (metrics.height as f32 * scale) as u32;
             
-            let offset_x = cell_x + padding + (available_width - scaled_width) / 2;
-            let offset_y = cell_y + padding + (available_height - scaled_height) / 2;
+            let offset_x = padding + (available_width - scaled_width) / 2;
+            let offset_y = padding + (available_height - scaled_height) / 2;
             
+            // Coverage for the whole cell, so the distance field can extend past the glyph
+            let mut cell = vec![0u8; (cell_size * cell_size) as usize];
             for y in 0..scaled_height {
                 for x in 0..scaled_width {
                     let src_x = (x as f32 / scale) as usize;
                     let src_y = (y as f32 / scale) as usize;
+                    let src_idx = src_y * metrics.width + src_x;
                     
-                    if src_x < metrics.width && src_y < metrics.height {
-                        let atlas_x = offset_x + x;
-                        let atlas_y = offset_y + y;
-                        
-                        if atlas_x < atlas_size && atlas_y < atlas_size {
-                            let atlas_idx = ((atlas_y * atlas_size + atlas_x) * 4) as usize;
-                            let src_idx = src_y * metrics.width + src_x;
-                            
-                            if atlas_idx + 3 < atlas_data.len() && src_idx < bitmap.len() {
-                                let alpha = bitmap[src_idx];
-
-                                let corrected_alpha = ((alpha as f32 / 255.0).powf(0.8) * 255.0) as u8;
-                                atlas_data[atlas_idx] = 255;     // R
-                                atlas_data[atlas_idx + 1] = 255; // G
-                                atlas_data[atlas_idx + 2] = 255; // B
-                                atlas_data[atlas_idx + 3] = corrected_alpha; // A
-                            }
-                        }
+                    if src_x < metrics.width && src_y < metrics.height && src_idx < bitmap.len() {
+                        cell[((offset_y + y) * cell_size + offset_x + x) as usize] = bitmap[src_idx];
                     }
                 }
             }
+            let sdf = coverage_to_sdf(&cell, cell_size as usize, cell_size as usize, FONT_SDF_SPREAD);
+            
+            for y in 0..cell_size {
+                for x in 0..cell_size {
+                    let cell_idx = (y * cell_size + x) as usize;
+                    let atlas_idx = (((cell_y + y) * atlas_size + cell_x + x) * 4) as usize;
+                    let alpha = cell[cell_idx];
+                    let distance = sdf[cell_idx];
+
+                    let corrected_alpha = ((alpha as f32 / 255.0).powf(0.8) * 255.0) as u8;
+                    atlas_data[atlas_idx] = distance;     // R: signed distance
+                    atlas_data[atlas_idx + 1] = distance; // G
+                    atlas_data[atlas_idx + 2] = distance; // B
+                    atlas_data[atlas_idx + 3] = corrected_alpha; // A: coverage
+                }
+            }
         }
         
         (atlas_data, atlas_size, atlas_size)
@@ -180,6 +192,11 @@ impl FontSystem {
         );
 
         let (atlas_texture, char_map, atlas_width, atlas_height) = Self::create_font_atlas(core, &font);
+
+        let mut glyph_atlas = GlyphAtlas::new_sdf(&core.device, 48.0, FONT_SDF_SPREAD);
+        let ascii: String = (32u8..127).map(char::from).collect();
+        glyph_atlas.prepare(&font, &ascii);
+        glyph_atlas.flush(&core.device, &core.queue);
         
         //font uniforms update with actual atlas dimensions
         let mut font_system = Self {
@@ -188,6 +205,7 @@ impl FontSystem {
             char_map,
             font_uniforms,
             font_bind_group_layout,
+            glyph_atlas,
         };
         
         font_system.font_uniforms.data.atlas_size = [atlas_width as f32, atlas_height as f32];
@@ -316,6 +334,21 @@ impl FontSystem {
         self.font_uniforms.update(queue);
     }
 
+    /// Glyph ID of `ch` in `glyph_atlas`, adding it if needed; call `flush_glyphs` before rendering
+    pub fn glyph_id(&mut self, ch: char) -> u32 {
+        self.glyph_atlas.glyph_id(&self.font, ch)
+    }
+
+    /// Positions `text` at `font_size` pixels with kerning; see `GlyphAtlas::layout_text`
+    pub fn layout_text(&mut self, text: &str, font_size: f32) -> Vec<GlyphInstance> {
+        self.glyph_atlas.layout_text(&self.font, text, font_size)
+    }
+
+    /// Uploads glyphs added since the last call. True if `glyph_atlas.bind_group` was replaced.
+    pub fn flush_glyphs(&mut self, core: &Core) -> bool {
+        self.glyph_atlas.flush(&core.device, &core.queue)
+    }
+
     pub fn get_char_info(&self, ch: char) -> Option<&CharInfo> {
         self.char_map.get(&ch)
     }
@@ -0,0 +1,454 @@
+// Dynamic glyph atlas: glyphs are rasterized with fontdue on first use and shelf-packed
+// into 1024² pages of an R8 texture array, with a storage buffer describing each glyph.
+// Shaders address glyphs by ID (from `GlyphAtlas::glyph_id` / `layout_text`), not by ASCII code.
+use std::collections::HashMap;
+use fontdue::Font;
+use crate::sdf::coverage_to_sdf;
+
+pub const GLYPH_PAGE_SIZE: u32 = 1024;
+/// Empty texels kept around each glyph so linear filtering doesn't bleed between them
+const GLYPH_PADDING: u32 = 2;
+
+/// One glyph as the shader sees it, at `GlyphAtlas::raster_size` pixels.
+/// ```wgsl
+/// struct Glyph {
+///     uv_min: vec2<f32>, uv_max: vec2<f32>,  // within layer `page` of the atlas
+///     size: vec2<f32>, bearing: vec2<f32>,   // bitmap size and offset from the pen (y up), px
+///     advance: f32, page: u32, _pad0: u32, _pad1: u32,
+/// }
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GlyphEntry {
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+    pub size: [f32; 2],
+    pub bearing: [f32; 2],
+    pub advance: f32,
+    pub page: u32,
+    pub _padding: [u32; 2],
+}
+
+/// A placed glyph from `layout_text`: the top-left of its quad and its size in pixels at the
+/// requested font size (y down from the top of the first line), plus its glyph ID.
+/// ```wgsl
+/// struct GlyphInstance { position: vec2<f32>, size: vec2<f32>, glyph: u32, _pad0: u32, _pad1: u32, _pad2: u32 }
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GlyphInstance {
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    pub glyph: u32,
+    pub _padding: [u32; 3],
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+}
+
+struct AtlasPage {
+    pixels: Vec<u8>,
+    shelves: Vec<Shelf>,
+    next_y: u32,
+    /// Region written since the last flush: x0, y0, x1, y1
+    dirty: Option<[u32; 4]>,
+}
+
+impl AtlasPage {
+    fn new() -> Self {
+        Self {
+            pixels: vec![0; (GLYPH_PAGE_SIZE * GLYPH_PAGE_SIZE) as usize],
+            shelves: Vec::new(),
+            next_y: 0,
+            dirty: None,
+        }
+    }
+
+    /// Shelf packing: the shortest shelf that fits, unless it would waste more than half
+    /// the glyph's height and there is room to open a new one.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let best = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(_, shelf)| shelf.height >= height && shelf.x + width <= GLYPH_PAGE_SIZE)
+            .min_by_key(|(_, shelf)| shelf.height)
+            .map(|(i, _)| i);
+        let snug = best.filter(|&i| self.shelves[i].height <= height + height / 2 + 4);
+        let index = match (snug, best) {
+            (Some(i), _) => i,
+            _ if self.next_y + height <= GLYPH_PAGE_SIZE => {
+                self.shelves.push(Shelf { y: self.next_y, height, x: 0 });
+                self.next_y += height;
+                self.shelves.len() - 1
+            }
+            (None, Some(i)) => i,
+            (None, None) => return None,
+        };
+        let shelf = &mut self.shelves[index];
+        let position = (shelf.x, shelf.y);
+        shelf.x += width;
+        Some(position)
+    }
+
+    fn blit(&mut self, x: u32, y: u32, width: u32, height: u32, bitmap: &[u8]) {
+        for row in 0..height {
+            let dst = ((y + row) * GLYPH_PAGE_SIZE + x) as usize;
+            let src = (row * width) as usize;
+            self.pixels[dst..dst + width as usize].copy_from_slice(&bitmap[src..src + width as usize]);
+        }
+        let [x0, y0, x1, y1] = self.dirty.unwrap_or([x, y, x + width, y + height]);
+        self.dirty = Some([x0.min(x), y0.min(y), x1.max(x + width), y1.max(y + height)]);
+    }
+}
+
+/// Rasterizes glyphs on demand into a texture array (one layer per page, new pages added
+/// when full) and keeps a `GlyphEntry` per glyph ID in a storage buffer. Call `flush` before
+/// rendering to upload what changed. Bind group: `@binding(0)` `texture_2d_array<f32>`
+/// (coverage or, for `new_sdf`, distance in `r`), `@binding(1)` a linear sampler, `@binding(2)` `array<Glyph>`.
+pub struct GlyphAtlas {
+    /// Pixel size glyphs are rasterized at; layouts at other sizes scale the quads
+    pub raster_size: f32,
+    /// Set for distance-field atlases, see `new_sdf`
+    sdf_spread: Option<f32>,
+    pages: Vec<AtlasPage>,
+    glyphs: Vec<GlyphEntry>,
+    /// Font glyph index -> glyph ID
+    ids: HashMap<u16, u32>,
+    uploaded_glyphs: usize,
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub glyph_buffer: wgpu::Buffer,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    texture_layers: u32,
+    buffer_capacity: usize,
+}
+
+impl GlyphAtlas {
+    pub fn new(device: &wgpu::Device, raster_size: f32) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Glyph Atlas Bind Group Layout"),
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let texture_layers = 1;
+        let buffer_capacity = 256;
+        let texture = Self::create_texture(device, texture_layers);
+        let view = Self::create_view(&texture);
+        let glyph_buffer = Self::create_glyph_buffer(device, buffer_capacity);
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &view, &sampler, &glyph_buffer);
+        Self {
+            raster_size,
+            sdf_spread: None,
+            pages: vec![AtlasPage::new()],
+            glyphs: Vec::new(),
+            ids: HashMap::new(),
+            uploaded_glyphs: 0,
+            texture,
+            view,
+            sampler,
+            glyph_buffer,
+            bind_group_layout,
+            bind_group,
+            texture_layers,
+            buffer_capacity,
+        }
+    }
+
+    /// Stores signed distance fields instead of coverage (see `coverage_to_sdf`): `r` is 0.5 on
+    /// the outline and falls to 0 `spread` raster pixels outside. Quads include that margin,
+    /// so text stays sharp at any size and can have outlines, glows and shadows.
+    pub fn new_sdf(device: &wgpu::Device, raster_size: f32, spread: f32) -> Self {
+        Self {
+            sdf_spread: Some(spread),
+            ..Self::new(device, raster_size)
+        }
+    }
+
+    /// Distance-field spread in raster pixels, or None for a coverage atlas
+    pub fn sdf_spread(&self) -> Option<f32> {
+        self.sdf_spread
+    }
+
+    fn create_texture(device: &wgpu::Device, layers: u32) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: wgpu::Extent3d {
+                width: GLYPH_PAGE_SIZE,
+                height: GLYPH_PAGE_SIZE,
+                depth_or_array_layers: layers,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn create_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        })
+    }
+
+    fn create_glyph_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Glyph Buffer"),
+            size: (capacity * std::mem::size_of::<GlyphEntry>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+        glyph_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: glyph_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Glyph Atlas Bind Group"),
+        })
+    }
+
+    /// ID of `ch`'s glyph, rasterizing and packing it on first use. Characters the font lacks
+    /// share its missing-glyph box.
+    pub fn glyph_id(&mut self, font: &Font, ch: char) -> u32 {
+        self.glyph_id_indexed(font, font.lookup_glyph_index(ch))
+    }
+
+    fn glyph_id_indexed(&mut self, font: &Font, index: u16) -> u32 {
+        if let Some(&id) = self.ids.get(&index) {
+            return id;
+        }
+        let (metrics, mut bitmap) = font.rasterize_indexed(index, self.raster_size);
+        let (mut width, mut height) = (metrics.width as u32, metrics.height as u32);
+        let mut bearing = [metrics.xmin as f32, metrics.ymin as f32];
+        if let (Some(spread), true) = (self.sdf_spread, width > 0 && height > 0) {
+            // Room for the field to fall off outside the outline
+            let margin = spread.ceil() as u32 + 1;
+            let (padded_width, padded_height) = (width + margin * 2, height + margin * 2);
+            let mut padded = vec![0u8; (padded_width * padded_height) as usize];
+            for (row, line) in bitmap.chunks(width as usize).enumerate() {
+                let start = ((row as u32 + margin) * padded_width + margin) as usize;
+                padded[start..start + width as usize].copy_from_slice(line);
+            }
+            bitmap = coverage_to_sdf(&padded, padded_width as usize, padded_height as usize, spread);
+            (width, height) = (padded_width, padded_height);
+            bearing = [bearing[0] - margin as f32, bearing[1] - margin as f32];
+        }
+        let bitmap_width = width as usize;
+        let mut entry = GlyphEntry {
+            size: [width as f32, height as f32],
+            bearing,
+            advance: metrics.advance_width,
+            ..Default::default()
+        };
+        if width > 0 && height > 0 {
+            let (w, h) = (width + GLYPH_PADDING * 2, height + GLYPH_PADDING * 2);
+            let allocation = self.pages.last_mut().and_then(|page| page.allocate(w, h));
+            let (x, y) = match allocation {
+                Some(position) => position,
+                None => {
+                    let mut page = AtlasPage::new();
+                    let position = page.allocate(w, h).unwrap_or((0, 0));
+                    self.pages.push(page);
+                    position
+                }
+            };
+            let (x, y) = (x + GLYPH_PADDING, y + GLYPH_PADDING);
+            let page = self.pages.len() - 1;
+            // Glyphs larger than a page are clipped rather than dropped, and drawn at the clipped
+            // size so the quad still matches its UVs
+            let (width, height) = (width.min(GLYPH_PAGE_SIZE - x), height.min(GLYPH_PAGE_SIZE - y));
+            entry.size = [width as f32, height as f32];
+            let clipped: Vec<u8> = bitmap
+                .chunks(bitmap_width)
+                .take(height as usize)
+                .flat_map(|row| row[..width as usize].iter().copied())
+                .collect();
+            self.pages[page].blit(x, y, width, height, &clipped);
+            let size = GLYPH_PAGE_SIZE as f32;
+            entry.uv_min = [x as f32 / size, y as f32 / size];
+            entry.uv_max = [(x + width) as f32 / size, (y + height) as f32 / size];
+            entry.page = page as u32;
+        }
+        let id = self.glyphs.len() as u32;
+        self.glyphs.push(entry);
+        self.ids.insert(index, id);
+        id
+    }
+
+    /// Makes sure every character of `text` is in the atlas
+    pub fn prepare(&mut self, font: &Font, text: &str) {
+        for ch in text.chars() {
+            self.glyph_id(font, ch);
+        }
+    }
+
+    pub fn glyph(&self, id: u32) -> Option<&GlyphEntry> {
+        self.glyphs.get(id as usize)
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Lays out `text` at `font_size` pixels with fontdue's advances and kerning. `\n` starts
+    /// a new line; whitespace advances the pen without producing an instance.
+    pub fn layout_text(&mut self, font: &Font, text: &str, font_size: f32) -> Vec<GlyphInstance> {
+        let scale = font_size / self.raster_size;
+        let (ascent, line_height) = font
+            .horizontal_line_metrics(font_size)
+            .map(|m| (m.ascent, m.new_line_size))
+            .unwrap_or((font_size * 0.8, font_size * 1.2));
+        let mut instances = Vec::with_capacity(text.len());
+        let (mut pen_x, mut baseline) = (0.0f32, ascent);
+        let mut previous: Option<u16> = None;
+        for ch in text.chars() {
+            if ch == '\n' {
+                pen_x = 0.0;
+                baseline += line_height;
+                previous = None;
+                continue;
+            }
+            let index = font.lookup_glyph_index(ch);
+            if let Some(left) = previous {
+                pen_x += font.horizontal_kern_indexed(left, index, font_size).unwrap_or(0.0);
+            }
+            let id = self.glyph_id_indexed(font, index);
+            let entry = self.glyphs[id as usize];
+            if entry.size[0] > 0.0 && entry.size[1] > 0.0 {
+                let size = [entry.size[0] * scale, entry.size[1] * scale];
+                instances.push(GlyphInstance {
+                    position: [pen_x + entry.bearing[0] * scale, baseline - (entry.bearing[1] * scale + size[1])],
+                    size,
+                    glyph: id,
+                    ..Default::default()
+                });
+            }
+            pen_x += entry.advance * scale;
+            previous = Some(index);
+        }
+        instances
+    }
+
+    /// Uploads new glyphs and dirty page regions. Returns true if the texture or glyph buffer
+    /// had to grow, which replaces `bind_group` (and `view`).
+    pub fn flush(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        let mut recreated = false;
+        if self.pages.len() as u32 > self.texture_layers {
+            self.texture_layers = (self.pages.len() as u32).next_power_of_two();
+            self.texture = Self::create_texture(device, self.texture_layers);
+            self.view = Self::create_view(&self.texture);
+            for page in &mut self.pages {
+                page.dirty = Some([0, 0, GLYPH_PAGE_SIZE, GLYPH_PAGE_SIZE]);
+            }
+            recreated = true;
+        }
+        if self.glyphs.len() > self.buffer_capacity {
+            self.buffer_capacity = self.glyphs.len().next_power_of_two();
+            self.glyph_buffer = Self::create_glyph_buffer(device, self.buffer_capacity);
+            self.uploaded_glyphs = 0;
+            recreated = true;
+        }
+        if recreated {
+            self.bind_group = Self::create_bind_group(device, &self.bind_group_layout, &self.view, &self.sampler, &self.glyph_buffer);
+        }
+
+        for (layer, page) in self.pages.iter_mut().enumerate() {
+            let Some([x0, y0, x1, y1]) = page.dirty.take() else {
+                continue;
+            };
+            let offset = (y0 * GLYPH_PAGE_SIZE + x0) as usize;
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: x0, y: y0, z: layer as u32 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &page.pixels[offset..],
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(GLYPH_PAGE_SIZE),
+                    rows_per_image: None,
+                },
+                wgpu::Extent3d {
+                    width: x1 - x0,
+                    height: y1 - y0,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        if self.uploaded_glyphs < self.glyphs.len() {
+            let offset = (self.uploaded_glyphs * std::mem::size_of::<GlyphEntry>()) as u64;
+            queue.write_buffer(&self.glyph_buffer, offset, bytemuck::cast_slice(&self.glyphs[self.uploaded_glyphs..]));
+            self.uploaded_glyphs = self.glyphs.len();
+        }
+        recreated
+    }
+}
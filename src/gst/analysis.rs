@@ -0,0 +1,310 @@
+use arc_swap::ArcSwap;
+use gstreamer as gst;
+use gst::glib;
+use gst::prelude::*;
+use log::{error, warn};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub struct SpectrumData {
+    /// Number of frequency bands
+    pub bands: usize,
+    /// Magnitude values for each frequency band in dB, power-averaged over all channels
+    pub magnitudes: Vec<f32>,
+    /// Phase values for each frequency band of the first channel
+    pub phases: Option<Vec<f32>>,
+    /// Magnitudes per channel (left, right, ...) in dB
+    pub channel_magnitudes: Vec<Vec<f32>>,
+    /// Phases per channel, empty unless the analyzer posts phases
+    pub channel_phases: Vec<Vec<f32>>,
+    /// Stream time of the spectrum data
+    pub timestamp: Option<gst::ClockTime>,
+    /// Sample rate negotiated on the analyzer's input, when known
+    pub sample_rate: Option<u32>,
+}
+
+/// Per-channel loudness from a `level` element, all in dB
+#[derive(Debug, Clone, Default)]
+pub struct LevelData {
+    pub rms: Vec<f32>,
+    pub peak: Vec<f32>,
+    /// Peak with falloff, as shown by a VU meter
+    pub decay: Vec<f32>,
+    /// Stream time of the level data
+    pub timestamp: Option<gst::ClockTime>,
+}
+
+/// Reads a float field that is a `gst::List` for a single-channel analysis, or a
+/// `gst::Array` with one `gst::Array` per channel when `multi-channel` is set
+/// (inner lists are accepted too)
+fn channel_floats(structure: &gst::StructureRef, field: &str) -> Option<Vec<Vec<f32>>> {
+    let value = structure.value(field).ok()?;
+    let floats = |values: &[glib::SendValue]| -> Vec<f32> {
+        values.iter().filter_map(|v| v.get::<f32>().ok()).collect()
+    };
+    if let Ok(list) = value.get::<gst::List>() {
+        return Some(vec![floats(list.as_slice())]);
+    }
+    if let Ok(array) = value.get::<gst::Array>() {
+        return Some(
+            array
+                .as_slice()
+                .iter()
+                .filter_map(|channel| {
+                    if let Ok(inner) = channel.get::<gst::Array>() {
+                        Some(floats(inner.as_slice()))
+                    } else {
+                        channel.get::<gst::List>().ok().map(|list| floats(list.as_slice()))
+                    }
+                })
+                .collect(),
+        );
+    }
+    None
+}
+
+/// Reads one of the `level` element's per-channel double arrays
+fn level_values(structure: &gst::StructureRef, field: &str) -> Vec<f32> {
+    let Ok(value) = structure.value(field) else {
+        return Vec::new();
+    };
+    let doubles = |values: &[glib::Value]| -> Vec<f32> {
+        values.iter().filter_map(|v| v.get::<f64>().ok()).map(|v| v as f32).collect()
+    };
+    // GValueArray on current GStreamer, GstValueArray on some older releases
+    if let Ok(array) = value.get::<glib::ValueArray>() {
+        return doubles(&array);
+    }
+    if let Ok(array) = value.get::<gst::Array>() {
+        return array.as_slice().iter().filter_map(|v| v.get::<f64>().ok()).map(|v| v as f32).collect();
+    }
+    Vec::new()
+}
+
+fn stream_time(structure: &gst::StructureRef) -> Option<gst::ClockTime> {
+    structure.get::<u64>("stream-time").ok().map(gst::ClockTime::from_nseconds)
+}
+
+/// Averages per-channel dB values in the power domain
+fn mix_channels(channels: &[Vec<f32>]) -> Vec<f32> {
+    match channels {
+        [] => Vec::new(),
+        [mono] => mono.clone(),
+        _ => {
+            let bands = channels.iter().map(Vec::len).min().unwrap_or(0);
+            (0..bands)
+                .map(|band| {
+                    let power: f32 = channels
+                        .iter()
+                        .map(|channel| 10f32.powf(channel[band] / 10.0))
+                        .sum();
+                    10.0 * (power / channels.len() as f32).log10()
+                })
+                .collect()
+        }
+    }
+}
+
+/// Decodes a `spectrum` element message, mono or multi-channel
+pub(crate) fn parse_spectrum_message(structure: &gst::StructureRef) -> Option<SpectrumData> {
+    if !structure.has_name("spectrum") {
+        return None;
+    }
+    let channel_magnitudes = channel_floats(structure, "magnitude")?;
+    let channel_phases = channel_floats(structure, "phase").unwrap_or_default();
+    let magnitudes = mix_channels(&channel_magnitudes);
+    if magnitudes.is_empty() {
+        return None;
+    }
+    Some(SpectrumData {
+        bands: magnitudes.len(),
+        magnitudes,
+        phases: channel_phases.first().cloned(),
+        channel_magnitudes,
+        channel_phases,
+        timestamp: stream_time(structure),
+        sample_rate: None,
+    })
+}
+
+/// `rate` field of negotiated audio caps
+pub(crate) fn caps_rate(caps: &gst::CapsRef) -> Option<u32> {
+    let rate = caps.structure(0)?.get::<i32>("rate").ok()?;
+    u32::try_from(rate).ok().filter(|&rate| rate > 0)
+}
+
+/// Sample rate on the sink pad of the element that posted `message`
+fn source_rate(message: &gst::Message) -> Option<u32> {
+    let element = message.src()?.downcast_ref::<gst::Element>()?;
+    caps_rate(&element.static_pad("sink")?.current_caps()?)
+}
+
+/// Decodes a `level` element message
+pub(crate) fn parse_level_message(structure: &gst::StructureRef) -> Option<LevelData> {
+    if !structure.has_name("level") {
+        return None;
+    }
+    Some(LevelData {
+        rms: level_values(structure, "rms"),
+        peak: level_values(structure, "peak"),
+        decay: level_values(structure, "decay"),
+        timestamp: stream_time(structure),
+    })
+}
+
+/// Latest analysis results published by the bus handler. Cloning is cheap and reads
+/// never block the streaming thread, so the render thread can sample it every frame.
+#[derive(Clone)]
+pub struct AnalysisSnapshot {
+    spectrum: Arc<ArcSwap<SpectrumData>>,
+    level: Arc<ArcSwap<LevelData>>,
+    bpm: Arc<AtomicU32>,
+}
+
+impl Default for AnalysisSnapshot {
+    fn default() -> Self {
+        Self {
+            spectrum: Arc::new(ArcSwap::from_pointee(SpectrumData::default())),
+            level: Arc::new(ArcSwap::from_pointee(LevelData::default())),
+            bpm: Arc::new(AtomicU32::new(0.0f32.to_bits())),
+        }
+    }
+}
+
+impl AnalysisSnapshot {
+    pub fn spectrum(&self) -> Arc<SpectrumData> {
+        self.spectrum.load_full()
+    }
+
+    pub fn level(&self) -> Arc<LevelData> {
+        self.level.load_full()
+    }
+
+    pub fn bpm(&self) -> f32 {
+        f32::from_bits(self.bpm.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn publish_spectrum(&self, data: SpectrumData) {
+        self.spectrum.store(Arc::new(data));
+    }
+}
+
+/// Owner side of the handler installed by `install_analysis_handler`
+pub(crate) struct AnalysisBus {
+    pub snapshot: AnalysisSnapshot,
+    /// When set, spectrum messages go to `history` instead of the snapshot so the
+    /// caller can publish the one matching the frame it is rendering
+    recording: Arc<AtomicBool>,
+    history: Receiver<SpectrumData>,
+}
+
+impl AnalysisBus {
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.recording.store(enabled, Ordering::Release);
+        self.history.try_iter().for_each(drop);
+    }
+
+    /// Spectrum messages received while recording, oldest first
+    pub fn drain_history(&self) -> impl Iterator<Item = SpectrumData> + '_ {
+        self.history.try_iter()
+    }
+}
+
+/// Folds a bpmdetect estimate into the current tempo, handling its octave ambiguity
+/// (https://www.ifs.tuwien.ac.at/~knees/publications/hoerschlaeger_etal_smc_2015.pdf)
+pub(crate) fn fold_bpm(current_bpm: f32, bpm_val: f32) -> f32 {
+    let in_range = |bpm: f32| (70.0..=150.0).contains(&bpm);
+    if current_bpm == 0.0 {
+        // First detection - apply preference for 70-150 BPM range
+        if bpm_val > 150.0 {
+            bpm_val / 2.0
+        } else if bpm_val < 70.0 {
+            bpm_val * 2.0
+        } else {
+            bpm_val
+        }
+    } else if (bpm_val > current_bpm * 1.8 && bpm_val < current_bpm * 2.2)
+        || (bpm_val > current_bpm * 0.45 && bpm_val < current_bpm * 0.55)
+    {
+        // Double or half tempo detected - stay in preferred range if possible
+        if !in_range(current_bpm) && in_range(bpm_val) {
+            bpm_val
+        } else {
+            current_bpm
+        }
+    } else {
+        // Apply light smoothing to avoid jumps
+        current_bpm * 0.8 + bpm_val * 0.2
+    }
+}
+
+/// Handles spectrum, level and BPM messages on the streaming thread that posts them.
+///
+/// Analysis element messages are consumed here so they never pile up on the bus; every
+/// other message is passed on. Tempo estimates are folded in with `fold_bpm`.
+pub(crate) fn install_analysis_handler(bus: &gst::Bus) -> AnalysisBus {
+    let snapshot = AnalysisSnapshot::default();
+    let recording = Arc::new(AtomicBool::new(false));
+    let (sender, history) = mpsc::channel();
+
+    let tempo = snapshot.bpm.clone();
+    let update_bpm = move |detected: f32| {
+        if detected > 0.0 {
+            let current = f32::from_bits(tempo.load(Ordering::Relaxed));
+            tempo.store(fold_bpm(current, detected).to_bits(), Ordering::Relaxed);
+        }
+    };
+    let published = snapshot.clone();
+    let recording_flag = recording.clone();
+    bus.set_sync_handler(move |_, message| {
+        match message.view() {
+            gst::MessageView::Element(element) => {
+                let Some(structure) = element.structure() else {
+                    return gst::BusSyncReply::Pass;
+                };
+                if structure.has_name("spectrum") {
+                    if let Some(mut data) = parse_spectrum_message(structure) {
+                        data.sample_rate = source_rate(message);
+                        if recording_flag.load(Ordering::Acquire) {
+                            let _ = sender.send(data);
+                        } else {
+                            published.publish_spectrum(data);
+                        }
+                    }
+                    return gst::BusSyncReply::Drop;
+                }
+                if let Some(level) = parse_level_message(structure) {
+                    published.level.store(Arc::new(level));
+                    return gst::BusSyncReply::Drop;
+                }
+                if structure.name().contains("bpm") {
+                    if let Ok(bpm) = structure.get::<f32>("bpm") {
+                        update_bpm(bpm);
+                    }
+                    return gst::BusSyncReply::Drop;
+                }
+            }
+            gst::MessageView::Tag(tag) => {
+                if let Some(bpm) = tag.tags().get::<gst::tags::BeatsPerMinute>() {
+                    update_bpm(bpm.get() as f32);
+                }
+            }
+            gst::MessageView::Error(err) => {
+                error!("Pipeline error: {} ({})", err.error(), err.debug().unwrap_or_default());
+            }
+            gst::MessageView::Warning(warning) => {
+                warn!("Pipeline warning: {}", warning.error());
+            }
+            _ => (),
+        }
+        gst::BusSyncReply::Pass
+    });
+
+    AnalysisBus {
+        snapshot,
+        recording,
+        history,
+    }
+}
@@ -1,305 +1,138 @@
 use anyhow::{Result, anyhow};
-use gstreamer as gst;
 use log::{debug, info, warn};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use gst::prelude::*;
-use gst::glib::ControlFlow;
-
-/// Individual audio voice for polyphonic synthesis
-/// Based: https://gstreamer.freedesktop.org/documentation/audiotestsrc/index.html?gi-language=c
-/// Sample rate: 44100
-struct AudioVoice {
-    /// The audiotestsrc element for this 'voice'
-    audiotestsrc: gst::Element,
-    /// The volume control element for this voice
-    volume: gst::Element,
-    /// Current frequency being generated
-    frequency: f64,
-    /// Current volume (0.0 to 1.0)
-    volume_level: f64,
-    /// Whether this voice is active
-    is_active: bool,
-    /// Musical note this voice is playing
-    note: Option<MusicalNote>,
-}
+use super::pcm::{PcmStream, PcmStreamConfig};
+use super::synth_engine::{SynthEngine, SynthConfig, AdsrConfig, EnvelopeStage, VoiceState, MAX_SYNTH_VOICES};
+use crate::midi::{MidiEvent, MidiSequencer, midi_note_frequency};
+
+/// Frames rendered between sequencer updates offline, ~1.5 ms at 44.1 kHz
+const MIDI_RENDER_STEP: usize = 64;
 
-/// Audio synthesis manager that can generate multiple tones simultaneously using GStreamer
+/// Audio synthesis manager that can generate multiple tones simultaneously. Voices are
+/// rendered at sample rate by a `SynthEngine` (ADSR, filter, unison, delay, reverb) and
+/// played through an `appsrc` pipeline.
 pub struct AudioSynthManager {
-    /// The GStreamer pipeline for audio generation
-    pipeline: gst::Pipeline,
-    /// The audio mixer element
-    _audiomixer: gst::Element,
-    /// Multiple voices for polyphonic synthesis
-    voices: Vec<AudioVoice>,
-    /// Current waveform type
-    current_waveform: AudioWaveform,
-    /// Master volume (0.0 to 1.0)
-    master_volume: Arc<Mutex<f64>>,
+    /// Output pipeline pulling blocks from the engine
+    stream: PcmStream,
+    /// Voices and effects, shared with the streaming thread
+    engine: Arc<Mutex<SynthEngine>>,
+    /// Musical note held on each voice
+    voice_notes: [Option<MusicalNote>; MAX_SYNTH_VOICES],
+    /// MIDI note number held on each voice
+    voice_midi: [Option<u8>; MAX_SYNTH_VOICES],
     /// Sample rate for audio generation
     sample_rate: u32,
     /// Last update time
     last_update: Instant,
-    /// Currently playing notes
-    active_notes: Arc<Mutex<Vec<MusicalNote>>>,
 }
 
 impl AudioSynthManager {
     pub fn new(sample_rate: Option<u32>) -> Result<Self> {
         let sample_rate = sample_rate.unwrap_or(44100);
-        // Support up to 8 simultaneous notes
-        let max_voices = 8;
-
-        info!("Creating polyphonic audio synthesis manager with {} voices at {} Hz", max_voices, sample_rate);
-
-        let pipeline = gst::Pipeline::new();
-
-        // Create audio mixer for combining multiple voices
-        let audiomixer = gst::ElementFactory::make("audiomixer")
-            .name("audio_mixer")
-            .build()
-            .map_err(|_| anyhow!("Failed to create audiomixer element"))?;
-
-        // Create final audio conversion and output chain
-        let final_convert = gst::ElementFactory::make("audioconvert")
-            .name("final_convert")
-            .build()
-            .map_err(|_| anyhow!("Failed to create final audioconvert element"))?;
-
-        let final_resample = gst::ElementFactory::make("audioresample")
-            .name("final_resample")
-            .build()
-            .map_err(|_| anyhow!("Failed to create final audioresample element"))?;
-
-        let master_volume = gst::ElementFactory::make("volume")
-            .name("master_volume")
-            .property("volume", 0.3f64)
-            .build()
-            .map_err(|_| anyhow!("Failed to create master volume element"))?;
-
-        let audiosink = gst::ElementFactory::make("autoaudiosink")
-            .name("audio_sink")
-            .build()
-            .map_err(|_| anyhow!("Failed to create autoaudiosink element"))?;
-
-        // A mixer and output chain to pipeline
-        pipeline.add_many(&[
-            &audiomixer,
-            &final_convert,
-            &final_resample,
-            &master_volume,
-            &audiosink,
-        ])
-        .map_err(|_| anyhow!("Failed to add output elements to pipeline"))?;
-
-        gst::Element::link_many(&[
-            &audiomixer,
-            &final_convert,
-            &final_resample,
-            &master_volume,
-            &audiosink,
-        ])
-        .map_err(|_| anyhow!("Failed to link output elements"))?;
-
-        // Create voices
-        let mut voices = Vec::new();
-        for i in 0..max_voices {
-            let voice = Self::create_voice(&pipeline, &audiomixer, i, sample_rate)?;
-            voices.push(voice);
-        }
-
-        // bus watch for error handling stuff
-        let bus = pipeline.bus().expect("Pipeline has no bus");
-        let _ = bus.add_watch(move |_, message| {
-            match message.view() {
-                gst::MessageView::Error(err) => {
-                    warn!("Audio pipeline error: {} ({})", err.error(), err.debug().unwrap_or_default());
-                },
-                gst::MessageView::Warning(warning) => {
-                    debug!("Audio pipeline warning: {}", warning.error());
-                },
-                gst::MessageView::Eos(_) => {
-                    info!("Audio pipeline reached end of stream");
-                },
-                _ => (),
-            }
-            ControlFlow::Continue
-        });
-
-        let audio_synth = Self {
-            pipeline,
-            _audiomixer: audiomixer,
-            voices,
-            current_waveform: AudioWaveform::Sine,
-            master_volume: Arc::new(Mutex::new(0.3)),
-            sample_rate,
-            last_update: Instant::now(),
-            active_notes: Arc::new(Mutex::new(Vec::new())),
-        };
+
+        info!("Creating polyphonic audio synthesis manager with {} voices at {} Hz", MAX_SYNTH_VOICES, sample_rate);
+
+        let engine = Arc::new(Mutex::new(SynthEngine::new(sample_rate)));
+        let render_engine = engine.clone();
+        let stream = PcmStream::with_generator(
+            PcmStreamConfig {
+                sample_rate,
+                channels: 2,
+                // ~6 ms blocks so note on/off lands quickly
+                block_frames: 256,
+                jitter_frames: 0,
+                volume: 1.0,
+            },
+            move |block| {
+                if let Ok(mut engine) = render_engine.lock() {
+                    engine.render(block);
+                }
+            },
+        )?;
 
         info!("Polyphonic audio synthesis manager created successfully");
-        Ok(audio_synth)
-    }
-
-    fn create_voice(pipeline: &gst::Pipeline, mixer: &gst::Element, voice_id: usize, _sample_rate: u32) -> Result<AudioVoice> {
-        // Create audiotestsrc for this voice
-        let audiotestsrc = gst::ElementFactory::make("audiotestsrc")
-            .name(&format!("voice_{}_source", voice_id))
-            .property("freq", 440.0f64)
-            .property("volume", 0.0f64)  // Start silent
-            .property("samplesperbuffer", 512i32)
-            .property("is-live", true)
-            .build()
-            .map_err(|_| anyhow!("Failed to create audiotestsrc for voice {}", voice_id))?;
-
-        //default waveform:
-        audiotestsrc.set_property_from_str("wave", "sine");
-
-        // lets create voice-specific elements
-        let audioconvert = gst::ElementFactory::make("audioconvert")
-            .name(&format!("voice_{}_convert", voice_id))
-            .build()
-            .map_err(|_| anyhow!("Failed to create audioconvert for voice {}", voice_id))?;
-
-        let audioresample = gst::ElementFactory::make("audioresample")
-            .name(&format!("voice_{}_resample", voice_id))
-            .build()
-            .map_err(|_| anyhow!("Failed to create audioresample for voice {}", voice_id))?;
-
-        let volume = gst::ElementFactory::make("volume")
-            .name(&format!("voice_{}_volume", voice_id))
-            .property("volume", 0.0f64)
-            .build()
-            .map_err(|_| anyhow!("Failed to create volume for voice {}", voice_id))?;
-
-        //voice elements to our pipeline...
-        pipeline.add_many(&[
-            &audiotestsrc,
-            &audioconvert,
-            &audioresample,
-            &volume,
-        ])
-        .map_err(|_| anyhow!("Failed to add voice {} elements to pipeline", voice_id))?;
-
-        // link:
-        gst::Element::link_many(&[
-            &audiotestsrc,
-            &audioconvert,
-            &audioresample,
-            &volume,
-        ])
-        .map_err(|_| anyhow!("Failed to link voice {} elements", voice_id))?;
-
-        // Connect to mixer
-        volume.link(mixer)
-            .map_err(|_| anyhow!("Failed to link voice {} to mixer", voice_id))?;
-
-        Ok(AudioVoice {
-            audiotestsrc,
-            volume,
-            frequency: 440.0,
-            volume_level: 0.0,
-            is_active: false,
-            note: None,
+        Ok(Self {
+            stream,
+            engine,
+            voice_notes: [None; MAX_SYNTH_VOICES],
+            voice_midi: [None; MAX_SYNTH_VOICES],
+            sample_rate,
+            last_update: Instant::now(),
         })
     }
 
+    fn engine(&self) -> std::sync::MutexGuard<'_, SynthEngine> {
+        self.engine.lock().unwrap()
+    }
+
     /// audio generation
     pub fn start(&mut self) -> Result<()> {
         info!("Starting polyphonic audio synthesis");
-        match self.pipeline.set_state(gst::State::Playing) {
-            Ok(_) => {
-                std::thread::sleep(std::time::Duration::from_millis(50));
-                Ok(())
-            },
-            Err(e) => Err(anyhow!("Failed to start audio synthesis: {:?}", e))
-        }
+        self.stream.start()
     }
 
     // Stop all voices
     pub fn stop(&mut self) -> Result<()> {
         info!("Stopping audio synthesis");
-        for voice in &mut self.voices {
-            voice.volume.set_property("volume", 0.0f64);
-            voice.is_active = false;
-            voice.note = None;
-        }
-        self.active_notes.lock().unwrap().clear();
-
-        match self.pipeline.set_state(gst::State::Null) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(anyhow!("Failed to stop audio synthesis: {:?}", e))
-        }
+        self.engine().reset();
+        self.voice_notes = [None; MAX_SYNTH_VOICES];
+        self.voice_midi = [None; MAX_SYNTH_VOICES];
+        self.stream.stop()
     }
 
     pub fn set_master_volume(&mut self, vol: f64) -> Result<()> {
-        let clamped_volume = vol.max(0.0).min(1.0);
-        *self.master_volume.lock().unwrap() = clamped_volume;
-
-        // Find master volume element
-        if let Some(master_vol) = self.pipeline.by_name("master_volume") {
-            master_vol.set_property("volume", clamped_volume);
-        }
-
+        let clamped_volume = vol.clamp(0.0, 1.0);
+        self.engine().master_volume = clamped_volume as f32;
         debug!("Set master volume to {:.3}", clamped_volume);
         Ok(())
     }
 
     /// Set the waveform type for all voices
     pub fn set_waveform(&mut self, wave_type: AudioWaveform) -> Result<()> {
-        let wave_str = match wave_type {
-            AudioWaveform::Sine => "sine",
-            AudioWaveform::Square => "square",
-            AudioWaveform::Saw => "saw",
-            AudioWaveform::Triangle => "triangle",
-        };
+        self.engine().waveform = wave_type;
+        debug!("Set waveform to {:?}", wave_type);
+        Ok(())
+    }
 
-        self.current_waveform = wave_type;
+    /// Envelope, filter, unison and effect settings, applied from the next block on
+    pub fn set_config(&mut self, config: SynthConfig) {
+        self.engine().config = config;
+    }
 
-        // Update all voices
-        for voice in &mut self.voices {
-            voice.audiotestsrc.set_property_from_str("wave", wave_str);
-        }
+    pub fn config(&self) -> SynthConfig {
+        self.engine().config
+    }
 
-        debug!("Set waveform to {:?}", wave_type);
-        Ok(())
+    /// Overrides the envelope of one voice, `None` returns it to the config's envelope
+    pub fn set_voice_envelope(&mut self, voice_id: usize, envelope: Option<AdsrConfig>) {
+        self.engine().set_voice_envelope(voice_id, envelope);
     }
 
     /// Play a frequency (polyphonic - can play multiple frequencies simultaneously)
     /// This allows arbitrary frequencies, not just predefined musical notes
     pub fn play_frequency(&mut self, frequency: f64, voice_id: usize) -> Result<()> {
-        if voice_id >= self.voices.len() {
+        if voice_id >= MAX_SYNTH_VOICES {
             return Err(anyhow!("Voice ID {} out of range", voice_id));
         }
-
-        let voice = &mut self.voices[voice_id];
-        let volume_level = 0.4; // Individual voice volume
-
-        // Configure voice with arbitrary frequency
-        voice.audiotestsrc.set_property("freq", frequency);
-        voice.audiotestsrc.set_property("volume", volume_level);
-        voice.volume.set_property("volume", volume_level);
-        voice.frequency = frequency;
-        voice.volume_level = volume_level;
-        voice.is_active = true;
-         // Not tied to a specific musical note
-        voice.note = None;
+        // Individual voice volume
+        self.engine().note_on(voice_id, frequency as f32, 0.4);
+        // Not tied to a specific musical note
+        self.voice_notes[voice_id] = None;
+        self.voice_midi[voice_id] = None;
 
         info!("Playing frequency {:.2} Hz on voice {}", frequency, voice_id);
         Ok(())
     }
 
-    /// Stop a specific voice by ID
+    /// Release a specific voice by ID; it fades out over the envelope's release time
     pub fn stop_voice(&mut self, voice_id: usize) -> Result<()> {
-        if voice_id >= self.voices.len() {
+        if voice_id >= MAX_SYNTH_VOICES {
             return Err(anyhow!("Voice ID {} out of range", voice_id));
         }
-
-        let voice = &mut self.voices[voice_id];
-        voice.audiotestsrc.set_property("volume", 0.0f64);
-        voice.volume.set_property("volume", 0.0f64);
-        voice.is_active = false;
-        voice.note = None;
+        self.engine().note_off(voice_id);
+        self.voice_notes[voice_id] = None;
+        self.voice_midi[voice_id] = None;
 
         info!("Stopped voice {}", voice_id);
         Ok(())
@@ -307,46 +140,31 @@ impl AudioSynthManager {
 
     /// Update frequency and amplitude for an already active voice
     pub fn update_voice_frequency(&mut self, voice_id: usize, frequency: f64, amplitude: f64) -> Result<()> {
-        if voice_id >= self.voices.len() {
+        if voice_id >= MAX_SYNTH_VOICES {
             return Err(anyhow!("Voice ID {} out of range", voice_id));
         }
-
-        let voice = &mut self.voices[voice_id];
-        if voice.is_active {
-            voice.audiotestsrc.set_property("freq", frequency);
-            voice.volume.set_property("volume", amplitude);
-            voice.frequency = frequency;
-            voice.volume_level = amplitude;
+        let mut engine = self.engine();
+        if engine.voice_state(voice_id).is_some_and(|v| v.gate) {
+            engine.set_voice(voice_id, frequency as f32, amplitude as f32);
         }
-
         Ok(())
     }
 
     /// Play a note (polyphonic - can play multiple notes simultaneously)
-    /// Little bit tricky, I tried to up to 8 notes at once 
     pub fn play_note(&mut self, note: MusicalNote) -> Result<()> {
         // Check if note is already playing
-        let mut active_notes = self.active_notes.lock().unwrap();
-        if active_notes.contains(&note) {
-            return Ok(()); // Note already playing
+        if self.voice_notes.contains(&Some(note)) {
+            return Ok(());
         }
 
-        // Find an available voice
-        if let Some(voice) = self.voices.iter_mut().find(|v| !v.is_active) {
+        // Find an available voice, taking over a releasing one if needed
+        let mut engine = self.engine.lock().unwrap();
+        if let Some(voice_id) = engine.free_voice() {
             let freq = note.to_frequency();
-            let volume_level = 0.4; // Individual voice volume
-
-            // cfg voice
-            voice.audiotestsrc.set_property("freq", freq);
-            voice.audiotestsrc.set_property("volume", volume_level);
-            voice.volume.set_property("volume", volume_level);
-            voice.frequency = freq;
-            voice.volume_level = volume_level;
-            voice.is_active = true;
-            voice.note = Some(note);
-
-            active_notes.push(note);
-            info!("Playing note {:?} ({:.2} Hz) on voice", note, freq);
+            engine.note_on(voice_id, freq as f32, 0.4);
+            self.voice_notes[voice_id] = Some(note);
+            self.voice_midi[voice_id] = None;
+            info!("Playing note {:?} ({:.2} Hz) on voice {}", note, freq, voice_id);
         } else {
             warn!("No available voice for note {:?}", note);
         }
@@ -356,51 +174,122 @@ impl AudioSynthManager {
 
     /// Stop a specific note. this more likely for testing purposes for myself
     pub fn stop_note(&mut self, note: MusicalNote) -> Result<()> {
-        if let Some(voice) = self.voices.iter_mut().find(|v| v.note == Some(note)) {
-            voice.audiotestsrc.set_property("volume", 0.0f64);
-            voice.volume.set_property("volume", 0.0f64);
-            voice.is_active = false;
-            voice.note = None;
-
-            // Remove from active notes
-            let mut active_notes = self.active_notes.lock().unwrap();
-            active_notes.retain(|&n| n != note);
-
+        if let Some(voice_id) = self.voice_notes.iter().position(|&n| n == Some(note)) {
+            self.engine().note_off(voice_id);
+            self.voice_notes[voice_id] = None;
             info!("Stopped note {:?}", note);
         }
         Ok(())
     }
 
     pub fn stop_all_notes(&mut self) -> Result<()> {
-        for voice in &mut self.voices {
-            voice.audiotestsrc.set_property("volume", 0.0f64);
-            voice.volume.set_property("volume", 0.0f64);
-            voice.is_active = false;
-            voice.note = None;
+        let mut engine = self.engine.lock().unwrap();
+        for voice_id in 0..MAX_SYNTH_VOICES {
+            engine.note_off(voice_id);
         }
-        self.active_notes.lock().unwrap().clear();
+        self.voice_notes = [None; MAX_SYNTH_VOICES];
+        self.voice_midi = [None; MAX_SYNTH_VOICES];
         info!("Stopped all notes");
         Ok(())
     }
 
+    /// Play a MIDI note number (0-127) at velocity 0..1. Striking a key that is already held
+    /// retriggers its voice.
+    pub fn play_midi_note(&mut self, key: u8, velocity: f32) -> Result<()> {
+        if key as usize >= crate::midi::MIDI_NOTES {
+            return Err(anyhow!("MIDI note {} out of range", key));
+        }
+        let mut engine = self.engine.lock().unwrap();
+        let voice_id = self.voice_midi.iter().position(|&k| k == Some(key)).or_else(|| engine.free_voice());
+        if let Some(voice_id) = voice_id {
+            engine.note_on(voice_id, midi_note_frequency(key) as f32, 0.4 * velocity.clamp(0.0, 1.0));
+            self.voice_midi[voice_id] = Some(key);
+            self.voice_notes[voice_id] = None;
+        } else {
+            // Dense passages can hold more keys than there are voices
+            debug!("No available voice for MIDI note {}", key);
+        }
+        Ok(())
+    }
+
+    /// Release a MIDI note number
+    pub fn stop_midi_note(&mut self, key: u8) -> Result<()> {
+        if let Some(voice_id) = self.voice_midi.iter().position(|&k| k == Some(key)) {
+            self.engine().note_off(voice_id);
+            self.voice_midi[voice_id] = None;
+        }
+        Ok(())
+    }
+
+    /// Applies events from `MidiSequencer::update`
+    pub fn apply_midi_events(&mut self, events: &[MidiEvent]) -> Result<()> {
+        for event in events {
+            match *event {
+                MidiEvent::NoteOn { key, velocity } => self.play_midi_note(key, velocity)?,
+                MidiEvent::NoteOff { key } => self.stop_midi_note(key)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// MIDI note numbers currently held
+    pub fn active_midi_notes(&self) -> Vec<u8> {
+        self.voice_midi.iter().flatten().copied().collect()
+    }
+
     /// Get current waveform
     pub fn waveform(&self) -> AudioWaveform {
-        self.current_waveform
+        self.engine().waveform
     }
 
     /// Get master volume
     pub fn master_volume(&self) -> f64 {
-        *self.master_volume.lock().unwrap()
+        self.engine().master_volume as f64
     }
 
     /// Check if any notes are playing
     pub fn is_active(&self) -> bool {
-        !self.active_notes.lock().unwrap().is_empty()
+        self.voice_notes.iter().any(Option::is_some)
     }
 
     /// Get currently playing notes
     pub fn active_notes(&self) -> Vec<MusicalNote> {
-        self.active_notes.lock().unwrap().clone()
+        self.voice_notes.iter().flatten().copied().collect()
+    }
+
+    /// Frequency, gain and envelope state of a voice
+    pub fn voice_state(&self, voice_id: usize) -> Option<VoiceState> {
+        self.engine().voice_state(voice_id)
+    }
+
+    /// Copies every voice's frequency, level and envelope stage into `uniform`
+    pub fn fill_uniform(&self, uniform: &mut SynthesisUniform) {
+        let engine = self.engine();
+        let mut active = 0;
+        for voice_id in 0..MAX_SYNTH_VOICES {
+            let state = engine.voice_state(voice_id).unwrap_or_default();
+            let (i, c) = (voice_id / 4, voice_id % 4);
+            uniform.note_frequencies[i][c] = state.frequency;
+            uniform.note_amplitudes[i][c] = state.gain * state.level;
+            uniform.note_envelopes[i][c] = state.level;
+            uniform.note_stages[i][c] = state.stage.to_u32();
+            if state.stage != EnvelopeStage::Idle {
+                active += 1;
+            }
+        }
+        uniform.active_note_count = active;
+        uniform.master_volume = engine.master_volume;
+        uniform.waveform_type = match engine.waveform {
+            AudioWaveform::Sine => 0,
+            AudioWaveform::Square => 1,
+            AudioWaveform::Saw => 2,
+            AudioWaveform::Triangle => 3,
+        };
+        let config = engine.config;
+        uniform.filter_cutoff = if config.filter.enabled { config.filter.cutoff_hz } else { 0.0 };
+        uniform.filter_resonance = config.filter.resonance;
+        uniform.reverb_mix = config.reverb.mix;
+        uniform.delay_mix = config.delay.mix;
     }
 
     /// Get sample rate
@@ -413,12 +302,40 @@ impl AudioSynthManager {
         self.last_update = Instant::now();
         //maybe per-frame updates?
     }
-}
 
-impl Drop for AudioSynthManager {
-    fn drop(&mut self) {
-        info!("Shutting down audio synthesis pipeline");
-        let _ = self.pipeline.set_state(gst::State::Null);
+    /// Renders `frames` frames of the voices offline, exactly as the pipeline would play them.
+    /// This advances the same voices and effects as playback, so pause playback (`stop`
+    /// resets the voices) while exporting. Stereo is downmixed or duplicated to `channels`.
+    pub fn render_offline(&mut self, frames: usize, channels: usize) -> Vec<f32> {
+        let mut stereo = vec![0.0f32; frames * 2];
+        self.engine().render(&mut stereo);
+        match channels {
+            2 => stereo,
+            1 => stereo.chunks_exact(2).map(|f| (f[0] + f[1]) * 0.5).collect(),
+            _ => stereo.chunks_exact(2).flat_map(|f| (0..channels).map(move |c| f[c % 2])).collect(),
+        }
+    }
+
+    /// Renders `frames` frames starting at clock `start_time` while stepping `sequencer` every
+    /// `MIDI_RENDER_STEP` frames, so note timing in an export depends only on the clock.
+    /// Pause playback first, as with `render_offline`.
+    pub fn render_midi_offline(&mut self, sequencer: &mut MidiSequencer, start_time: f64, frames: usize, channels: usize) -> Vec<f32> {
+        let mut samples = Vec::with_capacity(frames * channels);
+        let mut rendered = 0;
+        while rendered < frames {
+            let time = start_time + rendered as f64 / self.sample_rate as f64;
+            let events = sequencer.update(time);
+            let _ = self.apply_midi_events(&events);
+            let step = MIDI_RENDER_STEP.min(frames - rendered);
+            samples.extend(self.render_offline(step, channels));
+            rendered += step;
+        }
+        samples
+    }
+
+    /// Stops pulling from the engine without resetting the voices, e.g. during an export
+    pub fn pause(&mut self) -> Result<()> {
+        self.stream.pause()
     }
 }
 
@@ -431,6 +348,18 @@ pub enum AudioWaveform {
     Triangle,
 }
 
+impl AudioWaveform {
+    /// Value at `phase` (0..1) in -1..1
+    pub fn sample(self, phase: f64) -> f64 {
+        match self {
+            AudioWaveform::Sine => (phase * std::f64::consts::TAU).sin(),
+            AudioWaveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            AudioWaveform::Saw => 2.0 * phase - 1.0,
+            AudioWaveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        }
+    }
+}
+
 /// Musical notes with their frequencies
 /// I took these from here:
 /// https://www.liutaiomottola.com/formulae/freqtab.htm
@@ -471,6 +400,25 @@ impl MusicalNote {
         }
     }
 
+    /// MIDI note number, C4 = 60
+    pub fn midi_number(self) -> u8 {
+        match self {
+            MusicalNote::C4 => 60,
+            MusicalNote::CSharp4 => 61,
+            MusicalNote::D4 => 62,
+            MusicalNote::DSharp4 => 63,
+            MusicalNote::E4 => 64,
+            MusicalNote::F4 => 65,
+            MusicalNote::FSharp4 => 66,
+            MusicalNote::G4 => 67,
+            MusicalNote::GSharp4 => 68,
+            MusicalNote::A4 => 69,
+            MusicalNote::ASharp4 => 70,
+            MusicalNote::B4 => 71,
+            MusicalNote::C5 => 72,
+        }
+    }
+
     /// Get note from keyboard number (1-9 maps to different notes)
     pub fn from_keyboard_number(num: u32) -> Option<Self> {
         match num {
@@ -669,6 +617,8 @@ pub struct SynthesisManager {
     synthesis_enabled: bool,
      // Track which voices are active
     active_voices: Vec<bool>,
+    /// Sample-level output for sound shaders, replacing the voices while running
+    pcm_stream: Option<PcmStream>,
 }
 
 impl SynthesisManager {
@@ -686,7 +636,8 @@ impl SynthesisManager {
             sample_rate: 44100,
             last_update: std::time::Instant::now(),
             synthesis_enabled: false,
-            active_voices: vec![false; 9], // Track 9 voices
+            active_voices: vec![false; MAX_SYNTH_VOICES],
+            pcm_stream: None,
         })
     }
 
@@ -699,6 +650,7 @@ impl SynthesisManager {
     }
 
     pub fn stop_gpu_synthesis(&mut self) -> anyhow::Result<()> {
+        self.stop_pcm_stream()?;
         if let Some(ref mut manager) = self.audio_manager {
             manager.stop()?;
             self.synthesis_enabled = false;
@@ -732,11 +684,12 @@ impl SynthesisManager {
 
         if let Some(ref mut manager) = self.audio_manager {
             if should_be_active && !self.active_voices[voice_id] {
-                // Start the voice
+                // Start the voice's envelope at the requested level
                 let _ = manager.play_frequency(frequency as f64, voice_id);
+                let _ = manager.update_voice_frequency(voice_id, frequency as f64, amplitude as f64);
                 self.active_voices[voice_id] = true;
             } else if !should_be_active && self.active_voices[voice_id] {
-                // Stop the voice
+                // Release the voice
                 let _ = manager.stop_voice(voice_id);
                 self.active_voices[voice_id] = false;
             } else if should_be_active && self.active_voices[voice_id] {
@@ -747,8 +700,95 @@ impl SynthesisManager {
     }
 
 
-    pub fn stream_gpu_samples(&mut self, _samples: &[f32]) {
-        // This method is kept for compatibility (my impl on CPAL approach) but not used in GStreamer implementation
+    /// Switches output to sample-level streaming: samples passed to `stream_gpu_samples`
+    /// (e.g. from `compute::SoundShader::fill`) are played instead of the voices.
+    pub fn start_pcm_stream(&mut self, config: PcmStreamConfig) -> anyhow::Result<()> {
+        if let Some(ref mut manager) = self.audio_manager {
+            manager.stop()?;
+        }
+        let stream = PcmStream::new(config)?;
+        stream.start()?;
+        self.sample_rate = config.sample_rate;
+        self.pcm_stream = Some(stream);
+        self.synthesis_enabled = true;
+        Ok(())
+    }
+
+    pub fn stop_pcm_stream(&mut self) -> anyhow::Result<()> {
+        if let Some(stream) = self.pcm_stream.take() {
+            stream.stop()?;
+            self.synthesis_enabled = false;
+            // Back to the voices' rate, which `start_pcm_stream` replaced with the stream's
+            self.sample_rate = self.audio_manager.as_ref().map_or(44100, |manager| manager.sample_rate());
+        }
+        Ok(())
+    }
+
+    /// Queues interleaved GPU-generated samples on the PCM stream (no-op unless it's running)
+    pub fn stream_gpu_samples(&mut self, samples: &[f32]) {
+        if let Some(ref stream) = self.pcm_stream {
+            stream.push_samples(samples);
+        }
+    }
+
+    /// Frames to generate this frame to keep the PCM stream's jitter buffer full
+    pub fn pcm_frames_needed(&self) -> usize {
+        self.pcm_stream.as_ref().map_or(0, |stream| stream.frames_needed())
+    }
+
+    pub fn pcm_stream(&self) -> Option<&PcmStream> {
+        self.pcm_stream.as_ref()
+    }
+
+    /// Envelope, filter, unison and effect settings for all voices
+    pub fn set_synth_config(&mut self, config: SynthConfig) {
+        if let Some(ref mut manager) = self.audio_manager {
+            manager.set_config(config);
+        }
+    }
+
+    /// Gives one voice its own envelope (e.g. `AdsrConfig::pass_through()` for a voice whose
+    /// amplitude the shader shapes), `None` returns it to the shared one
+    pub fn set_voice_envelope(&mut self, voice_id: usize, envelope: Option<AdsrConfig>) {
+        if let Some(ref mut manager) = self.audio_manager {
+            manager.set_voice_envelope(voice_id, envelope);
+        }
+    }
+
+    pub fn synth_config(&self) -> SynthConfig {
+        self.audio_manager.as_ref().map(|m| m.config()).unwrap_or_default()
+    }
+
+    /// Current envelope level (0..1) of a voice, for visuals
+    pub fn voice_envelope(&self, voice_id: usize) -> f32 {
+        self.audio_manager
+            .as_ref()
+            .and_then(|m| m.voice_state(voice_id))
+            .map_or(0.0, |state| state.level)
+    }
+
+    /// Fills `uniform` with the current voice and effect state
+    pub fn update_uniform(&self, uniform: &mut SynthesisUniform) {
+        if let Some(ref manager) = self.audio_manager {
+            manager.fill_uniform(uniform);
+        }
+    }
+
+    /// Stops playback without resetting the voices, e.g. while an export renders them
+    /// offline; `start_gpu_synthesis` resumes it
+    pub fn pause(&mut self) -> anyhow::Result<()> {
+        if let Some(ref mut manager) = self.audio_manager {
+            manager.pause()?;
+        }
+        Ok(())
+    }
+
+    /// Offline render of the voices for export, see `AudioSynthManager::render_offline`
+    pub fn render_offline(&mut self, frames: usize, channels: usize) -> Vec<f32> {
+        match self.audio_manager {
+            Some(ref mut manager) => manager.render_offline(frames, channels),
+            None => vec![0.0; frames * channels],
+        }
     }
 
     pub fn update(&mut self) {
@@ -759,8 +799,10 @@ impl SynthesisManager {
         }
     }
 
+    /// (frames queued on the PCM stream, synthesis enabled)
     pub fn get_buffer_info(&self) -> Option<(usize, bool)> {
-        Some((0, self.synthesis_enabled))
+        let queued = self.pcm_stream.as_ref().map_or(0, |stream| stream.queued_frames());
+        Some((queued, self.synthesis_enabled))
     }
 
     pub fn is_gpu_synthesis_enabled(&self) -> bool {
@@ -772,14 +814,35 @@ impl SynthesisManager {
     }
 }
 
+/// Synth state for visuals, filled by `SynthesisManager::update_uniform`. One slot per voice.
+/// ```wgsl
+/// struct Synthesis {
+///     note_frequencies: array<vec4<f32>, 4>, note_amplitudes: array<vec4<f32>, 4>,
+///     note_envelopes: array<vec4<f32>, 4>, note_stages: array<vec4<u32>, 4>,
+///     master_volume: f32, waveform_type: u32, active_note_count: u32, filter_cutoff: f32,
+///     filter_resonance: f32, reverb_mix: f32, delay_mix: f32, _pad: u32,
+/// }
+/// ```
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct SynthesisUniform {
     pub note_frequencies: [[f32; 4]; 4],
+    /// Envelope level times voice gain
     pub note_amplitudes: [[f32; 4]; 4],
+    /// Envelope level, 0..1
+    pub note_envelopes: [[f32; 4]; 4],
+    /// 0 idle, 1 attack, 2 decay, 3 sustain, 4 release
+    pub note_stages: [[u32; 4]; 4],
     pub master_volume: f32,
     pub waveform_type: u32,
+    /// Voices that are sounding, including ones releasing
     pub active_note_count: u32,
+    /// Filter cutoff in Hz, 0 when the filter is off
+    pub filter_cutoff: f32,
+    pub filter_resonance: f32,
+    pub reverb_mix: f32,
+    pub delay_mix: f32,
+    pub _padding: u32,
 }
 
 unsafe impl bytemuck::Pod for SynthesisUniform {}
@@ -796,9 +859,16 @@ impl SynthesisUniform {
         Self {
             note_frequencies: [[0.0; 4]; 4],
             note_amplitudes: [[0.0; 4]; 4],
+            note_envelopes: [[0.0; 4]; 4],
+            note_stages: [[0; 4]; 4],
             master_volume: 0.3,
             waveform_type: 0,
             active_note_count: 0,
+            filter_cutoff: 0.0,
+            filter_resonance: 0.0,
+            reverb_mix: 0.0,
+            delay_mix: 0.0,
+            _padding: 0,
         }
     }
 }
@@ -0,0 +1,288 @@
+use anyhow::{Result, anyhow};
+use gstreamer as gst;
+use log::{debug, info, warn};
+use std::path::Path;
+use gst::prelude::*;
+use super::waveform::WaveformTap;
+use super::analysis::{AnalysisBus, AnalysisSnapshot, LevelData, SpectrumData, install_analysis_handler};
+
+/// Plays an audio file (WAV, FLAC, MP3, OGG, ...) with the same spectrum/BPM analysis
+/// `VideoTextureManager` does for a video's audio track, plus a waveform tap.
+///
+/// Pipeline: `filesrc ! decodebin ! audioconvert ! audioresample ! bpmdetect ! spectrum ! level ! tee`
+/// with one branch going to the speakers and one to an appsink for the raw waveform.
+pub struct AudioFileManager {
+    pipeline: gst::Pipeline,
+    path: String,
+    duration: Option<gst::ClockTime>,
+    position: gst::ClockTime,
+    is_playing: bool,
+    loop_playback: bool,
+    volume: f64,
+    is_muted: bool,
+    spectrum_threshold: i32,
+    analysis: AnalysisBus,
+    waveform: WaveformTap,
+}
+
+fn make(factory: &str, name: &str) -> Result<gst::Element> {
+    gst::ElementFactory::make(factory)
+        .name(name)
+        .build()
+        .map_err(|_| anyhow!("Failed to create {} element", factory))
+}
+
+impl AudioFileManager {
+    pub fn new(audio_path: impl AsRef<Path>) -> Result<Self> {
+        let path_str = audio_path.as_ref()
+            .to_str()
+            .ok_or_else(|| anyhow!("Invalid audio path"))?
+            .to_string();
+        info!("Loading audio file: {}", path_str);
+
+        let spectrum_bands = 128u32;
+        let spectrum_threshold = -60;
+
+        let pipeline = gst::Pipeline::new();
+        let filesrc = gst::ElementFactory::make("filesrc")
+            .name("source")
+            .property("location", &path_str)
+            .build()
+            .map_err(|_| anyhow!("Failed to create filesrc element"))?;
+        let decodebin = make("decodebin", "decoder")?;
+        let audioconvert = make("audioconvert", "audioconvert")?;
+        let audioresample = make("audioresample", "audioresample")?;
+        let bpmdetect = make("bpmdetect", "bpmdetect")?;
+        let spectrum = gst::ElementFactory::make("spectrum")
+            .name("spectrum")
+            .property("bands", spectrum_bands)
+            .property("threshold", spectrum_threshold)
+            .property("post-messages", true)
+            .property("message-magnitude", true)
+            .property("message-phase", true)
+            .property("multi-channel", true)
+            .property("interval", 50_000_000u64)
+            .build()
+            .map_err(|_| anyhow!("Failed to create spectrum element"))?;
+        let level = gst::ElementFactory::make("level")
+            .name("level")
+            .property("post-messages", true)
+            .property("interval", 50_000_000u64)
+            .build()
+            .map_err(|_| anyhow!("Failed to create level element"))?;
+        let tee = make("tee", "tee")?;
+
+        // Playback branch
+        let play_queue = make("queue", "play_queue")?;
+        let volume = gst::ElementFactory::make("volume")
+            .name("volume")
+            .property("volume", 1.0)
+            .build()
+            .map_err(|_| anyhow!("Failed to create volume element"))?;
+        let audio_sink = make("autoaudiosink", "audiosink")?;
+
+        pipeline.add_many(&[
+            &filesrc, &decodebin, &audioconvert, &audioresample, &bpmdetect, &spectrum, &level, &tee,
+            &play_queue, &volume, &audio_sink,
+        ]).map_err(|_| anyhow!("Failed to add audio elements to pipeline"))?;
+        gst::Element::link_many(&[&filesrc, &decodebin])
+            .map_err(|_| anyhow!("Failed to link filesrc to decodebin"))?;
+        gst::Element::link_many(&[&audioconvert, &audioresample, &bpmdetect, &spectrum, &level, &tee])
+            .map_err(|_| anyhow!("Failed to link audio analysis elements"))?;
+        gst::Element::link_many(&[&tee, &play_queue, &volume, &audio_sink])
+            .map_err(|_| anyhow!("Failed to link audio playback branch"))?;
+        // Waveform branch: stereo f32 into an appsink that follows playback time
+        let waveform = WaveformTap::default();
+        let wave_queue = waveform.build_branch(&pipeline, "wave")?;
+        tee.link(&wave_queue)
+            .map_err(|_| anyhow!("Failed to link waveform branch"))?;
+
+        let audioconvert_weak = audioconvert.downgrade();
+        decodebin.connect_pad_added(move |_, pad| {
+            let is_audio = pad.current_caps()
+                .and_then(|caps| caps.structure(0).map(|s| s.name().starts_with("audio/")))
+                .unwrap_or(false);
+            if !is_audio {
+                return;
+            }
+            if let Some(audioconvert) = audioconvert_weak.upgrade() {
+                if let Some(sink_pad) = audioconvert.static_pad("sink") {
+                    if !sink_pad.is_linked() {
+                        match pad.link(&sink_pad) {
+                            Ok(_) => info!("Linked decoder to audioconvert"),
+                            Err(e) => warn!("Failed to link audio pad: {:?}", e),
+                        }
+                    }
+                }
+            }
+        });
+
+        let bus = pipeline.bus().ok_or_else(|| anyhow!("Audio pipeline has no bus"))?;
+        let analysis = install_analysis_handler(&bus);
+
+        pipeline.set_state(gst::State::Paused)
+            .map_err(|_| anyhow!("Failed to set audio pipeline to PAUSED state"))?;
+        let _ = pipeline.state(gst::ClockTime::from_seconds(1));
+        let duration = pipeline.query_duration::<gst::ClockTime>();
+        if let Some(duration) = duration {
+            info!("Audio duration: {:.2} seconds", duration.mseconds() as f64 / 1000.0);
+        }
+
+        Ok(Self {
+            pipeline,
+            path: path_str,
+            duration,
+            position: gst::ClockTime::ZERO,
+            is_playing: false,
+            loop_playback: true,
+            volume: 1.0,
+            is_muted: false,
+            spectrum_threshold,
+            analysis,
+            waveform,
+        })
+    }
+
+    /// Handles end of file and refreshes the position. Call once per frame; analysis
+    /// messages never reach here, the bus handler publishes them as they arrive.
+    pub fn update(&mut self) {
+        if let Some(bus) = self.pipeline.bus() {
+            while let Some(message) = bus.pop() {
+                if let gst::MessageView::Eos(_) = message.view() {
+                    if self.loop_playback {
+                        debug!("Looping audio");
+                        let _ = self.seek(gst::ClockTime::ZERO);
+                    } else {
+                        let _ = self.pause();
+                    }
+                }
+            }
+        }
+
+        if let Some(position) = self.pipeline.query_position::<gst::ClockTime>() {
+            self.position = position;
+        }
+        if self.duration.is_none() {
+            self.duration = self.pipeline.query_duration::<gst::ClockTime>();
+        }
+    }
+
+    pub fn play(&mut self) -> Result<()> {
+        self.pipeline.set_state(gst::State::Playing)
+            .map_err(|e| anyhow!("Failed to start audio playback: {:?}", e))?;
+        self.is_playing = true;
+        Ok(())
+    }
+
+    pub fn pause(&mut self) -> Result<()> {
+        self.pipeline.set_state(gst::State::Paused)
+            .map_err(|e| anyhow!("Failed to pause audio playback: {:?}", e))?;
+        self.is_playing = false;
+        Ok(())
+    }
+
+    pub fn seek(&mut self, position: gst::ClockTime) -> Result<()> {
+        self.pipeline
+            .seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, position)
+            .map_err(|_| anyhow!("Failed to seek audio to {:?}", position))?;
+        self.position = position;
+        self.waveform.clear();
+        Ok(())
+    }
+
+    pub fn set_loop(&mut self, should_loop: bool) {
+        self.loop_playback = should_loop;
+    }
+
+    pub fn set_volume(&mut self, volume: f64) -> Result<()> {
+        self.volume = volume.clamp(0.0, 1.0);
+        if let Some(volume_elem) = self.pipeline.by_name("volume") {
+            volume_elem.set_property("volume", self.volume);
+        }
+        Ok(())
+    }
+
+    pub fn set_mute(&mut self, muted: bool) -> Result<()> {
+        self.is_muted = muted;
+        if let Some(volume_elem) = self.pipeline.by_name("volume") {
+            volume_elem.set_property("mute", muted);
+        }
+        Ok(())
+    }
+
+    pub fn toggle_mute(&mut self) -> Result<()> {
+        self.set_mute(!self.is_muted)
+    }
+
+    pub fn spectrum_data(&self) -> SpectrumData {
+        (*self.analysis.snapshot.spectrum()).clone()
+    }
+
+    pub fn level_data(&self) -> LevelData {
+        (*self.analysis.snapshot.level()).clone()
+    }
+
+    /// Lock-free handle to the analysis results, readable from any thread
+    pub fn analysis_snapshot(&self) -> AnalysisSnapshot {
+        self.analysis.snapshot.clone()
+    }
+
+    /// dB floor the spectrum element was configured with
+    pub fn spectrum_threshold(&self) -> i32 {
+        self.spectrum_threshold
+    }
+
+    pub fn get_bpm(&self) -> f32 {
+        self.analysis.snapshot.bpm()
+    }
+
+    /// The last `WAVEFORM_SAMPLES` mono samples in -1..1 (fewer right after loading or seeking)
+    pub fn waveform(&self) -> Vec<f32> {
+        self.waveform.mono()
+    }
+
+    /// Left and right channels of the last `WAVEFORM_SAMPLES` samples
+    pub fn waveform_channels(&self) -> (Vec<f32>, Vec<f32>) {
+        self.waveform.channels()
+    }
+
+    /// Sample rate of the decoded audio, once playback has started
+    pub fn audio_sample_rate(&self) -> Option<u32> {
+        self.waveform.sample_rate()
+    }
+
+    pub fn position(&self) -> gst::ClockTime {
+        self.position
+    }
+
+    pub fn duration(&self) -> Option<gst::ClockTime> {
+        self.duration
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.is_playing
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.loop_playback
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Drop for AudioFileManager {
+    fn drop(&mut self) {
+        info!("Shutting down audio file pipeline");
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::{Result, anyhow};
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use log::info;
+use crate::{Core, ShaderCache};
+use crate::texture::TextureManager;
+use crate::yuv::{YuvColorMatrix, YuvConverter, YuvFormat, YuvPlanes};
+
+/// Caps for frame appsinks: NV12 and I420 pass straight through from most decoders and are
+/// converted on the GPU, anything else is converted to RGBA by `videoconvert`
+pub(crate) fn frame_caps() -> gst::Caps {
+    gst_video::VideoCapsBuilder::new()
+        .format_list([gst_video::VideoFormat::Nv12, gst_video::VideoFormat::I420, gst_video::VideoFormat::Rgba])
+        .build()
+}
+
+/// Uploads decoded samples into a `TextureManager`, reusing its textures between frames.
+/// Planes are read straight from the decoder's buffer, without an intermediate image.
+pub(crate) struct FrameUploader {
+    texture_manager: TextureManager,
+    yuv: Option<YuvConverter>,
+    /// The core's cache, for the converter's shader when the first YUV frame arrives
+    shader_cache: ShaderCache,
+    /// Size of the RGBA texture, when the last frame was RGBA
+    rgba_size: Option<(u32, u32)>,
+    dimensions: (u32, u32),
+}
+
+impl FrameUploader {
+    pub fn new(core: &Core, bind_group_layout: &wgpu::BindGroupLayout) -> Self {
+        // Create a default 1x1 texture initially, replaced by the first frame
+        let default_image = image::RgbaImage::new(1, 1);
+        Self {
+            texture_manager: TextureManager::new(&core.device, &core.queue, &default_image, bind_group_layout),
+            yuv: None,
+            shader_cache: core.shader_cache.clone(),
+            rgba_size: None,
+            dimensions: (1, 1),
+        }
+    }
+
+    pub fn texture_manager(&self) -> &TextureManager {
+        &self.texture_manager
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    /// Uploads `sample`. Returns true if the texture was recreated (size or format changed),
+    /// meaning bind groups built from `texture_manager` are stale.
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        sample: &gst::Sample,
+    ) -> Result<bool> {
+        let buffer = sample.buffer().ok_or_else(|| anyhow!("Sample has no buffer"))?;
+        let caps = sample.caps().ok_or_else(|| anyhow!("Sample has no caps"))?;
+        let info = gst_video::VideoInfo::from_caps(caps).map_err(|_| anyhow!("Invalid video caps"))?;
+        // Honours per-buffer plane offsets and strides from the decoder's video meta
+        let frame = gst_video::VideoFrameRef::from_buffer_ref_readable(buffer, &info)
+            .map_err(|_| anyhow!("Failed to map video frame"))?;
+        let (width, height) = (info.width(), info.height());
+        let stride = |plane: usize| frame.plane_stride()[plane].max(0) as u32;
+        let plane = |plane: u32| frame.plane_data(plane).unwrap_or(&[]);
+
+        let format = match info.format() {
+            gst_video::VideoFormat::Nv12 => Some(YuvFormat::Nv12),
+            gst_video::VideoFormat::I420 => Some(YuvFormat::I420),
+            gst_video::VideoFormat::Rgba => None,
+            other => return Err(anyhow!("Unsupported frame format {:?}", other)),
+        };
+
+        let recreated = match format {
+            Some(format) => {
+                let colorimetry = info.colorimetry();
+                let matrix = match colorimetry.matrix() {
+                    gst_video::VideoColorMatrix::Bt601 | gst_video::VideoColorMatrix::Fcc => YuvColorMatrix::Bt601,
+                    gst_video::VideoColorMatrix::Bt709 | gst_video::VideoColorMatrix::Smpte240m => YuvColorMatrix::Bt709,
+                    gst_video::VideoColorMatrix::Bt2020 => YuvColorMatrix::Bt2020,
+                    _ => YuvColorMatrix::guess(height),
+                };
+                let planes = YuvPlanes {
+                    format,
+                    width,
+                    height,
+                    matrix,
+                    full_range: colorimetry.range() == gst_video::VideoColorRange::Range0_255,
+                    planes: [plane(0), plane(1), if format == YuvFormat::I420 { plane(2) } else { &[] }],
+                    strides: [stride(0), stride(1), if format == YuvFormat::I420 { stride(2) } else { 0 }],
+                };
+                let converter = self.yuv.get_or_insert_with(|| YuvConverter::new(device, &self.shader_cache));
+                let recreated = converter.upload(device, queue, bind_group_layout, &planes);
+                if recreated {
+                    if let Some(output) = converter.texture_manager() {
+                        self.texture_manager = output.clone();
+                    }
+                    self.rgba_size = None;
+                }
+                recreated
+            }
+            None => {
+                let recreated = self.rgba_size != Some((width, height));
+                if recreated {
+                    info!("Creating new RGBA frame texture: {}x{}", width, height);
+                    let blank = image::RgbaImage::new(width, height);
+                    self.texture_manager = TextureManager::new(device, queue, &blank, bind_group_layout);
+                    self.rgba_size = Some((width, height));
+                    // Switching back to YUV must rebuild its output too
+                    self.yuv = None;
+                }
+                let data = plane(0);
+                if data.len() < (stride(0) * (height - 1) + width * 4) as usize {
+                    return Err(anyhow!("RGBA frame is too short ({} bytes)", data.len()));
+                }
+                self.texture_manager.update_bytes(queue, data, width, height, stride(0));
+                recreated
+            }
+        };
+        self.dimensions = (width, height);
+        Ok(recreated)
+    }
+}
@@ -4,6 +4,20 @@ pub mod video;
 pub mod webcam;
 #[cfg(feature = "media")]
 pub mod audio;
+#[cfg(feature = "media")]
+pub mod audiofile;
+#[cfg(feature = "media")]
+pub mod analysis;
+#[cfg(feature = "media")]
+pub mod waveform;
+#[cfg(feature = "media")]
+pub mod pcm;
+#[cfg(feature = "media")]
+pub mod offline;
+#[cfg(feature = "media")]
+pub mod synth_engine;
+#[cfg(feature = "media")]
+mod frame;
 use log::info;
 
 #[cfg(feature = "media")]
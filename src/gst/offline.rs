@@ -0,0 +1,127 @@
+use anyhow::{Result, anyhow};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use log::{info, warn};
+use std::path::Path;
+use gst::prelude::*;
+
+/// Decodes `duration` seconds of a file's audio track from `start` as interleaved F32,
+/// resampled to `sample_rate` with `channels` channels, as fast as the decoder runs.
+/// Always returns exactly `duration * sample_rate` frames (silence past the end of the
+/// track), so it lines up with export frames no matter how the file is muxed.
+pub fn decode_audio(
+    path: impl AsRef<Path>,
+    start: f64,
+    duration: f64,
+    sample_rate: u32,
+    channels: u32,
+) -> Result<Vec<f32>> {
+    let path_str = path.as_ref()
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid media path"))?
+        .to_string();
+    let frames = (duration.max(0.0) * sample_rate as f64).round() as usize;
+    let wanted = frames * channels as usize;
+    info!("Decoding {:.2}s of audio from {} for export", duration, path_str);
+
+    let pipeline = gst::Pipeline::new();
+    let make = |factory: &str, name: &str| {
+        gst::ElementFactory::make(factory)
+            .name(name)
+            .build()
+            .map_err(|_| anyhow!("Failed to create {} element", factory))
+    };
+    let source = make("filesrc", "offline_source")?;
+    source.set_property("location", &path_str);
+    let decodebin = make("decodebin", "offline_decode")?;
+    let convert = make("audioconvert", "offline_convert")?;
+    let resample = make("audioresample", "offline_resample")?;
+    let sink = make("appsink", "offline_sink")?
+        .dynamic_cast::<gst_app::AppSink>()
+        .map_err(|_| anyhow!("Failed to cast to AppSink"))?;
+    sink.set_caps(Some(&gst::Caps::builder("audio/x-raw")
+        .field("format", "F32LE")
+        .field("layout", "interleaved")
+        .field("rate", sample_rate as i32)
+        .field("channels", channels as i32)
+        .build()));
+    // Pull as fast as decoding allows, nothing is dropped
+    sink.set_sync(false);
+
+    pipeline.add_many(&[&source, &decodebin, &convert, &resample, sink.upcast_ref()])
+        .map_err(|_| anyhow!("Failed to add decode elements to pipeline"))?;
+    source.link(&decodebin)
+        .map_err(|_| anyhow!("Failed to link source to decodebin"))?;
+    gst::Element::link_many(&[&convert, &resample, sink.upcast_ref()])
+        .map_err(|_| anyhow!("Failed to link audio decode chain"))?;
+
+    let convert_weak = convert.downgrade();
+    let pipeline_weak = pipeline.downgrade();
+    decodebin.connect_pad_added(move |_, pad| {
+        let (Some(convert), Some(pipeline)) = (convert_weak.upgrade(), pipeline_weak.upgrade()) else {
+            return;
+        };
+        let is_audio = pad.current_caps()
+            .and_then(|caps| caps.structure(0).map(|s| s.name().starts_with("audio/")))
+            .unwrap_or(false);
+        let Some(sink_pad) = convert.static_pad("sink") else {
+            return;
+        };
+        let link = if is_audio && !sink_pad.is_linked() {
+            pad.link(&sink_pad).map(|_| ())
+        } else {
+            // Video and extra audio tracks are discarded, but must be linked to keep data flowing
+            let Ok(fakesink) = gst::ElementFactory::make("fakesink").property("sync", false).build() else {
+                return;
+            };
+            let _ = pipeline.add(&fakesink);
+            let _ = fakesink.sync_state_with_parent();
+            match fakesink.static_pad("sink") {
+                Some(fake_pad) => pad.link(&fake_pad).map(|_| ()),
+                None => Ok(()),
+            }
+        };
+        if let Err(e) = link {
+            warn!("Failed to link decoded stream: {:?}", e);
+        }
+    });
+
+    let result = (|| -> Result<Vec<f32>> {
+        pipeline.set_state(gst::State::Paused)
+            .map_err(|e| anyhow!("Failed to preroll {}: {:?}", path_str, e))?;
+        let (state_result, _, _) = pipeline.state(gst::ClockTime::from_seconds(10));
+        state_result.map_err(|_| anyhow!("{} has no playable audio track", path_str))?;
+
+        let start_time = gst::ClockTime::from_nseconds((start.max(0.0) * 1e9) as u64);
+        pipeline.seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE, start_time)
+            .map_err(|_| anyhow!("Failed to seek {} for audio export", path_str))?;
+        pipeline.set_state(gst::State::Playing)
+            .map_err(|e| anyhow!("Failed to decode {}: {:?}", path_str, e))?;
+
+        let mut samples = Vec::with_capacity(wanted);
+        while samples.len() < wanted {
+            let Some(sample) = sink.try_pull_sample(gst::ClockTime::from_seconds(5)) else {
+                // EOS or a stalled decoder, pad the rest with silence
+                break;
+            };
+            let Some(buffer) = sample.buffer() else {
+                continue;
+            };
+            let map = buffer.map_readable().map_err(|_| anyhow!("Failed to map audio buffer"))?;
+            let mut data: &[u8] = map.as_slice();
+            // Accurate seeks can still deliver a little audio before `start`
+            if samples.is_empty() {
+                if let Some(pts) = buffer.pts().filter(|pts| *pts < start_time) {
+                    let early = ((start_time - pts).nseconds() as u128 * sample_rate as u128 / 1_000_000_000) as usize;
+                    data = &data[(early * channels as usize * 4).min(data.len())..];
+                }
+            }
+            samples.extend(data.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])));
+        }
+        samples.resize(wanted, 0.0);
+        Ok(samples)
+    })();
+
+    let _ = pipeline.set_state(gst::State::Null);
+    result
+}
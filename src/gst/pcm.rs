@@ -0,0 +1,254 @@
+use anyhow::{Result, anyhow};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use log::{debug, info, warn};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use gst::prelude::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PcmStreamConfig {
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// Frames handed to `appsrc` per buffer
+    pub block_frames: u32,
+    /// Frames kept queued ahead of playback. Must cover a few render frames plus GPU readback
+    /// latency; playback starts (and restarts after an underrun) once this much is queued.
+    pub jitter_frames: u32,
+    pub volume: f64,
+}
+
+impl Default for PcmStreamConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            channels: 2,
+            block_frames: 512,
+            // ~93 ms at 44.1 kHz
+            jitter_frames: 4096,
+            volume: 0.5,
+        }
+    }
+}
+
+/// Interleaved samples waiting for `appsrc`, shared with its `need-data` callback
+struct JitterBuffer {
+    samples: VecDeque<f32>,
+    /// False until `jitter_frames` are queued; silence is played meanwhile
+    primed: bool,
+    frames_sent: u64,
+    underruns: u64,
+}
+
+/// Renders one block of interleaved samples on the streaming thread
+type Generator = Box<dyn FnMut(&mut [f32]) + Send>;
+
+/// Plays interleaved F32 PCM pushed from the CPU, e.g. blocks read back from a `SoundShader`,
+/// through `appsrc ! audioconvert ! audioresample ! volume ! autoaudiosink`. With
+/// `with_generator` samples are instead rendered on demand, block by block.
+pub struct PcmStream {
+    pipeline: gst::Pipeline,
+    config: PcmStreamConfig,
+    buffer: Arc<Mutex<JitterBuffer>>,
+}
+
+impl PcmStream {
+    pub fn new(config: PcmStreamConfig) -> Result<Self> {
+        Self::build(config, None)
+    }
+
+    /// Calls `generator` for every block `appsrc` asks for instead of reading the jitter
+    /// buffer, so latency is just `block_frames` plus the sink's buffering
+    pub fn with_generator(config: PcmStreamConfig, generator: impl FnMut(&mut [f32]) + Send + 'static) -> Result<Self> {
+        Self::build(config, Some(Box::new(generator)))
+    }
+
+    fn build(config: PcmStreamConfig, generator: Option<Generator>) -> Result<Self> {
+        info!("Creating PCM stream: {} Hz, {} channels, {} frame jitter buffer",
+            config.sample_rate, config.channels, config.jitter_frames);
+
+        let pipeline = gst::Pipeline::new();
+        let make = |factory: &str, name: &str| {
+            gst::ElementFactory::make(factory)
+                .name(name)
+                .build()
+                .map_err(|_| anyhow!("Failed to create {} element", factory))
+        };
+        let appsrc = make("appsrc", "pcm_source")?
+            .dynamic_cast::<gst_app::AppSrc>()
+            .map_err(|_| anyhow!("Failed to cast to AppSrc"))?;
+        let convert = make("audioconvert", "pcm_convert")?;
+        let resample = make("audioresample", "pcm_resample")?;
+        let volume = make("volume", "pcm_volume")?;
+        volume.set_property("volume", config.volume.clamp(0.0, 1.0));
+        let sink = make("autoaudiosink", "pcm_sink")?;
+
+        appsrc.set_caps(Some(&gst::Caps::builder("audio/x-raw")
+            .field("format", "F32LE")
+            .field("layout", "interleaved")
+            .field("rate", config.sample_rate as i32)
+            .field("channels", config.channels as i32)
+            .build()));
+        appsrc.set_format(gst::Format::Time);
+        appsrc.set_is_live(true);
+        // Keep appsrc's own queue to about one block so latency lives in the jitter buffer
+        let block_bytes = (config.block_frames * config.channels) as u64 * 4;
+        appsrc.set_max_bytes(block_bytes * 2);
+
+        pipeline.add_many(&[appsrc.upcast_ref(), &convert, &resample, &volume, &sink])
+            .map_err(|_| anyhow!("Failed to add PCM elements to pipeline"))?;
+        gst::Element::link_many(&[appsrc.upcast_ref(), &convert, &resample, &volume, &sink])
+            .map_err(|_| anyhow!("Failed to link PCM elements"))?;
+
+        let buffer = Arc::new(Mutex::new(JitterBuffer {
+            samples: VecDeque::with_capacity(config.jitter_frames as usize * config.channels as usize * 2),
+            primed: false,
+            frames_sent: 0,
+            underruns: 0,
+        }));
+        let shared = buffer.clone();
+        let generator = Mutex::new(generator);
+        appsrc.set_callbacks(
+            gst_app::AppSrcCallbacks::builder()
+                .need_data(move |appsrc, _| {
+                    let Ok(mut jitter) = shared.lock() else {
+                        return;
+                    };
+                    let block = match generator.lock().ok().as_deref_mut().and_then(Option::as_mut) {
+                        Some(generator) => {
+                            let mut block = vec![0.0; (config.block_frames * config.channels) as usize];
+                            generator(&mut block);
+                            block
+                        }
+                        None => Self::next_block(&mut jitter, &config),
+                    };
+                    let pts = gst::ClockTime::from_nseconds(
+                        jitter.frames_sent * 1_000_000_000 / config.sample_rate as u64);
+                    jitter.frames_sent += config.block_frames as u64;
+                    drop(jitter);
+
+                    let mut buffer = gst::Buffer::from_mut_slice(
+                        block.iter().flat_map(|s| s.to_le_bytes()).collect::<Vec<u8>>());
+                    if let Some(buffer) = buffer.get_mut() {
+                        buffer.set_pts(pts);
+                        buffer.set_duration(gst::ClockTime::from_nseconds(
+                            config.block_frames as u64 * 1_000_000_000 / config.sample_rate as u64));
+                    }
+                    if appsrc.push_buffer(buffer).is_err() {
+                        debug!("PCM stream is not accepting buffers");
+                    }
+                })
+                .build(),
+        );
+
+        let bus = pipeline.bus().ok_or_else(|| anyhow!("PCM stream pipeline has no bus"))?;
+        // Nothing pops this bus, so messages are dropped here instead of piling up
+        bus.set_sync_handler(|_, message| {
+            if let gst::MessageView::Error(err) = message.view() {
+                warn!("PCM stream error: {} ({})", err.error(), err.debug().unwrap_or_default());
+            }
+            gst::BusSyncReply::Drop
+        });
+
+        Ok(Self { pipeline, config, buffer })
+    }
+
+    /// Takes one block from the jitter buffer, padding with silence while it refills
+    fn next_block(jitter: &mut JitterBuffer, config: &PcmStreamConfig) -> Vec<f32> {
+        let channels = config.channels as usize;
+        let block_len = config.block_frames as usize * channels;
+        let queued_frames = jitter.samples.len() / channels;
+        if !jitter.primed {
+            if queued_frames < config.jitter_frames as usize {
+                return vec![0.0; block_len];
+            }
+            jitter.primed = true;
+        }
+
+        let available = jitter.samples.len().min(block_len);
+        let mut block: Vec<f32> = jitter.samples.drain(..available).collect();
+        if block.len() < block_len {
+            jitter.underruns += 1;
+            jitter.primed = false;
+            debug!("PCM stream underrun ({} total), refilling", jitter.underruns);
+            block.resize(block_len, 0.0);
+        }
+        block
+    }
+
+    pub fn start(&self) -> Result<()> {
+        self.pipeline.set_state(gst::State::Playing)
+            .map_err(|e| anyhow!("Failed to start PCM stream: {:?}", e))?;
+        Ok(())
+    }
+
+    /// Holds playback without dropping anything queued
+    pub fn pause(&self) -> Result<()> {
+        self.pipeline.set_state(gst::State::Paused)
+            .map_err(|e| anyhow!("Failed to pause PCM stream: {:?}", e))?;
+        Ok(())
+    }
+
+    /// Stops playback and drops everything queued
+    pub fn stop(&self) -> Result<()> {
+        self.pipeline.set_state(gst::State::Null)
+            .map_err(|e| anyhow!("Failed to stop PCM stream: {:?}", e))?;
+        if let Ok(mut jitter) = self.buffer.lock() {
+            jitter.samples.clear();
+            jitter.primed = false;
+            jitter.frames_sent = 0;
+        }
+        Ok(())
+    }
+
+    /// Queues interleaved samples. Anything beyond twice the jitter depth is dropped from
+    /// the front so a stalled consumer doesn't build up latency.
+    pub fn push_samples(&self, samples: &[f32]) {
+        let Ok(mut jitter) = self.buffer.lock() else {
+            return;
+        };
+        jitter.samples.extend(samples.iter().copied());
+        let limit = self.config.jitter_frames as usize * self.config.channels as usize * 2;
+        if jitter.samples.len() > limit {
+            let excess = jitter.samples.len() - limit;
+            // Keep whole frames so channels stay aligned
+            let excess = excess.div_ceil(self.config.channels as usize) * self.config.channels as usize;
+            jitter.samples.drain(..excess);
+        }
+    }
+
+    /// Frames currently queued ahead of playback
+    pub fn queued_frames(&self) -> usize {
+        match self.buffer.lock() {
+            Ok(jitter) => jitter.samples.len() / self.config.channels as usize,
+            Err(_) => 0,
+        }
+    }
+
+    /// Frames the producer should supply to get back to the jitter depth, plus one block of headroom
+    pub fn frames_needed(&self) -> usize {
+        let target = (self.config.jitter_frames + self.config.block_frames) as usize;
+        target.saturating_sub(self.queued_frames())
+    }
+
+    /// Times playback ran dry and had to refill
+    pub fn underruns(&self) -> u64 {
+        self.buffer.lock().map(|jitter| jitter.underruns).unwrap_or(0)
+    }
+
+    pub fn set_volume(&self, volume: f64) {
+        if let Some(element) = self.pipeline.by_name("pcm_volume") {
+            element.set_property("volume", volume.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn config(&self) -> &PcmStreamConfig {
+        &self.config
+    }
+}
+
+impl Drop for PcmStream {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
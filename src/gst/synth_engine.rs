@@ -0,0 +1,501 @@
+// Sample-rate voice engine behind `AudioSynthManager`: oscillators with unison, ADSR
+// envelopes and a resonant low-pass per voice, then master delay and reverb.
+use super::audio::AudioWaveform;
+
+/// Most voices the engine plays at once (matches `SynthesisUniform`)
+pub const MAX_SYNTH_VOICES: usize = 16;
+/// Most oscillators stacked per voice by `UnisonConfig`
+pub const MAX_UNISON: usize = 8;
+
+/// Envelope times in seconds, sustain as a level 0..1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdsrConfig {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Default for AdsrConfig {
+    fn default() -> Self {
+        Self {
+            attack: 0.01,
+            decay: 0.3,
+            sustain: 0.7,
+            release: 0.5,
+        }
+    }
+}
+
+impl AdsrConfig {
+    /// Follows the requested amplitude directly (only a ~0.5 ms declick), for callers that
+    /// shape their notes themselves, e.g. with per-frame amplitudes from a shader
+    pub fn pass_through() -> Self {
+        Self {
+            attack: 0.0,
+            decay: 0.0,
+            sustain: 1.0,
+            release: 0.0,
+        }
+    }
+}
+
+/// Resonant low-pass (state variable filter) applied to each voice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterConfig {
+    pub enabled: bool,
+    pub cutoff_hz: f32,
+    /// 0 = no resonance, 1 = self-oscillation
+    pub resonance: f32,
+    /// How far the envelope opens the cutoff, in octaves at full level
+    pub envelope_octaves: f32,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cutoff_hz: 8000.0,
+            resonance: 0.2,
+            envelope_octaves: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnisonConfig {
+    /// Oscillators per voice, 1..=`MAX_UNISON`
+    pub voices: u32,
+    /// Spread between the lowest and highest oscillator, in cents
+    pub detune_cents: f32,
+    /// Stereo width of the spread oscillators, 0 = mono, 1 = hard left/right
+    pub stereo_spread: f32,
+}
+
+impl Default for UnisonConfig {
+    fn default() -> Self {
+        Self {
+            voices: 1,
+            detune_cents: 12.0,
+            stereo_spread: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DelayConfig {
+    /// Seconds, up to 2
+    pub time: f32,
+    pub feedback: f32,
+    /// Wet level, 0 = off
+    pub mix: f32,
+}
+
+impl Default for DelayConfig {
+    fn default() -> Self {
+        Self {
+            time: 0.3,
+            feedback: 0.35,
+            mix: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReverbConfig {
+    /// 0..1, longer tail as it grows
+    pub room_size: f32,
+    /// High-frequency absorption, 0..1
+    pub damping: f32,
+    /// Wet level, 0 = off
+    pub mix: f32,
+}
+
+impl Default for ReverbConfig {
+    fn default() -> Self {
+        Self {
+            room_size: 0.7,
+            damping: 0.5,
+            mix: 0.0,
+        }
+    }
+}
+
+/// Everything that shapes the synth's sound besides the notes themselves
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SynthConfig {
+    pub envelope: AdsrConfig,
+    pub filter: FilterConfig,
+    pub unison: UnisonConfig,
+    pub delay: DelayConfig,
+    pub reverb: ReverbConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnvelopeStage {
+    #[default]
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+impl EnvelopeStage {
+    /// 0 idle, 1 attack, 2 decay, 3 sustain, 4 release, as stored in `SynthesisUniform`
+    pub fn to_u32(self) -> u32 {
+        match self {
+            EnvelopeStage::Idle => 0,
+            EnvelopeStage::Attack => 1,
+            EnvelopeStage::Decay => 2,
+            EnvelopeStage::Sustain => 3,
+            EnvelopeStage::Release => 4,
+        }
+    }
+}
+
+/// Snapshot of a voice for visuals
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VoiceState {
+    pub frequency: f32,
+    pub gain: f32,
+    /// Current envelope level, 0..1
+    pub level: f32,
+    pub stage: EnvelopeStage,
+    /// Key is held (as opposed to releasing or idle)
+    pub gate: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Envelope {
+    stage: EnvelopeStage,
+    level: f32,
+    release_step: f32,
+}
+
+impl Envelope {
+    fn next(&mut self, config: &AdsrConfig, sample_rate: f32) -> f32 {
+        let per_second = |seconds: f32| 1.0 / (seconds.max(0.0005) * sample_rate);
+        match self.stage {
+            EnvelopeStage::Idle => self.level = 0.0,
+            EnvelopeStage::Attack => {
+                self.level += per_second(config.attack);
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = EnvelopeStage::Decay;
+                }
+            }
+            EnvelopeStage::Decay => {
+                let sustain = config.sustain.clamp(0.0, 1.0);
+                self.level -= (1.0 - sustain) * per_second(config.decay);
+                if self.level <= sustain {
+                    self.level = sustain;
+                    self.stage = EnvelopeStage::Sustain;
+                }
+            }
+            EnvelopeStage::Sustain => self.level = config.sustain.clamp(0.0, 1.0),
+            EnvelopeStage::Release => {
+                self.level -= self.release_step;
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = EnvelopeStage::Idle;
+                }
+            }
+        }
+        self.level
+    }
+
+    fn release(&mut self, config: &AdsrConfig, sample_rate: f32) {
+        if self.stage != EnvelopeStage::Idle {
+            // Linear from wherever the envelope is, so release time doesn't depend on the level
+            self.release_step = self.level / (config.release.max(0.0005) * sample_rate);
+            self.stage = EnvelopeStage::Release;
+        }
+    }
+}
+
+/// Zavalishin's TPT state variable filter, low-pass output, stereo
+#[derive(Clone, Copy, Default)]
+struct SvfLowPass {
+    ic1: [f32; 2],
+    ic2: [f32; 2],
+}
+
+impl SvfLowPass {
+    fn process(&mut self, input: [f32; 2], g: f32, k: f32) -> [f32; 2] {
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let mut output = [0.0; 2];
+        for c in 0..2 {
+            let v3 = input[c] - self.ic2[c];
+            let v1 = a1 * self.ic1[c] + g * a1 * v3;
+            let v2 = self.ic2[c] + g * v1;
+            self.ic1[c] = 2.0 * v1 - self.ic1[c];
+            self.ic2[c] = 2.0 * v2 - self.ic2[c];
+            output[c] = v2;
+        }
+        output
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Voice {
+    frequency: f32,
+    gain: f32,
+    gate: bool,
+    envelope: Envelope,
+    filter: SvfLowPass,
+    phases: [f64; MAX_UNISON],
+}
+
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    store: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len], index: 0, store: 0.0 }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.index];
+        self.store = output * (1.0 - damping) + self.store * damping;
+        self.buffer[self.index] = input + self.store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+struct AllPass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl AllPass {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len], index: 0 }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// Freeverb-style reverb: parallel damped combs into series all-passes, per channel
+struct Reverb {
+    combs: [Vec<Comb>; 2],
+    allpasses: [Vec<AllPass>; 2],
+}
+
+impl Reverb {
+    fn new(sample_rate: f32) -> Self {
+        // Freeverb's tunings at 44.1 kHz, the right channel offset for width
+        const COMBS: [usize; 4] = [1116, 1277, 1422, 1617];
+        const ALLPASSES: [usize; 2] = [556, 441];
+        const SPREAD: usize = 23;
+        let scale = |len: usize| ((len as f32 * sample_rate / 44100.0) as usize).max(1);
+        let channel = |offset: usize| {
+            (
+                COMBS.iter().map(|&len| Comb::new(scale(len + offset))).collect(),
+                ALLPASSES.iter().map(|&len| AllPass::new(scale(len + offset))).collect(),
+            )
+        };
+        let (left_combs, left_allpasses) = channel(0);
+        let (right_combs, right_allpasses) = channel(SPREAD);
+        Self {
+            combs: [left_combs, right_combs],
+            allpasses: [left_allpasses, right_allpasses],
+        }
+    }
+
+    fn process(&mut self, input: [f32; 2], config: &ReverbConfig) -> [f32; 2] {
+        let feedback = 0.7 + 0.28 * config.room_size.clamp(0.0, 1.0);
+        let damping = config.damping.clamp(0.0, 1.0) * 0.4;
+        let mono = (input[0] + input[1]) * 0.015;
+        let mut output = [0.0; 2];
+        for ((combs, allpasses), out) in self.combs.iter_mut().zip(&mut self.allpasses).zip(&mut output) {
+            let mut sum: f32 = combs.iter_mut().map(|comb| comb.process(mono, feedback, damping)).sum();
+            for allpass in allpasses {
+                sum = allpass.process(sum);
+            }
+            *out = sum;
+        }
+        output
+    }
+}
+
+struct StereoDelay {
+    buffer: Vec<[f32; 2]>,
+    index: usize,
+}
+
+impl StereoDelay {
+    fn new(sample_rate: f32) -> Self {
+        Self { buffer: vec![[0.0; 2]; (sample_rate * 2.0) as usize + 1], index: 0 }
+    }
+
+    fn process(&mut self, input: [f32; 2], config: &DelayConfig, sample_rate: f32) -> [f32; 2] {
+        let len = self.buffer.len();
+        let delay = ((config.time.clamp(0.001, 2.0) * sample_rate) as usize).clamp(1, len - 1);
+        let delayed = self.buffer[(self.index + len - delay) % len];
+        let feedback = config.feedback.clamp(0.0, 0.95);
+        self.buffer[self.index] = [input[0] + delayed[0] * feedback, input[1] + delayed[1] * feedback];
+        self.index = (self.index + 1) % len;
+        delayed
+    }
+}
+
+pub(crate) struct SynthEngine {
+    pub config: SynthConfig,
+    pub waveform: AudioWaveform,
+    pub master_volume: f32,
+    sample_rate: f32,
+    voices: [Voice; MAX_SYNTH_VOICES],
+    /// Per-voice envelopes used instead of `config.envelope`, kept across `reset`
+    envelopes: [Option<AdsrConfig>; MAX_SYNTH_VOICES],
+    delay: StereoDelay,
+    reverb: Reverb,
+}
+
+impl SynthEngine {
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate as f32;
+        Self {
+            config: SynthConfig::default(),
+            waveform: AudioWaveform::Sine,
+            master_volume: 0.3,
+            sample_rate,
+            voices: [Voice::default(); MAX_SYNTH_VOICES],
+            envelopes: [None; MAX_SYNTH_VOICES],
+            delay: StereoDelay::new(sample_rate),
+            reverb: Reverb::new(sample_rate),
+        }
+    }
+
+    /// Starts (or retriggers) the envelope of `voice`
+    pub fn note_on(&mut self, voice: usize, frequency: f32, gain: f32) {
+        let Some(v) = self.voices.get_mut(voice) else {
+            return;
+        };
+        v.frequency = frequency;
+        v.gain = gain;
+        v.gate = true;
+        v.envelope.stage = EnvelopeStage::Attack;
+    }
+
+    /// Gives `voice` its own envelope, or back to `config.envelope` with `None`
+    pub fn set_voice_envelope(&mut self, voice: usize, envelope: Option<AdsrConfig>) {
+        if let Some(slot) = self.envelopes.get_mut(voice) {
+            *slot = envelope;
+        }
+    }
+
+    /// Moves `voice` into its release stage
+    pub fn note_off(&mut self, voice: usize) {
+        let sample_rate = self.sample_rate;
+        let config = self.envelopes.get(voice).copied().flatten().unwrap_or(self.config.envelope);
+        if let Some(v) = self.voices.get_mut(voice) {
+            v.gate = false;
+            v.envelope.release(&config, sample_rate);
+        }
+    }
+
+    /// Changes pitch and level without retriggering the envelope
+    pub fn set_voice(&mut self, voice: usize, frequency: f32, gain: f32) {
+        if let Some(v) = self.voices.get_mut(voice) {
+            v.frequency = frequency;
+            v.gain = gain;
+        }
+    }
+
+    /// Silences everything immediately, including effect tails
+    pub fn reset(&mut self) {
+        self.voices = [Voice::default(); MAX_SYNTH_VOICES];
+        self.delay = StereoDelay::new(self.sample_rate);
+        self.reverb = Reverb::new(self.sample_rate);
+    }
+
+    /// A voice whose key isn't held: idle if possible, otherwise the quietest releasing one
+    pub fn free_voice(&self) -> Option<usize> {
+        self.voices
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !v.gate)
+            .min_by(|(_, a), (_, b)| {
+                let sounding = |v: &Voice| v.envelope.stage != EnvelopeStage::Idle;
+                sounding(a).cmp(&sounding(b)).then(a.envelope.level.total_cmp(&b.envelope.level))
+            })
+            .map(|(i, _)| i)
+    }
+
+    pub fn voice_state(&self, voice: usize) -> Option<VoiceState> {
+        self.voices.get(voice).map(|v| VoiceState {
+            frequency: v.frequency,
+            gain: v.gain,
+            level: v.envelope.level,
+            stage: v.envelope.stage,
+            gate: v.gate,
+        })
+    }
+
+    /// Renders interleaved stereo into `output` (length a multiple of 2)
+    pub fn render(&mut self, output: &mut [f32]) {
+        let sample_rate = self.sample_rate;
+        let config = self.config;
+        let unison = config.unison.voices.clamp(1, MAX_UNISON as u32) as usize;
+        // Detune and pan of each unison oscillator, spread evenly around the centre
+        let mut detune = [1.0f64; MAX_UNISON];
+        let mut pan = [[1.0f32; 2]; MAX_UNISON];
+        for i in 0..unison {
+            let offset = if unison > 1 { i as f32 / (unison - 1) as f32 - 0.5 } else { 0.0 };
+            detune[i] = 2f64.powf((offset * config.unison.detune_cents) as f64 / 1200.0);
+            let position = offset * 2.0 * config.unison.stereo_spread.clamp(0.0, 1.0);
+            pan[i] = [(1.0 - position).min(1.0), (1.0 + position).min(1.0)];
+        }
+        let unison_gain = 1.0 / (unison as f32).sqrt();
+        let k = 2.0 - 2.0 * config.filter.resonance.clamp(0.0, 0.98);
+        let nyquist_limit = sample_rate * 0.45;
+
+        for frame in output.chunks_exact_mut(2) {
+            let mut mix = [0.0f32; 2];
+            for (voice, envelope) in self.voices.iter_mut().zip(&self.envelopes) {
+                if voice.envelope.stage == EnvelopeStage::Idle {
+                    continue;
+                }
+                let level = voice.envelope.next(envelope.as_ref().unwrap_or(&config.envelope), sample_rate);
+                let mut sample = [0.0f32; 2];
+                for i in 0..unison {
+                    let value = self.waveform.sample(voice.phases[i]) as f32;
+                    sample[0] += value * pan[i][0];
+                    sample[1] += value * pan[i][1];
+                    let step = voice.frequency as f64 * detune[i] / sample_rate as f64;
+                    voice.phases[i] = (voice.phases[i] + step).fract();
+                }
+                if config.filter.enabled {
+                    let cutoff = (config.filter.cutoff_hz * 2f32.powf(config.filter.envelope_octaves * level))
+                        .clamp(20.0, nyquist_limit);
+                    let g = (std::f32::consts::PI * cutoff / sample_rate).tan();
+                    sample = voice.filter.process(sample, g, k);
+                }
+                let amplitude = level * voice.gain * unison_gain;
+                mix[0] += sample[0] * amplitude;
+                mix[1] += sample[1] * amplitude;
+            }
+
+            if config.delay.mix > 0.0 {
+                let wet = self.delay.process(mix, &config.delay, sample_rate);
+                mix = [mix[0] + wet[0] * config.delay.mix, mix[1] + wet[1] * config.delay.mix];
+            }
+            if config.reverb.mix > 0.0 {
+                let wet = self.reverb.process(mix, &config.reverb);
+                mix = [mix[0] + wet[0] * config.reverb.mix, mix[1] + wet[1] * config.reverb.mix];
+            }
+            frame[0] = (mix[0] * self.master_volume).clamp(-1.0, 1.0);
+            frame[1] = (mix[1] * self.master_volume).clamp(-1.0, 1.0);
+        }
+    }
+}
@@ -1,44 +1,27 @@
 use anyhow::{Result, anyhow};
 use gstreamer as gst;
 use gstreamer_app as gst_app;
-use gstreamer_video as gst_video;
 use log::{debug, error, info, warn};
 use std::path::Path;
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use gst::prelude::*;
-use gst::glib::ControlFlow;
+use crate::Core;
 use crate::texture::TextureManager;
+use super::frame::{FrameUploader, frame_caps};
+use super::analysis::{AnalysisBus, install_analysis_handler};
+use super::waveform::WaveformTap;
 use wgpu;
 
-#[derive(Debug, Clone)]
-pub struct SpectrumData {
-    /// Number of frequency bands
-    pub bands: usize,
-    /// Magnitude values for each frequency band in dB
-    pub magnitudes: Vec<f32>,
-    /// Phase values for each frequency band
-    pub phases: Option<Vec<f32>>,
-    /// Timestamp of the spectrum data
-    pub timestamp: Option<gst::ClockTime>,
-}
-
-impl Default for SpectrumData {
-    fn default() -> Self {
-        Self {
-            bands: 0,
-            magnitudes: Vec::new(),
-            phases: None,
-            timestamp: None,
-        }
-    }
-}
+pub use super::analysis::{SpectrumData, LevelData, AnalysisSnapshot};
 
 /// Here I created a struct to organize the video text mang.
 /// Manages a video texture that can be updated frame by frame
 pub struct VideoTextureManager {
-    /// The underlying TextureManager that handles the WGPU resources
-    texture_manager: TextureManager,
+    /// Uploads decoded frames into the texture shaders sample
+    frames: FrameUploader,
     /// The GStreamer pipeline for video decoding
     pipeline: gst::Pipeline,
     /// The AppSink element that receives decoded frames
@@ -63,12 +46,10 @@ pub struct VideoTextureManager {
     loop_playback: Arc<Mutex<bool>>,
     /// Last frame update time
     last_update: Instant,
-    /// Frame buffer for the most recently decoded frame
-    current_frame: Arc<Mutex<Option<image::RgbaImage>>>,
+    /// Most recently decoded sample, uploaded on the next `update_texture`
+    current_frame: Arc<Mutex<Option<gst::Sample>>>,
     /// Path to the video file
     video_path: String,
-    /// Whether the video texture has been initialized
-    texture_initialized: bool,
     /// Frame counter for debugging
     frame_count: usize,
     /// Spectrum analysis enabled
@@ -77,22 +58,28 @@ pub struct VideoTextureManager {
     spectrum_bands: usize,
     /// Threshold in dB for spectrum analysis
     spectrum_threshold: i32,
-    /// Spectrum data from the most recent analysis
-    spectrum_data: Arc<Mutex<SpectrumData>>,
-    /// bpm
-    bpm_value: Arc<Mutex<f32>>,
+    /// Spectrum, level and BPM published by the bus handler
+    analysis: AnalysisBus,
+    /// Recent decoded audio samples per channel
+    waveform: WaveformTap,
+    /// Stepped (export) mode: frames are pulled on demand instead of played in real time
+    stepped: Arc<AtomicBool>,
+    /// Presentation window (pts, end) of the frame currently shown in stepped mode
+    stepped_frame: Option<(gst::ClockTime, gst::ClockTime)>,
+    /// Spectrum analyses decoded ahead of the current stepped frame, ordered by stream time
+    stepped_spectrum: VecDeque<SpectrumData>,
+    /// Playback state to restore when leaving stepped mode
+    was_playing_before_step: bool,
 }
 
 impl VideoTextureManager {
     pub fn new(
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
+        core: &Core,
         bind_group_layout: &wgpu::BindGroupLayout,
         video_path: impl AsRef<Path>,
     ) -> Result<Self> {
-        // Create a default 1x1 texture initially, note that, this going to be replaced with first video frame
-        let default_image = image::RgbaImage::new(1, 1);
-        let texture_manager = TextureManager::new(device, queue, &default_image, bind_group_layout);
+        // Starts as a 1x1 texture, note that, this going to be replaced with first video frame
+        let frames = FrameUploader::new(core, bind_group_layout);
         
         let path_str = video_path.as_ref()
             .to_str()
@@ -140,9 +127,8 @@ impl VideoTextureManager {
         let appsink = appsink.dynamic_cast::<gst_app::AppSink>()
             .map_err(|_| anyhow!("Failed to cast to AppSink"))?;
             // Configure appsink
-            appsink.set_caps(Some(&gst::Caps::builder("video/x-raw")
-                .field("format", gst_video::VideoFormat::Rgba.to_str())
-                .build()));
+            // NV12/I420 are uploaded as planes and converted on the GPU
+            appsink.set_caps(Some(&frame_caps()));
             appsink.set_max_buffers(2);
             // Drop old buffers when full
             appsink.set_drop(true);
@@ -178,140 +164,15 @@ impl VideoTextureManager {
         let spectrum_bands = 128;
         let spectrum_threshold = -60;
         let spectrum_enabled = true;
-        let spectrum_data = Arc::new(Mutex::new(SpectrumData::default()));
         
-        // bus watch for spectrum messages with debug
+        // Analysis messages are handled on the streaming thread as they are posted
         let bus = pipeline.bus().expect("Pipeline has no bus");
-        let spectrum_data_clone2 = spectrum_data.clone();
-        let bpm_value_clone = Arc::new(Mutex::new(0.0));
-        // message handler for spectrum messages (these are my sanity checks)
-        let _ = bus.add_watch(move |_, message| {
-            // Log ALL message types
-            info!("Bus message received: type={:?}", message.type_());
-            
-            // Print source information
-            if let Some(src) = message.src() {
-                info!("Message source: {}", src.name());
-            }
-            
-            match message.view() {
-                gst::MessageView::Element(element) => {
-                    if let Some(structure) = element.structure() {
-                        info!("Element message structure name: '{}'", structure.name());
-                        
-                        // Explicitly check for spectrum messages
-                        if structure.name() == "spectrum" {
-                            info!("SPECTRUM MESSAGE DETECTED ");
-                            info!("Full structure: {}", structure.to_string());
-                            
-                            // Try ALL possible ways to extract spectrum data
-                            let mut magnitude_values = Vec::new();
-                            
-                            // Method 1: Direct indexing
-                            for i in 0..5 {  // Just try first 5 bands initially
-                                let field_name = format!("magnitude[{}]", i);
-                                match structure.get::<f32>(&field_name) {
-                                    Ok(value) => {
-                                        info!("✅ Method 1 - Band {}: {} dB", i, value);
-                                        magnitude_values.push(value);
-                                    },
-                                    Err(e) => {
-                                        info!("❌ Method 1 failed: {:?}", e);
-                                        break;
-                                    }
-                                }
-                            }
-                            
-                            // Method 2: Try to access magnitude as array field
-                            if structure.has_field("magnitude") {
-                                info!("✅ Structure has 'magnitude' field");
-                            } else {
-                                info!("❌ Structure does NOT have 'magnitude' field");
-                            }
-                            
-                            // Method 3: Parse from structure string
-                            let struct_str = structure.to_string();
-                            info!("Structure string: {}", struct_str);
-                            
-                            // If we found magnitude values through any method, process them
-                            if !magnitude_values.is_empty() {
-                                // Continue extracting all magnitude values
-                                let mut i = magnitude_values.len();
-                                loop {
-                                    let field_name = format!("magnitude[{}]", i);
-                                    if let Ok(value) = structure.get::<f32>(&field_name) {
-                                        magnitude_values.push(value);
-                                        i += 1;
-                                    } else {
-                                        break;
-                                    }
-                                }
-                                
-                                // Log summary of extracted data
-                                info!("Extracted {} magnitude values", magnitude_values.len());
-                                
-                                // Calculate average magnitude
-                                let avg_magnitude = magnitude_values.iter().sum::<f32>() / magnitude_values.len() as f32;
-                                info!("Average magnitude: {:.2} dB", avg_magnitude);
-                                
-                                // Find peak frequency
-                                if let Some((peak_idx, &peak_val)) = magnitude_values.iter()
-                                    .enumerate()
-                                    .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)) 
-                                {
-                                    info!("Peak frequency: band {} at {:.2} dB", peak_idx, peak_val);
-                                }
-                                
-                                // Update spectrum data
-                                if let Ok(mut data) = spectrum_data_clone2.lock() {
-                                    *data = SpectrumData {
-                                        bands: magnitude_values.len(),
-                                        magnitudes: magnitude_values,
-                                        phases: None,
-                                        timestamp: structure.get("timestamp").ok(),
-                                    };
-                                }
-                            } else {
-                                warn!("⚠️ Failed to extract any magnitude values from spectrum message");
-                            }
-                        }
-
-                        if structure.name() == "bpm" || structure.name().contains("bpm") {
-                                    info!("BPM MESSAGE DETECTED");
-                                    info!("Full BPM structure: {}", structure.to_string());
-                                    // Try to extract BPM value from structure
-                                    if let Ok(bpm_val) = structure.get::<f32>("bpm") {
-                                        info!("BPM detected: {:.1}", bpm_val);
-                                        // Update our stored BPM value
-                                        if let Ok(mut bpm_lock) = bpm_value_clone.lock() {
-                                            *bpm_lock = bpm_val as f32;
-                                        }
-                                    }
-                                }
-                            }
-                        },
-                        gst::MessageView::Tag(tag) => {
-                            let tags = tag.tags();
-                            // Check for BPM tag
-                            if let Some(bpm) = tags.get::<gst::tags::BeatsPerMinute>() {
-                                let bpm_val = bpm.get();
-                                info!("BPM tag detected: {:.1}", bpm_val);
-                                
-                                if bpm_val > 0.0 {
-                                    if let Ok(mut bpm_lock) = bpm_value_clone.lock() {
-                                        *bpm_lock = bpm_val as f32;
-                                    }
-                                }
-                    }
-                },
-                gst::MessageView::Error(err) => {
-                    error!("Pipeline error: {} ({})", err.error(), err.debug().unwrap_or_default());
-                },
-                _ => (),
-            }
-            
-            ControlFlow::Continue
-        }).map_err(|_| anyhow!("Failed to add bus watch"));
+        let analysis = install_analysis_handler(&bus);
+        
+        // Raw samples, tapped once an audio stream shows up
+        let waveform = WaveformTap::default();
+        let waveform_clone = waveform.clone();
+        let pipeline_weak = pipeline.downgrade();
         
         decodebin.connect_pad_added(move |_, pad| {
             let caps = match pad.current_caps() {
@@ -386,7 +247,8 @@ impl VideoTextureManager {
                             .property("threshold", spectrum_threshold)
                             .property("post-messages", true)
                             .property("message-magnitude", true)
-                            .property("message-phase", false)
+                            .property("message-phase", true)
+                            .property("multi-channel", true)
                             .property("interval", 50000000u64) 
                             .build() {
                                 Ok(e) => {
@@ -399,6 +261,17 @@ impl VideoTextureManager {
                                     return;
                                 }
                             };
+                        let level = match gst::ElementFactory::make("level")
+                            .name("level")
+                            .property("post-messages", true)
+                            .property("interval", 50000000u64)
+                            .build() {
+                                Ok(e) => e,
+                                Err(_) => {
+                                    warn!("Failed to create level meter");
+                                    return;
+                                }
+                            };
                         let volume = match gst::ElementFactory::make("volume")
                             .name("volume")
                             .property("volume", 1.0)
@@ -421,25 +294,49 @@ impl VideoTextureManager {
                                 }
                             };
                             
+                        // Analysis output is split between the speakers and the waveform tap
+                        let (tee, play_queue) = match (
+                            gst::ElementFactory::make("tee").name("audiotee").build(),
+                            gst::ElementFactory::make("queue").name("play_queue").build(),
+                        ) {
+                            (Ok(tee), Ok(queue)) => (tee, queue),
+                            _ => {
+                                warn!("Failed to create audio tee");
+                                return;
+                            }
+                        };
+                        let Some(pipeline) = pipeline_weak.upgrade() else {
+                            return;
+                        };
+                            
                         // Add elements to pipeline
-                        if let Err(e) = pad.parent_element().unwrap().parent().unwrap()
-                            .downcast_ref::<gst::Pipeline>().unwrap()
-                            .add_many(&[&audioconvert, &audioresample, &bpmdetect, &spectrum, &volume, &audio_sink]) {
+                        if let Err(e) = pipeline.add_many(&[&audioconvert, &audioresample, &bpmdetect, &spectrum, &level, &tee, &play_queue, &volume, &audio_sink]) {
                             warn!("Failed to add audio elements: {:?}", e);
                             return;
                         }
                         // Link audio elements
-                        if let Err(e) = gst::Element::link_many(&[&audioconvert, &audioresample, &bpmdetect, &spectrum, &volume, &audio_sink]) {
+                        if let Err(e) = gst::Element::link_many(&[&audioconvert, &audioresample, &bpmdetect, &spectrum, &level, &tee])
+                            .and_then(|_| gst::Element::link_many(&[&tee, &play_queue, &volume, &audio_sink])) {
                             warn!("Failed to link audio elements: {:?}", e);
                             return;
                         }
-                            
+                        match waveform_clone.build_branch(&pipeline, "wave") {
+                            Ok(wave_queue) => {
+                                if let Err(e) = tee.link(&wave_queue) {
+                                    warn!("Failed to link waveform branch: {:?}", e);
+                                }
+                            }
+                            Err(e) => warn!("Failed to create waveform branch: {}", e),
+                        }
                         
                         // Set elements to PAUSED state
                         let _ = audioconvert.sync_state_with_parent();
                         let _ = audioresample.sync_state_with_parent();
                         let _ = bpmdetect.sync_state_with_parent();
                         let _ = spectrum.sync_state_with_parent();
+                        let _ = level.sync_state_with_parent();
+                        let _ = tee.sync_state_with_parent();
+                        let _ = play_queue.sync_state_with_parent();
                         let _ = volume.sync_state_with_parent();
                         let _ = audio_sink.sync_state_with_parent();
                         
@@ -477,63 +374,25 @@ impl VideoTextureManager {
         let is_muted = Arc::new(Mutex::new(false));
         
         // Setup callbacks to receive frames
+        let stepped = Arc::new(AtomicBool::new(false));
+        let stepped_clone = stepped.clone();
         appsink.set_callbacks(
             gst_app::AppSinkCallbacks::builder()
                 .new_sample(move |sink| {
+                    // In stepped mode the sample stays queued so the sink blocks the decoder
+                    // until step_to pulls it
+                    if stepped_clone.load(Ordering::Acquire) {
+                        return Ok(gst::FlowSuccess::Ok);
+                    }
+
                     let sample = match sink.pull_sample() {
                         Ok(sample) => sample,
                         Err(_) => return Err(gst::FlowError::Eos),
                     };
                     
-                    let buffer = match sample.buffer() {
-                        Some(buffer) => buffer,
-                        _ => return Err(gst::FlowError::Error),
-                    };
-                    
-                    let caps = match sample.caps() {
-                        Some(caps) => caps,
-                        _ => return Err(gst::FlowError::Error),
-                    };
-                    
-                    let video_info = match gst_video::VideoInfo::from_caps(caps) {
-                        Ok(info) => info,
-                        Err(_) => return Err(gst::FlowError::Error),
-                    };
-                    
-                    let map = match buffer.map_readable() {
-                        Ok(map) => map,
-                        Err(_) => return Err(gst::FlowError::Error),
-                    };
-                    
-                    // Access the raw frame data
-                    let frame_data = map.as_slice();
-                    let width = video_info.width() as usize;
-                    let height = video_info.height() as usize;
-                    
-                    // Create an RgbaImage from the frame data
-                    // (We need to copy the data because buffer will be unmapped after this function)
-                    let mut rgba_image = image::RgbaImage::new(width as u32, height as u32);
-                    
-                    // Stride might be larger than width * 4
-                    let stride = video_info.stride()[0] as usize;
-                    
-                    for y in 0..height {
-                        let src_start = y * stride;
-                        let src_end = src_start + width * 4;
-                        let dst_start = y * width * 4;
-                        let dst_end = dst_start + width * 4;
-                        
-                        // Copy row by row to handle stride correctly
-                        let dst_buffer = rgba_image.as_mut();
-                        if src_end <= frame_data.len() && dst_end <= dst_buffer.len() {
-                            dst_buffer[dst_start..dst_end]
-                                .copy_from_slice(&frame_data[src_start..src_end]);
-                        }
-                    }
-                    
-                    // Store the frame
+                    // Keep the sample itself, it is uploaded straight from the decoder's buffer
                     if let Ok(mut frame_lock) = current_frame_clone.lock() {
-                        *frame_lock = Some(rgba_image);
+                        *frame_lock = Some(sample);
                     }
                     
                     Ok(gst::FlowSuccess::Ok)
@@ -543,7 +402,7 @@ impl VideoTextureManager {
         
         // init the object
         let mut video_texture = Self {
-            texture_manager,
+            frames,
             pipeline,
             appsink,
             has_audio: false,
@@ -558,13 +417,16 @@ impl VideoTextureManager {
             last_update: Instant::now(),
             current_frame,
             video_path: path_str,
-            texture_initialized: false,
             frame_count: 0,
             spectrum_enabled,
             spectrum_bands,
             spectrum_threshold,
-            spectrum_data,
-            bpm_value: Arc::new(Mutex::new(0.0)),
+            analysis,
+            waveform,
+            stepped,
+            stepped_frame: None,
+            stepped_spectrum: VecDeque::new(),
+            was_playing_before_step: false,
         };
         // Start pipeline in paused state to get video info
         if video_texture.pipeline.set_state(gst::State::Paused).is_err() {
@@ -632,7 +494,7 @@ impl VideoTextureManager {
     
     /// Get the texture manager (for binding to shaders)
     pub fn texture_manager(&self) -> &TextureManager {
-        &self.texture_manager
+        self.frames.texture_manager()
     }
     
     /// Update the texture with the current video frame
@@ -642,8 +504,10 @@ impl VideoTextureManager {
         queue: &wgpu::Queue, 
         bind_group_layout: &wgpu::BindGroupLayout
     ) -> Result<bool> {
-        // No update needed if video is not playing
-        if !*self.is_playing.lock().unwrap() {
+        self.handle_bus_messages()?;
+
+        // No update needed if video is not playing, stepped mode uploads in step_to
+        if !*self.is_playing.lock().unwrap() || self.is_stepped() {
             return Ok(false);
         }
         
@@ -656,285 +520,12 @@ impl VideoTextureManager {
         // If we have a frame, update the texture
         if let Some(frame) = frame_to_process {
             self.frame_count += 1;
-            
-            // Get frame dimensions
-            let width = frame.width();
-            let height = frame.height();
+            self.upload_frame(device, queue, bind_group_layout, &frame);
             
             // Log less frequently to reduce spam
             if self.frame_count % 30 == 0 {
                 debug!("Processing video frame #{} (dimensions: {}x{})", 
-                     self.frame_count, width, height);
-            }
-            if self.has_audio && self.spectrum_enabled {
-                if let Some(bus) = self.pipeline.bus() {
-                    // Poll for pending messages
-                    while let Some(message) = bus.pop() {
-                        match message.view() {
-                            gst::MessageView::Element(element) => {
-                                if let Some(structure) = element.structure() {
-                                    //spectrum data
-                                    if structure.name() == "spectrum" {
-                                        info!("🎵 Spectrum data received");
-                                        
-                                        // Extract magnitude values - two possible approaches
-                                        let mut magnitude_values = Vec::with_capacity(128);
-                                        
-                                        // APPROACH 1: Parse from structure string
-                                        let struct_str = structure.to_string();
-                                        if struct_str.contains("magnitude=(float){") {
-                                            // Extract magnitude values from string
-                                            if let Some(start_idx) = struct_str.find("magnitude=(float){") {
-                                                if let Some(end_idx) = struct_str[start_idx..].find("}") {
-                                                    let magnitude_str = &struct_str[start_idx + "magnitude=(float){".len()..start_idx + end_idx];
-                                                    let values: Vec<&str> = magnitude_str.split(',').collect();
-                                                    
-                                                    for value_str in values {
-                                                        if let Ok(value) = value_str.trim().parse::<f32>() {
-                                                            magnitude_values.push(value);
-                                                        }
-                                                    }
-                                                    
-                                                    info!("Extracted {} magnitude values from string", magnitude_values.len());
-                                                }
-                                            }
-                                        }
-                                        
-                                        // APPROACH 2: Try to access directly by index if approach 1 fails
-                                        if magnitude_values.is_empty() {
-                                            for i in 0..128 {
-                                                let field_name = format!("magnitude[{}]", i);
-                                                if let Ok(value) = structure.get::<f32>(&field_name) {
-                                                    magnitude_values.push(value);
-                                                } else {
-                                                    break;
-                                                }
-                                            }
-                                            
-                                            if !magnitude_values.is_empty() {
-                                                info!("Extracted {} magnitude values by field access", magnitude_values.len());
-                                            }
-                                        }
-                                        
-                                        // Process spectrum data if we have it
-                                        if !magnitude_values.is_empty() {
-                                            // Calculate audio metrics
-                                            let bands = magnitude_values.len();
-                                            
-                                            // Calculate average and normalize values
-                                            // Values are in dB (typically negative, with higher/less negative being louder)
-                                            let threshold = self.spectrum_threshold as f32;
-                                            
-                                            // Create normalized values from -60dB (silence) to 0dB (loudest)
-                                            let normalized_values: Vec<f32> = magnitude_values.iter()
-                                                .map(|&v| ((v - threshold) / -threshold).max(0.0).min(1.0))
-                                                .collect();
-                                            
-                                            // Calculate frequency band energy averages (useful for visualization)
-                                            let bass_range = (bands as f32 * 0.1) as usize; // First 10% of frequencies
-                                            let mid_range_start = bass_range;
-                                            let mid_range_end = (bands as f32 * 0.5) as usize; // 10-50% of frequencies
-                                            let high_range_start = mid_range_end;
-                                            
-                                            let bass_energy = if bass_range > 0 {
-                                                normalized_values[0..bass_range].iter().sum::<f32>() / bass_range as f32
-                                            } else {
-                                                0.0
-                                            };
-                                            
-                                            let mid_energy = if mid_range_end > mid_range_start {
-                                                normalized_values[mid_range_start..mid_range_end].iter().sum::<f32>() 
-                                                / (mid_range_end - mid_range_start) as f32
-                                            } else {
-                                                0.0
-                                            };
-                                            
-                                            let high_energy = if bands > high_range_start {
-                                                normalized_values[high_range_start..].iter().sum::<f32>() 
-                                                / (bands - high_range_start) as f32
-                                            } else {
-                                                0.0
-                                            };
-                                            
-                                            // Find peak frequency band
-                                            let peak_info = normalized_values.iter()
-                                                .enumerate()
-                                                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-                                            
-                                            if let Some((peak_idx, &peak_val)) = peak_info {
-                                                 // Rough estimate of frequency based on band index
-                                                let peak_freq = (peak_idx as f32 / bands as f32) * 20000.0;
-                                                info!("Peak freq: ~{:.0} Hz (band {}, val: {:.2})", peak_freq, peak_idx, peak_val);
-                                            }
-                                            
-                                            // Log energy metrics
-                                            info!("Audio energy - Bass: {:.2}, Mid: {:.2}, High: {:.2}", 
-                                                 bass_energy, mid_energy, high_energy);
-                                            
-                                            // Update spectrum data
-                                            if let Ok(mut data) = self.spectrum_data.lock() {
-                                                *data = SpectrumData {
-                                                    bands,
-                                                    magnitudes: magnitude_values,
-                                                    phases: None,
-                                                    timestamp: structure.get("timestamp").ok(),
-                                                };
-                                            }
-                                        }
-                                    }
-                                    
-                                    // Check for BPM messages
-                                    if structure.name() == "bpm" || structure.name().contains("bpm") {
-                                        info!("🎵 BPM data received");
-                                        info!("Full BPM structure: {}", structure.to_string());
-                                        
-                                        // Try to extract BPM value directly
-                                        if let Ok(bpm_val) = structure.get::<f32>("bpm") {
-                                            info!("BPM detected: {:.1}", bpm_val);
-                                            if let Ok(mut bpm_lock) = self.bpm_value.lock() {
-                                                // Apply musical heuristics to handle tempo octave ambiguity: https://www.ifs.tuwien.ac.at/~knees/publications/hoerschlaeger_etal_smc_2015.pdf
-                                                let current_bpm = *bpm_lock;
-                                                
-                                                if current_bpm == 0.0 && bpm_val > 0.0 {
-                                                    // First detection - apply preference for 70-150 BPM range
-                                                    if bpm_val > 150.0 {
-                                                        // If detected BPM is high, use half tempo
-                                                        *bpm_lock = (bpm_val / 2.0) as f32;
-                                                        info!("Initial BPM halved: {:.1} → {:.1}", bpm_val, *bpm_lock);
-                                                    } else if bpm_val < 70.0 {
-                                                        // If detected BPM is low, use double tempo
-                                                        *bpm_lock = (bpm_val * 2.0) as f32;
-                                                        info!("Initial BPM doubled: {:.1} → {:.1}", bpm_val, *bpm_lock);
-                                                    } else {
-                                                        // Within preferred range - use directly
-                                                        *bpm_lock = bpm_val as f32;
-                                                    }
-                                                } else if current_bpm > 0.0 && bpm_val > 0.0 {
-                                                    // Subsequent detection - check for tempo octave jumps
-                                                    if bpm_val > current_bpm * 1.8 && bpm_val < current_bpm * 2.2 {
-                                                        // Double tempo detected - stay in preferred range if possible
-                                                        let target = if current_bpm >= 70.0 && current_bpm <= 150.0 {
-                                                            current_bpm  // Keep current if already in good range
-                                                        } else if bpm_val >= 70.0 && bpm_val <= 150.0 {
-                                                            bpm_val      // Use new if it's in good range
-                                                        } else {
-                                                            // Neither in ideal range - prefer the lower value
-                                                            current_bpm
-                                                        };
-                                                        *bpm_lock = target as f32;
-                                                        info!("Tempo doubling corrected: {:.1} → {:.1}", bpm_val, *bpm_lock);
-                                                    } else if bpm_val > current_bpm * 0.45 && bpm_val < current_bpm * 0.55 {
-                                                        // Half tempo detected - stay in preferred range if possible
-                                                        let target = if current_bpm >= 70.0 && current_bpm <= 150.0 {
-                                                            current_bpm  // Keep current if already in good range
-                                                        } else if bpm_val >= 70.0 && bpm_val <= 150.0 {
-                                                            bpm_val      // Use new if it's in good range
-                                                        } else {
-                                                            // Neither in ideal range - prefer the higher value
-                                                            current_bpm
-                                                        };
-                                                        *bpm_lock = target as f32;
-                                                        info!("Tempo halving corrected: {:.1} → {:.1}", bpm_val, *bpm_lock);
-                                                    } else {
-                                                        // Apply light smoothing to avoid jumps
-                                                        *bpm_lock = (current_bpm * 0.8 + bpm_val as f32 * 0.2) as f32;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            },
-                            // Also check for tag messages as bpmdetect might send tags
-                            gst::MessageView::Tag(tag) => {
-                                let tags = tag.tags();
-                                
-                                // Check for BPM tag
-                                if let Some(bpm) = tags.get::<gst::tags::BeatsPerMinute>() {
-                                    let bpm_val = bpm.get();
-                                    info!("BPM tag detected: {:.1}", bpm_val);
-                                    
-                                    if bpm_val > 0.0 {
-                                        // Update stored BPM value with musical heuristics
-                                        if let Ok(mut bpm_lock) = self.bpm_value.lock() {
-                                            let current_bpm = *bpm_lock;
-                                            let bpm_val_f32 = bpm_val as f32;
-                                            
-                                            // Tags are usually pre-processed, but I ll apply heuristics anyway
-                                            if current_bpm == 0.0 {
-                                                // First detection - apply preference for 70-150 BPM range
-                                                if bpm_val_f32 > 150.0 {
-                                                    *bpm_lock = bpm_val_f32 / 2.0;
-                                                    info!("Tag BPM halved: {:.1} → {:.1}", bpm_val, *bpm_lock as f64);
-                                                } else if bpm_val_f32 < 70.0 {
-                                                    *bpm_lock = bpm_val_f32 * 2.0;
-                                                    info!("Tag BPM doubled: {:.1} → {:.1}", bpm_val, *bpm_lock as f64);
-                                                } else {
-                                                    *bpm_lock = bpm_val_f32;
-                                                }
-                                            } else {
-                                                // Check for octave relationship and prefer values in 70-150 range
-                                                let double_current = current_bpm * 2.0;
-                                                let half_current = current_bpm / 2.0;
-                                                
-                                                if (bpm_val_f32 > current_bpm * 1.8 && bpm_val_f32 < current_bpm * 2.2) || 
-                                                (bpm_val_f32 > current_bpm * 0.45 && bpm_val_f32 < current_bpm * 0.55) {
-                                                    // Octave relationship detected
-                                                    // Choose value in preferred range
-                                                    let candidates = [bpm_val_f32, current_bpm, double_current, half_current];
-                                                    let preferred = candidates.iter()
-                                                        .filter(|&&v| v >= 70.0 && v <= 150.0)
-                                                        .min_by(|a, b| {
-                                                            let a_dist = (**a - 110.0).abs();
-                                                            let b_dist = (**b - 110.0).abs();
-                                                            a_dist.partial_cmp(&b_dist).unwrap_or(std::cmp::Ordering::Equal)
-                                                        });
-                                                    
-                                                    if let Some(&best_bpm) = preferred {
-                                                        *bpm_lock = best_bpm;
-                                                        info!("Tag BPM adjusted to preferred range: {:.1} → {:.1}", bpm_val, *bpm_lock as f64);
-                                                    } else {
-                                                        // No value in preferred range, use tag value
-                                                        *bpm_lock = bpm_val_f32;
-                                                    }
-                                                } else {
-                                                    // Not an octave relationship - tags are usually reliable
-                                                    // Use 30% weighting for current value to avoid abrupt changes
-                                                    *bpm_lock = current_bpm * 0.3 + bpm_val_f32 * 0.7;
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            },
-                            _ => (),
-                        }
-                    }
-                }
-            }
-            // ALWAYS recreate the texture for the first frame or if dimensions don't match
-            let should_recreate = !self.texture_initialized || 
-                                 self.dimensions != (width, height) ||
-                                 self.dimensions.0 <= 1 || 
-                                 self.dimensions.1 <= 1 ||
-                                 self.frame_count <= 3;
-            
-            if should_recreate {
-                info!("Creating new texture with dimensions: {}x{}", width, height);
-                
-                // Create a completely new texture with the frame's dimensions
-                let new_texture_manager = TextureManager::new(
-                    device, 
-                    queue, 
-                    &frame, 
-                    bind_group_layout
-                );
-                
-                self.texture_manager = new_texture_manager;
-                self.dimensions = (width, height);
-                self.texture_initialized = true;
-            } else {
-                self.texture_manager.update(queue, &frame);
+                     self.frame_count, self.dimensions.0, self.dimensions.1);
             }
             
             // Get current position
@@ -970,6 +561,202 @@ impl VideoTextureManager {
         }
     }
     
+    /// Drains what the analysis handler passes on, looping or pausing on end of stream.
+    /// Errors are already logged by the handler.
+    fn handle_bus_messages(&mut self) -> Result<()> {
+        let Some(bus) = self.pipeline.bus() else {
+            return Ok(());
+        };
+        while let Some(message) = bus.pop() {
+            if let gst::MessageView::Eos(_) = message.view() {
+                if self.is_stepped() {
+                    continue;
+                }
+                if self.is_looping() {
+                    debug!("Looping video at end of stream");
+                    self.seek(gst::ClockTime::ZERO)?;
+                } else {
+                    debug!("Pausing at end of stream");
+                    self.pause()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads a decoded frame, recreating the texture only when its size or format changes
+    fn upload_frame(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        sample: &gst::Sample,
+    ) {
+        match self.frames.upload(device, queue, bind_group_layout, sample) {
+            Ok(_) => self.dimensions = self.frames.dimensions(),
+            Err(e) => warn!("Failed to upload video frame: {}", e),
+        }
+    }
+
+    /// Whether the video is in stepped (frame-accurate) mode
+    pub fn is_stepped(&self) -> bool {
+        self.stepped.load(Ordering::Acquire)
+    }
+
+    /// Switches between real-time playback and stepped mode.
+    ///
+    /// In stepped mode nothing syncs to the clock and the appsink holds one frame at a
+    /// time, so decoding only advances when `step_to` asks for the next frame. Audio is
+    /// muted but still flows through the spectrum analyzer.
+    pub fn set_stepped(&mut self, enabled: bool) -> Result<()> {
+        if self.is_stepped() == enabled {
+            return Ok(());
+        }
+        info!("Video stepped mode: {}", enabled);
+
+        if enabled {
+            self.was_playing_before_step = self.is_playing();
+        }
+        self.pipeline.set_state(gst::State::Paused)
+            .map_err(|e| anyhow!("Failed to pause pipeline: {:?}", e))?;
+        let _ = self.pipeline.state(gst::ClockTime::from_seconds(5));
+
+        self.stepped.store(enabled, Ordering::Release);
+        self.appsink.set_sync(!enabled);
+        self.appsink.set_drop(!enabled);
+        self.appsink.set_max_buffers(if enabled { 1 } else { 2 });
+        if let Some(audio_sink) = self.pipeline.by_name("audiosink") {
+            if audio_sink.find_property("sync").is_some() {
+                audio_sink.set_property("sync", !enabled);
+            }
+        }
+        if let Some(wave_sink) = self.pipeline.by_name("wave_sink") {
+            wave_sink.set_property("sync", !enabled);
+        }
+        if let Some(volume_elem) = self.pipeline.by_name("volume") {
+            volume_elem.set_property("mute", enabled || self.is_muted());
+        }
+        self.stepped_frame = None;
+        self.stepped_spectrum.clear();
+        self.analysis.set_recording(enabled);
+
+        if enabled {
+            self.pipeline.set_state(gst::State::Playing)
+                .map_err(|e| anyhow!("Failed to start stepped playback: {:?}", e))?;
+            *self.is_playing.lock().unwrap() = false;
+        } else {
+            // Flush the frame the appsink was holding for us
+            let position = self.position();
+            self.seek(position)?;
+            if self.was_playing_before_step {
+                self.play()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn frame_duration(&self) -> gst::ClockTime {
+        match self.framerate {
+            Some(f) if f.numer() > 0 => gst::ClockTime::from_nseconds(
+                1_000_000_000u64 * f.denom() as u64 / f.numer() as u64
+            ),
+            _ => gst::ClockTime::from_mseconds(33),
+        }
+    }
+
+    /// Shows the frame whose presentation window contains `time`, blocking until it is
+    /// decoded, and makes `spectrum_data` return the analysis for the same moment.
+    /// Enters stepped mode if needed. Returns true if the texture changed.
+    pub fn step_to(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        time: gst::ClockTime,
+    ) -> Result<bool> {
+        self.set_stepped(true)?;
+
+        let target = match self.duration {
+            Some(duration) if self.is_looping() && duration > gst::ClockTime::ZERO => {
+                gst::ClockTime::from_nseconds(time.nseconds() % duration.nseconds())
+            }
+            _ => time,
+        };
+
+        let needs_seek = match self.stepped_frame {
+            Some((pts, end)) => {
+                if target >= pts && target < end {
+                    // Still inside the current frame
+                    *self.position.lock().unwrap() = target;
+                    self.collect_stepped_spectrum(target);
+                    return Ok(false);
+                }
+                // Decoding forward is cheaper than a seek for short gaps
+                target < pts || target > end + gst::ClockTime::from_seconds(1)
+            }
+            None => true,
+        };
+
+        if needs_seek {
+            debug!("Stepped seek to {:?}", target);
+            self.pipeline
+                .seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE, target)
+                .map_err(|_| anyhow!("Failed to seek to {:?}", target))?;
+            let _ = self.pipeline.state(gst::ClockTime::from_seconds(5));
+            self.stepped_frame = None;
+            self.stepped_spectrum.clear();
+        }
+
+        let frame_duration = self.frame_duration();
+        let mut frame = None;
+        loop {
+            let sample = match self.appsink.try_pull_sample(gst::ClockTime::from_seconds(5)) {
+                Some(sample) => sample,
+                None if self.appsink.is_eos() => break,
+                None => return Err(anyhow!("Timed out waiting for video frame at {:?}", target)),
+            };
+            let Some(buffer) = sample.buffer() else {
+                continue;
+            };
+            let pts = buffer.pts().unwrap_or(target);
+            let end = pts + buffer.duration().unwrap_or(frame_duration);
+            self.stepped_frame = Some((pts, end));
+            frame = Some(sample);
+            if end > target {
+                break;
+            }
+        }
+
+        *self.position.lock().unwrap() = target;
+        self.collect_stepped_spectrum(target);
+
+        let Some(sample) = frame else {
+            return Ok(false);
+        };
+        self.frame_count += 1;
+        self.upload_frame(device, queue, bind_group_layout, &sample);
+        Ok(true)
+    }
+
+    /// Queues analyses recorded by the bus handler and publishes the latest one at or before `target`
+    fn collect_stepped_spectrum(&mut self, target: gst::ClockTime) {
+        if !self.has_audio {
+            return;
+        }
+        self.stepped_spectrum.extend(self.analysis.drain_history());
+
+        let mut current = None;
+        while let Some(front) = self.stepped_spectrum.front() {
+            match front.timestamp {
+                Some(ts) if ts > target => break,
+                _ => current = self.stepped_spectrum.pop_front(),
+            }
+        }
+        if let Some(data) = current {
+            self.analysis.snapshot.publish_spectrum(data);
+        }
+    }
+
     /// Start playing the video
     pub fn play(&mut self) -> Result<()> {
         info!("Playing video");
@@ -1010,6 +797,7 @@ impl VideoTextureManager {
         if self.pipeline.seek_simple(seek_flags, position).is_ok() {
             debug!("Seek successful");
             *self.position.lock().unwrap() = position;
+            self.waveform.clear();
             Ok(())
         } else {
             let err = anyhow!("Failed to seek to {:?}", position);
@@ -1152,20 +940,39 @@ impl VideoTextureManager {
     
     /// Get current spectrum data
     pub fn spectrum_data(&self) -> SpectrumData {
-        match self.spectrum_data.lock() {
-            Ok(data) => data.clone(),
-            Err(_) => SpectrumData::default(),
-        }
+        (*self.analysis.snapshot.spectrum()).clone()
+    }
+
+    /// Get the current per-channel RMS/peak levels
+    pub fn level_data(&self) -> LevelData {
+        (*self.analysis.snapshot.level()).clone()
+    }
+
+    /// Lock-free handle to the analysis results, readable from any thread
+    pub fn analysis_snapshot(&self) -> AnalysisSnapshot {
+        self.analysis.snapshot.clone()
+    }
+
+    /// The last `WAVEFORM_SAMPLES` mono samples of the audio track in -1..1
+    pub fn waveform(&self) -> Vec<f32> {
+        self.waveform.mono()
+    }
+
+    /// Left and right channels of the last `WAVEFORM_SAMPLES` samples
+    pub fn waveform_channels(&self) -> (Vec<f32>, Vec<f32>) {
+        self.waveform.channels()
     }
+
+    /// Sample rate of the decoded audio, once playback has started
+    pub fn audio_sample_rate(&self) -> Option<u32> {
+        self.waveform.sample_rate()
+    }
+
     pub fn get_bpm(&self) -> f32 {
         if !self.has_audio {
             return 0.0;
         }
-        
-        match self.bpm_value.lock() {
-            Ok(bpm) => *bpm,
-            Err(_) => 0.0
-        }
+        self.analysis.snapshot.bpm()
     }
     /// Set interval between spectrum updates in milliseconds
     pub fn set_spectrum_interval(&mut self, interval_ms: u64) -> Result<()> {
@@ -0,0 +1,121 @@
+use anyhow::{Result, anyhow};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
+use gst::prelude::*;
+use super::analysis::caps_rate;
+
+/// Number of most recent samples kept per channel
+pub const WAVEFORM_SAMPLES: usize = 1024;
+
+/// Ring buffer of recent stereo samples fed by an appsink branch of a media pipeline.
+/// Mono sources are upmixed by the branch's `audioconvert`, so both channels are always present.
+#[derive(Clone)]
+pub struct WaveformTap {
+    samples: Arc<Mutex<VecDeque<[f32; 2]>>>,
+    /// Rate from the caps of the last sample, 0 until one arrives
+    rate: Arc<AtomicU32>,
+}
+
+impl Default for WaveformTap {
+    fn default() -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(VecDeque::with_capacity(WAVEFORM_SAMPLES))),
+            rate: Arc::new(AtomicU32::new(0)),
+        }
+    }
+}
+
+impl WaveformTap {
+    /// Builds `queue ! audioconvert ! appsink` in `pipeline`, feeding this tap, and returns
+    /// the queue to link a `tee` source pad to. Element names are prefixed with `prefix`;
+    /// the new elements follow the pipeline's state, so this also works from `pad-added`.
+    pub fn build_branch(&self, pipeline: &gst::Pipeline, prefix: &str) -> Result<gst::Element> {
+        let make = |factory: &str, name: &str| {
+            gst::ElementFactory::make(factory)
+                .name(format!("{}_{}", prefix, name))
+                .build()
+                .map_err(|_| anyhow!("Failed to create {} element", factory))
+        };
+        let queue = make("queue", "queue")?;
+        let convert = make("audioconvert", "convert")?;
+        let sink = make("appsink", "sink")?
+            .dynamic_cast::<gst_app::AppSink>()
+            .map_err(|_| anyhow!("Failed to cast to AppSink"))?;
+        sink.set_caps(Some(&gst::Caps::builder("audio/x-raw")
+            .field("format", "F32LE")
+            .field("layout", "interleaved")
+            .field("channels", 2i32)
+            .build()));
+        sink.set_max_buffers(4);
+        sink.set_drop(true);
+        sink.set_sync(true);
+
+        pipeline.add_many(&[&queue, &convert, sink.upcast_ref()])
+            .map_err(|_| anyhow!("Failed to add waveform elements to pipeline"))?;
+        gst::Element::link_many(&[&queue, &convert, sink.upcast_ref()])
+            .map_err(|_| anyhow!("Failed to link waveform branch"))?;
+
+        let samples = self.samples.clone();
+        let rate = self.rate.clone();
+        sink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    // The caps leave the rate open, so it follows the source
+                    if let Some(negotiated) = sample.caps().and_then(caps_rate) {
+                        rate.store(negotiated, Ordering::Relaxed);
+                    }
+                    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+                    if let Ok(mut samples) = samples.lock() {
+                        for frame in map.as_slice().chunks_exact(8) {
+                            samples.push_back([
+                                f32::from_le_bytes([frame[0], frame[1], frame[2], frame[3]]),
+                                f32::from_le_bytes([frame[4], frame[5], frame[6], frame[7]]),
+                            ]);
+                        }
+                        while samples.len() > WAVEFORM_SAMPLES {
+                            samples.pop_front();
+                        }
+                    }
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
+        // Downstream first so the sink is ready before data arrives
+        for element in [sink.upcast_ref(), &convert, &queue] {
+            let _ = element.sync_state_with_parent();
+        }
+        Ok(queue)
+    }
+
+    /// Mono mix of the last `WAVEFORM_SAMPLES` samples in -1..1 (fewer right after loading or seeking)
+    pub fn mono(&self) -> Vec<f32> {
+        match self.samples.lock() {
+            Ok(samples) => samples.iter().map(|[l, r]| (l + r) * 0.5).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Left and right channels of the last `WAVEFORM_SAMPLES` samples
+    pub fn channels(&self) -> (Vec<f32>, Vec<f32>) {
+        match self.samples.lock() {
+            Ok(samples) => samples.iter().map(|&[l, r]| (l, r)).unzip(),
+            Err(_) => (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// Sample rate of the tapped stream, once the first buffer has arrived
+    pub fn sample_rate(&self) -> Option<u32> {
+        Some(self.rate.load(Ordering::Relaxed)).filter(|&rate| rate > 0)
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.clear();
+        }
+    }
+}
@@ -1,18 +1,110 @@
 use anyhow::{Result, anyhow};
 use gstreamer as gst;
 use gstreamer_app as gst_app;
-use gstreamer_video as gst_video;
-use log::{debug, info};
+use log::{debug, info, warn};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use gst::prelude::*;
+use crate::Core;
 use crate::texture::TextureManager;
+use super::frame::{FrameUploader, frame_caps};
 use wgpu;
 
+/// Where webcam frames come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebcamSource {
+    /// Platform camera by index (`/dev/videoN` on Linux), `None` for the default one
+    Device(Option<u32>),
+    /// Camera by the display name `WebcamTextureManager::enumerate_devices` reports
+    Named(String),
+    /// `videotestsrc` with the given pattern (`smpte`, `ball`, `snow`, ...), for machines without a camera
+    TestPattern(String),
+    /// A video file played in a loop as if it were a live camera
+    File(PathBuf),
+}
+
+impl WebcamSource {
+    /// Reads `CUNEUS_WEBCAM`: `test` or `test:<pattern>` for a test pattern, otherwise a video
+    /// file path to use as a virtual camera
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("CUNEUS_WEBCAM").ok()?;
+        let value = value.trim_matches('"');
+        if value.is_empty() {
+            return None;
+        }
+        if value == "test" {
+            return Some(Self::TestPattern("smpte".to_string()));
+        }
+        if let Some(pattern) = value.strip_prefix("test:") {
+            return Some(Self::TestPattern(pattern.to_string()));
+        }
+        Some(Self::File(PathBuf::from(value)))
+    }
+}
+
+/// Pixels trimmed off each edge of the captured frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WebcamCrop {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebcamConfig {
+    pub source: WebcamSource,
+    /// Requested capture size; `None` takes whatever the camera negotiates
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Requested frame rate as a fraction, e.g. `(30, 1)`
+    pub framerate: Option<(i32, i32)>,
+    /// Camera pixel format as listed in `WebcamMode::format` (`YUY2`, `NV12`, `MJPG`, ...)
+    pub format: Option<String>,
+    /// Flip the feed horizontally, like a mirror
+    pub mirror: bool,
+    pub crop: WebcamCrop,
+}
+
+impl Default for WebcamConfig {
+    fn default() -> Self {
+        Self {
+            source: WebcamSource::from_env().unwrap_or(WebcamSource::Device(None)),
+            width: Some(1280),
+            height: Some(720),
+            framerate: Some((30, 1)),
+            format: None,
+            mirror: false,
+            crop: WebcamCrop::default(),
+        }
+    }
+}
+
+/// One capture mode a camera supports
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebcamMode {
+    /// Raw format name (`YUY2`, `NV12`, ...) or `MJPG` for motion JPEG
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    /// Supported frame rates as fractions; for a continuous range, its minimum and maximum
+    pub framerates: Vec<(i32, i32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebcamDevice {
+    /// Name to pass as `WebcamSource::Named`
+    pub name: String,
+    /// Device node, where the platform exposes one (`/dev/video0`)
+    pub path: Option<String>,
+    pub modes: Vec<WebcamMode>,
+}
+
 /// Manages a webcam texture that can be updated frame by frame. My approach is actually same for src/gst/video.rs
 pub struct WebcamTextureManager {
-    /// The underlying TextureManager that handles the WGPU resources
-    texture_manager: TextureManager,
+    /// Uploads captured frames into the texture shaders sample
+    frames: FrameUploader,
     /// The GStreamer pipeline for webcam capture
     pipeline: gst::Pipeline,
     /// The AppSink element that receives decoded frames
@@ -24,85 +116,128 @@ pub struct WebcamTextureManager {
     /// Last frame update time
     last_update: Instant,
     /// Frame buffer for the most recently captured frame
-    current_frame: Arc<Mutex<Option<image::RgbaImage>>>,
-    /// Whether the webcam texture has been initialized
-    texture_initialized: bool,
+    current_frame: Arc<Mutex<Option<gst::Sample>>>,
     /// Frame counter for debugging
     frame_count: usize,
     /// Webcam device name/index
     device_name: String,
+    /// Source, capture mode and mirror/crop settings
+    config: WebcamConfig,
 }
 
 impl WebcamTextureManager {
     pub fn new(
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
+        core: &Core,
         bind_group_layout: &wgpu::BindGroupLayout,
         device_index: Option<u32>,
     ) -> Result<Self> {
-        // Create a default 1x1 texture initially, this will be replaced with first webcam frame
-        let default_image = image::RgbaImage::new(1, 1);
-        let texture_manager = TextureManager::new(device, queue, &default_image, bind_group_layout);
-        
-        let device_name = device_index
-            .map(|i| format!("/dev/video{}", i))
-            .unwrap_or_else(|| "0".to_string());
-            
-        info!("Creating webcam capture from device: {}", device_name);
-        
+        let config = WebcamConfig {
+            source: WebcamSource::Device(device_index),
+            ..WebcamConfig::default()
+        };
+        Self::with_config(core, bind_group_layout, config)
+    }
+
+    pub fn with_config(
+        core: &Core,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        config: WebcamConfig,
+    ) -> Result<Self> {
+        // Starts as a 1x1 texture, this will be replaced with first webcam frame
+        let frames = FrameUploader::new(core, bind_group_layout);
+
+        let make = |factory: &str, name: &str| {
+            gst::ElementFactory::make(factory)
+                .name(name)
+                .build()
+                .map_err(|_| anyhow!("Failed to create {} element", factory))
+        };
+
+        // Requested mode. Cameras must produce it directly; test and file sources are scaled to it.
+        let is_camera = matches!(config.source, WebcamSource::Device(_) | WebcamSource::Named(_));
+        let is_jpeg = is_camera && config.format.as_deref().is_some_and(|f| f.eq_ignore_ascii_case("MJPG") || f.eq_ignore_ascii_case("JPEG"));
+        let mut caps = gst::Caps::builder(if is_jpeg { "image/jpeg" } else { "video/x-raw" });
+        if let Some(format) = config.format.as_deref().filter(|_| !is_jpeg) {
+            caps = caps.field("format", format);
+        }
+        if let Some(width) = config.width {
+            caps = caps.field("width", width as i32);
+        }
+        if let Some(height) = config.height {
+            caps = caps.field("height", height as i32);
+        }
+        if let Some((numer, denom)) = config.framerate {
+            caps = caps.field("framerate", gst::Fraction::new(numer, denom));
+        }
+        let caps_filter = make("capsfilter", "caps")?;
+        caps_filter.set_property("caps", &caps.build());
+
         let pipeline = gst::Pipeline::new();
-        //MAC  :   https://gstreamer.freedesktop.org/documentation/applemedia/avfvideosrc.html?gi-language=c#avfvideosrc-page
-        //Linux:   https://gstreamer.freedesktop.org/documentation/video4linux2/v4l2src.html?gi-language=c#v4l2src-page
-        //Win  :   https://gstreamer.freedesktop.org/documentation/winks/index.html?gi-language=c#ksvideosrc-page
-        // Will be traditional pipeline.
-        // Create source element for webcam
-        #[cfg(target_os = "linux")]
-        let source = gst::ElementFactory::make("v4l2src")
-            .name("webcam_source")
-            .property("device", &device_name)
-            .build()
-            .map_err(|_| anyhow!("Failed to create v4l2src element"))?;
-            
-        #[cfg(target_os = "macos")]
-        let source = gst::ElementFactory::make("avfvideosrc")
-            .name("webcam_source")
-            .property("device-index", device_index.unwrap_or(0) as i32)
-            .build()
-            .map_err(|_| anyhow!("Failed to create avfvideosrc element"))?;
-            
-        #[cfg(target_os = "windows")]
-        let source = gst::ElementFactory::make("ksvideosrc")
-            .name("webcam_source")
-            .property("device-index", device_index.unwrap_or(0) as i32)
-            .build()
-            .map_err(|_| anyhow!("Failed to create ksvideosrc element"))?;
-        
-        // Create caps filter to set resolution and framerate
-        let caps_filter = gst::ElementFactory::make("capsfilter")
-            .name("caps")
-            .build()
-            .map_err(|_| anyhow!("Failed to create capsfilter element"))?;
-            
-        // Set preferred webcam format: EXPERIMENTAL
-        let caps = gst::Caps::builder("video/x-raw")
-            .field("width", 1280i32)
-            .field("height", 720i32)
-            .field("framerate", gst::Fraction::new(30, 1))
-            .build();
-        caps_filter.set_property("caps", &caps);
-        
-        // videorate element to stabilize frame timing
-        let videorate = gst::ElementFactory::make("videorate")
-            .name("rate")
-            .build()
-            .map_err(|_| anyhow!("Failed to create videorate element"))?;
-            
+        // Elements from the source (or the decoder's output, for files) up to the appsink
+        let mut chain: Vec<gst::Element> = Vec::new();
+        let device_name = match &config.source {
+            WebcamSource::Device(device_index) => {
+                let device_name = device_index
+                    .map(|i| format!("/dev/video{}", i))
+                    .unwrap_or_else(|| "0".to_string());
+                info!("Creating webcam capture from device: {}", device_name);
+                chain.push(Self::platform_source(&device_name, *device_index)?);
+                device_name
+            }
+            WebcamSource::Named(name) => {
+                info!("Creating webcam capture from device: {}", name);
+                let source = Self::monitor_devices()
+                    .into_iter()
+                    .find(|d| d.display_name().as_str() == name)
+                    .ok_or_else(|| anyhow!("No camera named {}", name))?
+                    .create_element(Some("webcam_source"))
+                    .map_err(|_| anyhow!("Failed to create source for camera {}", name))?;
+                chain.push(source);
+                name.clone()
+            }
+            WebcamSource::TestPattern(pattern) => {
+                info!("Creating virtual webcam from test pattern: {}", pattern);
+                let source = make("videotestsrc", "webcam_source")?;
+                source.set_property("is-live", true);
+                let known = source.find_property("pattern")
+                    .and_then(|pspec| gst::glib::EnumClass::with_type(pspec.value_type()))
+                    .is_some_and(|class| class.value_by_nick(pattern).is_some() || class.value_by_name(pattern).is_some());
+                if !known {
+                    return Err(anyhow!("Unknown videotestsrc pattern {}", pattern));
+                }
+                source.set_property_from_str("pattern", pattern);
+                chain.push(source);
+                format!("videotestsrc ({})", pattern)
+            }
+            WebcamSource::File(path) => {
+                let path_str = path.to_str().ok_or_else(|| anyhow!("Invalid video path"))?.to_string();
+                info!("Creating virtual webcam from file: {}", path_str);
+                chain.push(make("videoconvert", "file_convert")?);
+                chain.push(make("videoscale", "file_scale")?);
+                path_str
+            }
+        };
+        // videorate element to stabilize frame timing. Files and test patterns run at whatever
+        // rate they have, so it goes ahead of the caps filter to produce the requested one.
+        if !is_camera {
+            chain.push(make("videorate", "rate")?);
+            chain.push(caps_filter);
+        } else {
+            chain.push(caps_filter);
+            if is_jpeg {
+                chain.push(make("jpegdec", "jpeg_decode")?);
+            }
+            chain.push(make("videorate", "rate")?);
+        }
         // Convert to proper format
-        let videoconvert = gst::ElementFactory::make("videoconvert")
-            .name("convert")
-            .build()
-            .map_err(|_| anyhow!("Failed to create videoconvert element"))?;
-            
+        chain.push(make("videoconvert", "convert")?);
+        let crop = make("videocrop", "crop")?;
+        Self::apply_crop(&crop, config.crop);
+        chain.push(crop);
+        let flip = make("videoflip", "flip")?;
+        flip.set_property_from_str("video-direction", if config.mirror { "horiz" } else { "identity" });
+        chain.push(flip);
+
         // Output sink for video
         let appsink = gst::ElementFactory::make("appsink")
             .name("sink")
@@ -113,25 +248,61 @@ impl WebcamTextureManager {
             .map_err(|_| anyhow!("Failed to cast to AppSink"))?;
             
         // Configure appsink
-        appsink.set_caps(Some(&gst::Caps::builder("video/x-raw")
-            .field("format", gst_video::VideoFormat::Rgba.to_str())
-            .build()));
+        // NV12/I420 are uploaded as planes and converted on the GPU
+        appsink.set_caps(Some(&frame_caps()));
         appsink.set_max_buffers(2);
         appsink.set_drop(true);
-        appsink.set_sync(false);
-        
-        pipeline.add_many(&[
-            &source, 
-            &caps_filter,
-            &videorate, 
-            &videoconvert, 
-            &appsink.upcast_ref()
-        ])
-        .map_err(|_| anyhow!("Failed to add webcam elements to pipeline"))?;
+        // Live sources pace themselves; a file has to be held to its own timing
+        appsink.set_sync(matches!(config.source, WebcamSource::File(_)));
+        chain.push(appsink.clone().upcast());
+
+        pipeline.add_many(&chain)
+            .map_err(|_| anyhow!("Failed to add webcam elements to pipeline"))?;
             
         // Link elements
-        gst::Element::link_many(&[&source, &caps_filter, &videorate, &videoconvert, &appsink.upcast_ref()])
+        gst::Element::link_many(&chain)
             .map_err(|_| anyhow!("Failed to link webcam elements"))?;
+
+        if let WebcamSource::File(_) = &config.source {
+            let filesrc = make("filesrc", "webcam_source")?;
+            filesrc.set_property("location", &device_name);
+            let decodebin = make("decodebin", "decoder")?;
+            pipeline.add_many(&[&filesrc, &decodebin])
+                .map_err(|_| anyhow!("Failed to add file elements to pipeline"))?;
+            filesrc.link(&decodebin)
+                .map_err(|_| anyhow!("Failed to link filesrc to decodebin"))?;
+
+            let entry_weak = chain[0].downgrade();
+            let pipeline_weak = pipeline.downgrade();
+            decodebin.connect_pad_added(move |_, pad| {
+                let (Some(entry), Some(pipeline)) = (entry_weak.upgrade(), pipeline_weak.upgrade()) else {
+                    return;
+                };
+                let is_video = pad.current_caps()
+                    .and_then(|caps| caps.structure(0).map(|s| s.name().starts_with("video/")))
+                    .unwrap_or(false);
+                let Some(sink_pad) = entry.static_pad("sink") else {
+                    return;
+                };
+                let link = if is_video && !sink_pad.is_linked() {
+                    pad.link(&sink_pad).map(|_| ())
+                } else {
+                    // Audio and extra streams are discarded, but must be linked to keep data flowing
+                    let Ok(fakesink) = gst::ElementFactory::make("fakesink").property("sync", false).build() else {
+                        return;
+                    };
+                    let _ = pipeline.add(&fakesink);
+                    let _ = fakesink.sync_state_with_parent();
+                    match fakesink.static_pad("sink") {
+                        Some(fake_pad) => pad.link(&fake_pad).map(|_| ()),
+                        None => Ok(()),
+                    }
+                };
+                if let Err(e) = link {
+                    warn!("Failed to link decoded stream: {:?}", e);
+                }
+            });
+        }
         
         // Create shared state
         let current_frame = Arc::new(Mutex::new(None));
@@ -147,52 +318,9 @@ impl WebcamTextureManager {
                         Err(_) => return Err(gst::FlowError::Eos),
                     };
                     
-                    let buffer = match sample.buffer() {
-                        Some(buffer) => buffer,
-                        _ => return Err(gst::FlowError::Error),
-                    };
-                    
-                    let caps = match sample.caps() {
-                        Some(caps) => caps,
-                        _ => return Err(gst::FlowError::Error),
-                    };
-                    
-                    let video_info = match gst_video::VideoInfo::from_caps(caps) {
-                        Ok(info) => info,
-                        Err(_) => return Err(gst::FlowError::Error),
-                    };
-                    
-                    let map = match buffer.map_readable() {
-                        Ok(map) => map,
-                        Err(_) => return Err(gst::FlowError::Error),
-                    };
-                    
-                    // Access the raw frame data
-                    let frame_data = map.as_slice();
-                    let width = video_info.width() as usize;
-                    let height = video_info.height() as usize;
-                    
-                    // Create an RgbaImage from the frame data
-                    let mut rgba_image = image::RgbaImage::new(width as u32, height as u32);
-                    
-                    // Stride might be larger than width * 4
-                    let stride = video_info.stride()[0] as usize;
-                    
-                    for y in 0..height {
-                        let src_start = y * stride;
-                        let src_end = src_start + width * 4;
-                        let dst_start = y * width * 4;
-                        let dst_end = dst_start + width * 4;
-                        
-                        let dst_buffer = rgba_image.as_mut();
-                        if src_end <= frame_data.len() && dst_end <= dst_buffer.len() {
-                            dst_buffer[dst_start..dst_end]
-                                .copy_from_slice(&frame_data[src_start..src_end]);
-                        }
-                    }
-                    
+                    // Keep the sample itself, it is uploaded straight from the capture buffer
                     if let Ok(mut frame_lock) = current_frame_clone.lock() {
-                        *frame_lock = Some(rgba_image);
+                        *frame_lock = Some(sample);
                     }
                     
                     Ok(gst::FlowSuccess::Ok)
@@ -202,21 +330,86 @@ impl WebcamTextureManager {
         
         // init the obj
         let webcam_texture = Self {
-            texture_manager,
+            frames,
             pipeline,
             appsink,
-            dimensions: (1280, 720),
+            dimensions: (config.width.unwrap_or(1280), config.height.unwrap_or(720)),
             is_active,
             last_update: Instant::now(),
             current_frame,
-            texture_initialized: false,
             frame_count: 0,
             device_name,
+            config,
         };
         
         info!("Webcam texture manager created successfully");
         Ok(webcam_texture)
     }
+
+    /// Capture element for this platform
+    fn platform_source(device_name: &str, device_index: Option<u32>) -> Result<gst::Element> {
+        //MAC  :   https://gstreamer.freedesktop.org/documentation/applemedia/avfvideosrc.html?gi-language=c#avfvideosrc-page
+        //Linux:   https://gstreamer.freedesktop.org/documentation/video4linux2/v4l2src.html?gi-language=c#v4l2src-page
+        //Win  :   https://gstreamer.freedesktop.org/documentation/winks/index.html?gi-language=c#ksvideosrc-page
+        #[cfg(target_os = "linux")]
+        let source = {
+            let _ = device_index;
+            gst::ElementFactory::make("v4l2src")
+                .name("webcam_source")
+                .property("device", device_name)
+                .build()
+                .map_err(|_| anyhow!("Failed to create v4l2src element"))?
+        };
+
+        #[cfg(target_os = "macos")]
+        let source = {
+            let _ = device_name;
+            gst::ElementFactory::make("avfvideosrc")
+                .name("webcam_source")
+                .property("device-index", device_index.unwrap_or(0) as i32)
+                .build()
+                .map_err(|_| anyhow!("Failed to create avfvideosrc element"))?
+        };
+
+        #[cfg(target_os = "windows")]
+        let source = {
+            let _ = device_name;
+            gst::ElementFactory::make("ksvideosrc")
+                .name("webcam_source")
+                .property("device-index", device_index.unwrap_or(0) as i32)
+                .build()
+                .map_err(|_| anyhow!("Failed to create ksvideosrc element"))?
+        };
+
+        Ok(source)
+    }
+
+    fn apply_crop(element: &gst::Element, crop: WebcamCrop) {
+        element.set_property("left", crop.left as i32);
+        element.set_property("right", crop.right as i32);
+        element.set_property("top", crop.top as i32);
+        element.set_property("bottom", crop.bottom as i32);
+    }
+
+    pub fn config(&self) -> &WebcamConfig {
+        &self.config
+    }
+
+    /// Flips the feed horizontally; takes effect on the next frame
+    pub fn set_mirror(&mut self, mirror: bool) {
+        if let Some(flip) = self.pipeline.by_name("flip") {
+            flip.set_property_from_str("video-direction", if mirror { "horiz" } else { "identity" });
+        }
+        self.config.mirror = mirror;
+    }
+
+    /// Changes the crop while running; the texture is recreated at the new size
+    pub fn set_crop(&mut self, crop: WebcamCrop) {
+        if let Some(element) = self.pipeline.by_name("crop") {
+            Self::apply_crop(&element, crop);
+        }
+        self.config.crop = crop;
+    }
     
     pub fn start(&mut self) -> Result<()> {
         info!("Starting webcam capture");
@@ -258,7 +451,7 @@ impl WebcamTextureManager {
     
     /// I need this for wgpu
     pub fn texture_manager(&self) -> &TextureManager {
-        &self.texture_manager
+        self.frames.texture_manager()
     }
     
     pub fn update_texture(
@@ -271,6 +464,21 @@ impl WebcamTextureManager {
         if !*self.is_active.lock().unwrap() {
             return Ok(false);
         }
+
+        if let Some(bus) = self.pipeline.bus() {
+            while let Some(message) = bus.pop_filtered(&[gst::MessageType::Eos, gst::MessageType::Error]) {
+                match message.view() {
+                    // A virtual camera never runs out
+                    gst::MessageView::Eos(_) => {
+                        let _ = self.pipeline.seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, gst::ClockTime::ZERO);
+                    }
+                    gst::MessageView::Error(err) => {
+                        warn!("Webcam error: {} ({})", err.error(), err.debug().unwrap_or_default());
+                    }
+                    _ => {}
+                }
+            }
+        }
         
         // Check if we have a NEW frame to process
         let frame_to_process = {
@@ -279,41 +487,19 @@ impl WebcamTextureManager {
         };
         
         // If we have a frame, update the texture
-        if let Some(frame) = frame_to_process {
+        if let Some(sample) = frame_to_process {
             self.frame_count += 1;
             
-            let width = frame.width();
-            let height = frame.height();
+            if let Err(e) = self.frames.upload(device, queue, bind_group_layout, &sample) {
+                warn!("Failed to upload webcam frame: {}", e);
+                return Ok(false);
+            }
+            self.dimensions = self.frames.dimensions();
             
             // Log less frequently to reduce spam
             if self.frame_count % 60 == 0 {
                 debug!("Processing webcam frame #{} (dimensions: {}x{})", 
-                     self.frame_count, width, height);
-            }
-            
-            // ALWAYS recreate the texture for the first frame or if dimensions don't match
-            let should_recreate = !self.texture_initialized || 
-                                 self.dimensions != (width, height) ||
-                                 self.dimensions.0 <= 1 || 
-                                 self.dimensions.1 <= 1 ||
-                                 self.frame_count <= 3;
-            
-            if should_recreate {
-                info!("Creating new webcam texture with dimensions: {}x{}", width, height);
-                
-                // Create a completely new texture with the frame's dimensions
-                let new_texture_manager = TextureManager::new(
-                    device, 
-                    queue, 
-                    &frame, 
-                    bind_group_layout
-                );
-                
-                self.texture_manager = new_texture_manager;
-                self.dimensions = (width, height);
-                self.texture_initialized = true;
-            } else {
-                self.texture_manager.update(queue, &frame);
+                     self.frame_count, self.dimensions.0, self.dimensions.1);
             }
             
             // Update the last update time
@@ -377,6 +563,70 @@ impl WebcamTextureManager {
         
         devices
     }
+
+    fn monitor_devices() -> Vec<gst::Device> {
+        let monitor = gst::DeviceMonitor::new();
+        monitor.add_filter(Some("Video/Source"), None);
+        if let Err(e) = monitor.start() {
+            warn!("Failed to start device monitor: {}", e);
+            return Vec::new();
+        }
+        let devices = monitor.devices().into_iter().collect();
+        monitor.stop();
+        devices
+    }
+
+    /// Cameras with every mode they report, for choosing `WebcamConfig` resolution, frame rate and format
+    pub fn enumerate_devices() -> Vec<WebcamDevice> {
+        Self::monitor_devices()
+            .iter()
+            .map(|device| {
+                let path = device.properties().and_then(|props| {
+                    ["device.path", "api.v4l2.path", "object.path"]
+                        .iter()
+                        .find_map(|key| props.get::<String>(*key).ok())
+                });
+                let modes = device.caps().map(|caps| Self::modes_from_caps(&caps)).unwrap_or_default();
+                WebcamDevice { name: device.display_name().to_string(), path, modes }
+            })
+            .collect()
+    }
+
+    fn modes_from_caps(caps: &gst::CapsRef) -> Vec<WebcamMode> {
+        let mut modes: Vec<WebcamMode> = Vec::new();
+        for structure in caps.iter() {
+            let format = match structure.name().as_str() {
+                "image/jpeg" => "MJPG".to_string(),
+                "video/x-raw" => match structure.get::<String>("format") {
+                    Ok(format) => format,
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            let (Ok(width), Ok(height)) = (structure.get::<i32>("width"), structure.get::<i32>("height")) else {
+                continue;
+            };
+            let framerates: Vec<(i32, i32)> = if let Ok(rate) = structure.get::<gst::Fraction>("framerate") {
+                vec![(rate.numer(), rate.denom())]
+            } else if let Ok(list) = structure.get::<gst::List>("framerate") {
+                list.iter()
+                    .filter_map(|value| value.get::<gst::Fraction>().ok())
+                    .map(|rate| (rate.numer(), rate.denom()))
+                    .collect()
+            } else if let Ok(range) = structure.get::<gst::FractionRange>("framerate") {
+                vec![(range.min().numer(), range.min().denom()), (range.max().numer(), range.max().denom())]
+            } else {
+                Vec::new()
+            };
+
+            let (width, height) = (width as u32, height as u32);
+            match modes.iter_mut().find(|m| m.format == format && m.width == width && m.height == height) {
+                Some(mode) => mode.framerates.extend(framerates.into_iter().filter(|r| !mode.framerates.contains(r))),
+                None => modes.push(WebcamMode { format, width, height, framerates }),
+            }
+        }
+        modes
+    }
 }
 
 impl Drop for WebcamTextureManager {
@@ -1,7 +1,7 @@
 use image::codecs::hdr::HdrDecoder;
-use image::{RgbaImage,ImageDecoder};
+use image::ImageDecoder;
 use std::io::Cursor;
-use crate::TextureManager;
+use crate::{Core, ShaderCache, TextureManager, UniformBinding, UniformProvider};
 
 #[derive(Clone, Debug, Copy)]
 pub struct HdriMetadata {
@@ -22,94 +22,389 @@ impl Default for HdriMetadata {
     }
 }
 
-pub fn load_hdri_texture(
-    device: &wgpu::Device,
-    queue: &wgpu::Queue,
-    data: &[u8],
-    layout: &wgpu::BindGroupLayout,
+/// Exposure and gamma for shaders that read the linear HDRI directly.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct HdriUniform {
+    pub exposure: f32,
+    pub gamma: f32,
+    pub _padding: [f32; 2],
+}
+
+impl Default for HdriUniform {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            gamma: 2.2,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+impl UniformProvider for HdriUniform {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+pub fn create_hdri_uniform_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("HDRI Uniform Layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+    })
+}
+
+const TONEMAP_SHADER: &str = r#"
+struct HdriParams {
     exposure: f32,
-) -> Result<(TextureManager, HdriMetadata), String> {
-    let format = detect_format(data)?;
-    let gamma = 2.2;
-    let hdri_image = match format {
-        HdriFormat::Hdr => hdr_to_rgba8(data, exposure, Some(gamma))?,
-        HdriFormat::Exr => exr_to_rgba8(data, exposure, Some(gamma))?,
-    };
-    let dimensions = hdri_image.dimensions();
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("HDRI Texture"),
-        size: wgpu::Extent3d {
-            width: dimensions.0,
-            height: dimensions.1,
+    gamma: f32,
+    _padding: vec2<f32>,
+};
+@group(0) @binding(0) var hdri: texture_2d<f32>;
+@group(1) @binding(0) var<uniform> params: HdriParams;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let c = textureLoad(hdri, vec2<i32>(pos.xy), 0);
+    let mapped = pow(max(c.rgb * params.exposure, vec3<f32>(0.0)), vec3<f32>(1.0 / params.gamma));
+    return vec4<f32>(min(mapped, vec3<f32>(1.0)), clamp(c.a, 0.0, 1.0));
+}
+"#;
+
+/// An HDRI uploaded as linear float data with its full dynamic range.
+///
+/// `view` holds the untouched radiance values (use it for image-based lighting).
+/// `display` is an `Rgba8UnormSrgb` copy with exposure and gamma applied on the GPU,
+/// for shaders that expect a regular texture; changing exposure only re-runs that pass.
+pub struct HdriTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub format: wgpu::TextureFormat,
+    pub metadata: HdriMetadata,
+    pub uniform: UniformBinding<HdriUniform>,
+    pub uniform_layout: wgpu::BindGroupLayout,
+    pub display: TextureManager,
+    display_target: wgpu::TextureView,
+    tonemap_pipeline: wgpu::RenderPipeline,
+    tonemap_bind_group: wgpu::BindGroup,
+}
+
+impl HdriTexture {
+    /// Decodes `.hdr`/`.exr` data into a float texture. `format` must be `Rgba16Float` or `Rgba32Float`;
+    /// note that `Rgba32Float` is only filterable with `Features::FLOAT32_FILTERABLE`.
+    pub fn new(
+        core: &Core,
+        data: &[u8],
+        layout: &wgpu::BindGroupLayout,
+        exposure: f32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, String> {
+        Self::build(&core.device, &core.queue, &core.shader_cache, data, layout, exposure, format)
+    }
+
+    fn build(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        shader_cache: &ShaderCache,
+        data: &[u8],
+        layout: &wgpu::BindGroupLayout,
+        exposure: f32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, String> {
+        let (width, height, pixels) = match detect_format(data)? {
+            HdriFormat::Hdr => decode_hdr(data)?,
+            HdriFormat::Exr => decode_exr(data)?,
+        };
+        let size = wgpu::Extent3d {
+            width,
+            height,
             depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8UnormSrgb,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        view_formats: &[],
-    });
-    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        mipmap_filter: wgpu::FilterMode::Linear,
-        ..Default::default()
-    });
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout,
-        entries: &[
-            wgpu::BindGroupEntry {
+        };
+
+        let (bytes, bytes_per_pixel): (Vec<u8>, u32) = match format {
+            wgpu::TextureFormat::Rgba16Float => {
+                let halfs: Vec<u16> = pixels.iter().map(|v| half::f16::from_f32(*v).to_bits()).collect();
+                (bytemuck::cast_slice(&halfs).to_vec(), 8)
+            }
+            wgpu::TextureFormat::Rgba32Float => (bytemuck::cast_slice(&pixels).to_vec(), 16),
+            other => return Err(format!("Unsupported HDRI texture format: {:?}", other)),
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("HDRI Float Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &bytes,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_pixel * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let filter = if format == wgpu::TextureFormat::Rgba32Float {
+            wgpu::FilterMode::Nearest
+        } else {
+            wgpu::FilterMode::Linear
+        };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("HDRI Float Sampler"),
+            // Equirectangular maps wrap horizontally
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: filter,
+            ..Default::default()
+        });
+
+        let metadata = HdriMetadata {
+            width,
+            height,
+            exposure,
+            gamma: 2.2,
+        };
+        let uniform_layout = create_hdri_uniform_layout(device);
+        let uniform = UniformBinding::new(
+            device,
+            "HDRI Uniform",
+            HdriUniform {
+                exposure,
+                gamma: metadata.gamma,
+                _padding: [0.0; 2],
+            },
+            &uniform_layout,
+            0,
+        );
+
+        // Display copy: written through a non-sRGB view so the stored bytes are the gamma-encoded values
+        let display_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("HDRI Display Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
+        });
+        let display_view = display_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let display_target = display_texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(wgpu::TextureFormat::Rgba8Unorm),
+            ..Default::default()
+        });
+        let display_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let display_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&display_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&display_sampler),
+                },
+            ],
+            label: Some("HDRI Texture Bind Group"),
+        });
+        let display = TextureManager {
+            texture: display_texture,
+            view: display_view,
+            sampler: display_sampler,
+            bind_group: display_bind_group,
+        };
+
+        let source_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("HDRI Tonemap Source Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                },
+                count: None,
+            }],
+        });
+        let tonemap_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("HDRI Tonemap Source"),
+            layout: &source_layout,
+            entries: &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+        let shader = shader_cache.create_shader_module(device, TONEMAP_SHADER, "HDRI Tonemap Shader");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("HDRI Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&source_layout, &uniform_layout],
+            push_constant_ranges: &[],
+        });
+        let tonemap_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("HDRI Tonemap Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
             },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::Sampler(&sampler),
-            },
-        ],
-        label: Some("HDRI Texture Bind Group"),
-    });
-    queue.write_texture(
-        wgpu::TexelCopyTextureInfo {
-            texture: &texture,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-            aspect: wgpu::TextureAspect::All,
-        },
-        &hdri_image,
-        wgpu::TexelCopyBufferLayout {
-            offset: 0,
-            bytes_per_row: Some(4 * dimensions.0),
-            rows_per_image: Some(dimensions.1),
-        },
-        wgpu::Extent3d {
-            width: dimensions.0,
-            height: dimensions.1,
-            depth_or_array_layers: 1,
-        },
-    );
-
-    let metadata = HdriMetadata {
-        width: dimensions.0,
-        height: dimensions.1, 
-        exposure,
-        gamma,
-    };
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
 
-    Ok((
-        TextureManager {
+        let hdri = Self {
             texture,
             view,
-            sampler, 
-            bind_group,
-        },
-        metadata
-    ))
+            sampler,
+            format,
+            metadata,
+            uniform,
+            uniform_layout,
+            display,
+            display_target,
+            tonemap_pipeline,
+            tonemap_bind_group,
+        };
+        hdri.render_display(device, queue);
+        Ok(hdri)
+    }
+
+    fn render_display(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("HDRI Tonemap Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("HDRI Tonemap Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.display_target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.tonemap_pipeline);
+            render_pass.set_bind_group(0, &self.tonemap_bind_group, &[]);
+            render_pass.set_bind_group(1, &self.uniform.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Updates the exposure/gamma uniform and refreshes the display copy. No CPU decoding involved.
+    pub fn set_exposure_gamma(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, exposure: f32, gamma: f32) {
+        self.metadata.exposure = exposure;
+        self.metadata.gamma = gamma;
+        self.uniform.data.exposure = exposure;
+        self.uniform.data.gamma = gamma;
+        self.uniform.update(queue);
+        self.render_display(device, queue);
+    }
+}
+
+/// Loads an HDRI and returns the exposure-mapped display texture (see `HdriTexture` for the linear data).
+pub fn load_hdri_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    data: &[u8],
+    layout: &wgpu::BindGroupLayout,
+    exposure: f32,
+) -> Result<(TextureManager, HdriMetadata), String> {
+    let hdri = HdriTexture::build(
+        device, queue, &ShaderCache::default(), data, layout, exposure, wgpu::TextureFormat::Rgba16Float,
+    )?;
+    Ok((hdri.display.clone(), hdri.metadata))
+}
+
+/// Re-decodes `data` and writes it into `texture_manager` with new exposure/gamma on the CPU.
+#[deprecated(note = "use `HdriTexture::set_exposure_gamma`, which only re-runs a GPU pass")]
+pub fn update_hdri_exposure(
+    _device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    data: &[u8],
+    _layout: &wgpu::BindGroupLayout,
+    texture_manager: &mut TextureManager,
+    new_exposure: f32,
+    gamma: Option<f32>,
+) -> Result<(), String> {
+    let (width, height, pixels) = match detect_format(data)? {
+        HdriFormat::Hdr => decode_hdr(data)?,
+        HdriFormat::Exr => decode_exr(data)?,
+    };
+    let gamma_correction = 1.0 / gamma.unwrap_or(2.2);
+    let bytes: Vec<u8> = pixels
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let encode = |v: f32| ((v * new_exposure).max(0.0).powf(gamma_correction).min(1.0) * 255.0) as u8;
+            [encode(p[0]), encode(p[1]), encode(p[2]), (p[3].clamp(0.0, 1.0) * 255.0) as u8]
+        })
+        .collect();
+    let rgba_image = image::RgbaImage::from_raw(width, height, bytes)
+        .ok_or_else(|| "HDRI pixel data does not match its dimensions".to_string())?;
+    texture_manager.update(queue, &rgba_image);
+    Ok(())
 }
 
 enum HdriFormat {
@@ -130,71 +425,25 @@ fn detect_format(data: &[u8]) -> Result<HdriFormat, String> {
     Ok(HdriFormat::Hdr)
 }
 
-fn hdr_to_rgba8(hdr_data: &[u8], exposure: f32, gamma: Option<f32>) -> Result<RgbaImage, String> {
+/// Returns width, height and linear RGBA f32 pixels.
+fn decode_hdr(hdr_data: &[u8]) -> Result<(u32, u32, Vec<f32>), String> {
     let cursor = Cursor::new(hdr_data);
     let decoder = HdrDecoder::new(cursor).map_err(|e| e.to_string())?;
     let metadata = decoder.metadata();
     let dynamic_img = image::DynamicImage::from_decoder(decoder)
         .map_err(|e| format!("Failed to decode HDR: {}", e))?;
-    let mut rgba8_image = RgbaImage::new(metadata.width, metadata.height);
-    let rgb8_image = dynamic_img.to_rgb8();
-    let gamma_value = gamma.unwrap_or(2.2);
-    let gamma_correction = 1.0 / gamma_value;
-    for (x, y, pixel) in rgb8_image.enumerate_pixels() {
-        let r_linear = (pixel[0] as f32 / 255.0) * exposure;
-        let g_linear = (pixel[1] as f32 / 255.0) * exposure;
-        let b_linear = (pixel[2] as f32 / 255.0) * exposure;
-        let r = ((r_linear.powf(gamma_correction)).min(1.0) * 255.0) as u8;
-        let g = ((g_linear.powf(gamma_correction)).min(1.0) * 255.0) as u8;
-        let b = ((b_linear.powf(gamma_correction)).min(1.0) * 255.0) as u8;
-        rgba8_image.put_pixel(x, y, image::Rgba([r, g, b, 255]));
-    }
-    Ok(rgba8_image)
+    let rgba_float = dynamic_img.to_rgba32f();
+    Ok((metadata.width, metadata.height, rgba_float.into_raw()))
 }
 
-fn exr_to_rgba8(exr_data: &[u8], exposure: f32, gamma: Option<f32>) -> Result<RgbaImage, String> {
+fn decode_exr(exr_data: &[u8]) -> Result<(u32, u32, Vec<f32>), String> {
     use image::codecs::openexr::OpenExrDecoder;
-    
+
     let cursor = Cursor::new(exr_data);
     let decoder = OpenExrDecoder::new(cursor).map_err(|e| format!("Failed to decode EXR: {}", e))?;
     let (width, height) = decoder.dimensions();
     let dynamic_img = image::DynamicImage::from_decoder(decoder)
         .map_err(|e| format!("Failed to create DynamicImage from EXR: {}", e))?;
     let rgba_float = dynamic_img.to_rgba32f();
-    let mut rgba8_image = RgbaImage::new(width, height);
-    let gamma_value = gamma.unwrap_or(2.2);
-    let gamma_correction = 1.0 / gamma_value;
-    for (x, y, pixel) in rgba_float.enumerate_pixels() {
-        let r_linear = pixel[0] * exposure;
-        let g_linear = pixel[1] * exposure;
-        let b_linear = pixel[2] * exposure;
-        let a = pixel[3];
-        
-        let r = ((r_linear.powf(gamma_correction)).min(1.0) * 255.0) as u8;
-        let g = ((g_linear.powf(gamma_correction)).min(1.0) * 255.0) as u8;
-        let b = ((b_linear.powf(gamma_correction)).min(1.0) * 255.0) as u8;
-        let a = (a.min(1.0) * 255.0) as u8;
-        
-        rgba8_image.put_pixel(x, y, image::Rgba([r, g, b, a]));
-    }
-    
-    Ok(rgba8_image)
+    Ok((width, height, rgba_float.into_raw()))
 }
-
-pub fn update_hdri_exposure(
-    _device: &wgpu::Device,
-    queue: &wgpu::Queue,
-    data: &[u8],
-    _layout: &wgpu::BindGroupLayout,
-    texture_manager: &mut TextureManager,
-    new_exposure: f32,
-    gamma: Option<f32>,
-) -> Result<(), String> {
-    let format = detect_format(data)?;
-    let rgba_image = match format {
-        HdriFormat::Hdr => hdr_to_rgba8(data, new_exposure, gamma)?,
-        HdriFormat::Exr => exr_to_rgba8(data, new_exposure, gamma)?,
-    };
-    texture_manager.update(queue, &rgba_image);
-    Ok(())
-}
\ No newline at end of file
@@ -2,15 +2,51 @@ use notify::{Watcher, RecursiveMode, Event, EventKind};
 use std::sync::Arc;
 use std::path::{PathBuf, Path};
 use std::fs;
-use std::sync::mpsc::{channel, Receiver};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::{Duration, Instant};
-use std::collections::HashMap;
+use std::collections::BTreeSet;
+use crate::ShaderCache;
+use log::{error, warn};
 
 pub enum ShaderType {
     RenderPair, // Vertex + Fragment
     Compute     // Compute
 }
 
+/// Controls what `ShaderHotReload` watches and how eagerly it reacts.
+#[derive(Debug, Clone)]
+pub struct HotReloadConfig {
+    /// Extra glob patterns (relative to each shader's directory) that should trigger a reload,
+    /// e.g. `"*.wgsl"` or `"includes/**/*.wgsl"`. The shader files themselves are always watched.
+    pub watch_patterns: Vec<String>,
+    /// Quiet period after the last file event before the shaders are re-read.
+    pub debounce: Duration,
+    /// Use a polling watcher instead of native OS notifications (network filesystems, containers).
+    pub use_polling: bool,
+    pub poll_interval: Duration,
+}
+
+impl Default for HotReloadConfig {
+    fn default() -> Self {
+        Self {
+            watch_patterns: Vec::new(),
+            debounce: Duration::from_millis(100),
+            use_polling: false,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl HotReloadConfig {
+    // Only descend into subdirectories when a pattern actually asks for it.
+    fn needs_recursive(&self) -> bool {
+        self.watch_patterns.iter().any(|p| p.contains('/') || p.contains("**"))
+    }
+}
+
+pub type ReloadCallback = Box<dyn FnMut(&[PathBuf]) + Send>;
+type WatcherParts = (Box<dyn Watcher + Send>, Receiver<notify::Event>, Vec<PathBuf>);
+
 pub struct ShaderHotReload {
     pub vs_module: wgpu::ShaderModule,
     pub fs_module: wgpu::ShaderModule,
@@ -21,62 +57,47 @@ pub struct ShaderHotReload {
     last_fs_content: String,
     last_compute_content: Option<String>,
     #[allow(dead_code)]
-    watcher: notify::RecommendedWatcher,
+    watcher: Box<dyn Watcher + Send>,
     rx: Receiver<notify::Event>,
-    _watcher_tx: std::sync::mpsc::Sender<notify::Event>,
-    last_update_times: HashMap<PathBuf, Instant>,
-    debounce_duration: Duration,
+    config: HotReloadConfig,
+    watched_files: Vec<PathBuf>,
+    watched_dirs: Vec<PathBuf>,
+    pending_changes: BTreeSet<PathBuf>,
+    last_event_time: Option<Instant>,
+    force_reload: bool,
+    subscribers: Vec<Sender<Vec<PathBuf>>>,
+    callback: Option<ReloadCallback>,
     shader_type: ShaderType,
     entry_point: Option<String>,
+    shader_cache: ShaderCache,
 }
 
 impl ShaderHotReload {
+    /// `shader_cache` is usually `core.shader_cache.clone()`, so reloads hit the same cache.
     pub fn new(
         device: Arc<wgpu::Device>,
+        shader_cache: ShaderCache,
         shader_paths: Vec<PathBuf>,
         vs_module: wgpu::ShaderModule,
         fs_module: wgpu::ShaderModule,
     ) -> notify::Result<Self> {
-        let (tx, rx) = channel();
-        let watcher_tx = tx.clone();
-
-        let mut watcher = notify::recommended_watcher(move |res: Result<Event, _>| {
-            if let Ok(event) = res {
-                match event.kind {
-                    EventKind::Modify(_) |
-                    EventKind::Create(_) |
-                    EventKind::Remove(_)
-                    => {
-                        tx.send(event).unwrap_or_default();
-                    },
-                    _ => {}
-                }
-            }
-        })?;
+        Self::new_with_config(device, shader_cache, shader_paths, vs_module, fs_module, HotReloadConfig::default())
+    }
 
+    pub fn new_with_config(
+        device: Arc<wgpu::Device>,
+        shader_cache: ShaderCache,
+        shader_paths: Vec<PathBuf>,
+        vs_module: wgpu::ShaderModule,
+        fs_module: wgpu::ShaderModule,
+        config: HotReloadConfig,
+    ) -> notify::Result<Self> {
         //normalize for Windows
         let normalized_paths: Vec<PathBuf> = shader_paths.iter()
             .map(|path| Self::normalize_path(path))
             .collect();
 
-        for path in &normalized_paths {
-            if let Some(parent) = path.parent() {
-                if !parent.exists() {
-                    fs::create_dir_all(parent).unwrap_or_else(|e| {
-                        println!("Failed to create shader directory: {}", e);
-                    });
-                }
-                
-                if let Err(e) = watcher.watch(parent, RecursiveMode::Recursive) {
-                    println!("Warning: Could not watch shader directory {}: {}", parent.display(), e);
-                    if cfg!(windows) {
-                        if let Err(e) = watcher.watch(parent, RecursiveMode::NonRecursive) {
-                            println!("Fallback watch failed: {}", e);
-                        }
-                    }
-                }
-            }
-        }
+        let (watcher, rx, watched_dirs) = Self::create_watcher(&normalized_paths, &config)?;
 
         let last_vs_content = fs::read_to_string(&normalized_paths[0]).unwrap_or_default();
         let last_fs_content = fs::read_to_string(&normalized_paths[1]).unwrap_or_default();
@@ -86,61 +107,48 @@ impl ShaderHotReload {
             fs_module,
             compute_module: None,
             device,
+            watched_files: normalized_paths.iter().map(|p| Self::absolute_path(p)).collect(),
             shader_paths: normalized_paths,
             last_vs_content,
             last_fs_content,
             last_compute_content: None,
             watcher,
             rx,
-            _watcher_tx: watcher_tx,
-            last_update_times: HashMap::new(),
-            debounce_duration: Duration::from_millis(100),
+            config,
+            watched_dirs,
+            pending_changes: BTreeSet::new(),
+            last_event_time: None,
+            force_reload: false,
+            subscribers: Vec::new(),
+            callback: None,
             shader_type: ShaderType::RenderPair,
             entry_point: None,
+            shader_cache,
         })
     }
+
     pub fn new_compute(
         device: Arc<wgpu::Device>,
+        shader_cache: ShaderCache,
         shader_path: PathBuf,
         compute_module: wgpu::ShaderModule,
         entry_point: &str,
     ) -> notify::Result<Self> {
-        let (tx, rx) = channel();
-        let watcher_tx = tx.clone();
-
-        let mut watcher = notify::recommended_watcher(move |res: Result<Event, _>| {
-            if let Ok(event) = res {
-                match event.kind {
-                    EventKind::Modify(_) |
-                    EventKind::Create(_) |
-                    EventKind::Remove(_)
-                    => {
-                        tx.send(event).unwrap_or_default();
-                    },
-                    _ => {}
-                }
-            }
-        })?;
+        Self::new_compute_with_config(device, shader_cache, shader_path, compute_module, entry_point, HotReloadConfig::default())
+    }
 
+    pub fn new_compute_with_config(
+        device: Arc<wgpu::Device>,
+        shader_cache: ShaderCache,
+        shader_path: PathBuf,
+        compute_module: wgpu::ShaderModule,
+        entry_point: &str,
+        config: HotReloadConfig,
+    ) -> notify::Result<Self> {
         let normalized_path = Self::normalize_path(&shader_path);
         let shader_paths = vec![normalized_path.clone()];
 
-        if let Some(parent) = normalized_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent).unwrap_or_else(|e| {
-                    println!("Failed to create shader directory: {}", e);
-                });
-            }
-            
-            if let Err(e) = watcher.watch(parent, RecursiveMode::Recursive) {
-                println!("Warning: Could not watch shader directory {}: {}", parent.display(), e);
-                if cfg!(windows) {
-                    if let Err(e) = watcher.watch(parent, RecursiveMode::NonRecursive) {
-                        println!("Fallback watch failed: {}", e);
-                    }
-                }
-            }
-        }
+        let (watcher, rx, watched_dirs) = Self::create_watcher(&shader_paths, &config)?;
 
         let last_compute_content = fs::read_to_string(&normalized_path).unwrap_or_default();
         let dummy_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -153,20 +161,93 @@ impl ShaderHotReload {
             fs_module: dummy_shader,
             compute_module: Some(compute_module),
             device,
+            watched_files: vec![Self::absolute_path(&normalized_path)],
             shader_paths,
             last_vs_content: String::new(),
             last_fs_content: String::new(),
             last_compute_content: Some(last_compute_content),
             watcher,
             rx,
-            _watcher_tx: watcher_tx,
-            last_update_times: HashMap::new(),
-            debounce_duration: Duration::from_millis(100),
+            config,
+            watched_dirs,
+            pending_changes: BTreeSet::new(),
+            last_event_time: None,
+            force_reload: false,
+            subscribers: Vec::new(),
+            callback: None,
             shader_type: ShaderType::Compute,
             entry_point: Some(entry_point.to_string()),
+            shader_cache,
         })
     }
 
+    fn create_watcher(shader_paths: &[PathBuf], config: &HotReloadConfig) -> notify::Result<WatcherParts> {
+        let (tx, rx) = channel();
+        let handler = move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                match event.kind {
+                    EventKind::Modify(_) |
+                    EventKind::Create(_) |
+                    EventKind::Remove(_)
+                    => {
+                        tx.send(event).unwrap_or_default();
+                    },
+                    _ => {}
+                }
+            }
+        };
+
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for path in shader_paths {
+            if let Some(parent) = path.parent() {
+                let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+                if !parent.exists() {
+                    fs::create_dir_all(parent).unwrap_or_else(|e| {
+                        warn!("Failed to create shader directory: {}", e);
+                    });
+                }
+                if !dirs.iter().any(|d| d == parent) {
+                    dirs.push(parent.to_path_buf());
+                }
+            }
+        }
+
+        let mode = if config.needs_recursive() {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        let poll_config = notify::Config::default().with_poll_interval(config.poll_interval);
+
+        let mut watcher: Box<dyn Watcher + Send> = if config.use_polling {
+            Box::new(notify::PollWatcher::new(handler, poll_config)?)
+        } else {
+            let native = notify::recommended_watcher(handler.clone());
+            match native {
+                Ok(mut native) => {
+                    // Native watches can fail on network mounts; fall back to polling in that case.
+                    if dirs.iter().all(|dir| native.watch(dir, mode).is_ok()) {
+                        return Ok((Box::new(native), rx, Self::absolute_dirs(&dirs)));
+                    }
+                    warn!("Native file watching unavailable, falling back to polling");
+                    Box::new(notify::PollWatcher::new(handler, poll_config)?)
+                }
+                Err(e) => {
+                    warn!("Could not create file watcher ({}), falling back to polling", e);
+                    Box::new(notify::PollWatcher::new(handler, poll_config)?)
+                }
+            }
+        };
+
+        for dir in &dirs {
+            if let Err(e) = watcher.watch(dir, mode) {
+                warn!("Could not watch shader directory {}: {}", dir.display(), e);
+            }
+        }
+
+        Ok((watcher, rx, Self::absolute_dirs(&dirs)))
+    }
+
     fn normalize_path(path: &Path) -> PathBuf {
         if cfg!(windows) {
             path.components()
@@ -178,43 +259,135 @@ impl ShaderHotReload {
         }
     }
 
-    pub fn check_and_reload(&mut self) -> Option<(&wgpu::ShaderModule, &wgpu::ShaderModule)> {
-        match self.shader_type {
-            ShaderType::RenderPair => self.reload_render_shaders(),
-            ShaderType::Compute => {
-                self.reload_compute_shader();
-                None
+    // Event paths may be relative or absolute depending on the backend, so compare absolute forms.
+    fn absolute_path(path: &Path) -> PathBuf {
+        if let Ok(canonical) = path.canonicalize() {
+            return canonical;
+        }
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            if let Ok(parent) = parent.canonicalize() {
+                return parent.join(name);
             }
         }
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
     }
 
-    fn reload_render_shaders(&mut self) -> Option<(&wgpu::ShaderModule, &wgpu::ShaderModule)> {
-        let mut should_reload = false;
-        
-        // Process all pending events
+    fn absolute_dirs(dirs: &[PathBuf]) -> Vec<PathBuf> {
+        dirs.iter()
+            .map(|d| d.canonicalize().unwrap_or_else(|_| d.clone()))
+            .collect()
+    }
+
+    fn is_relevant(&self, path: &Path) -> bool {
+        let path = Self::absolute_path(path);
+        if self.watched_files.contains(&path) {
+            return true;
+        }
+        self.watched_dirs.iter().any(|dir| {
+            path.strip_prefix(dir)
+                .map(|relative| {
+                    let relative = relative.to_string_lossy().replace('\\', "/");
+                    self.config.watch_patterns.iter().any(|pattern| glob_match(pattern, &relative))
+                })
+                .unwrap_or(false)
+        })
+    }
+
+    /// Drains watcher events and returns the changed paths once the debounce window has elapsed.
+    /// The paths stay pending until `finish_reload`, so a failed rebuild still sees them next time.
+    fn poll_changes(&mut self) -> Option<Vec<PathBuf>> {
         while let Ok(event) = self.rx.try_recv() {
             for path in event.paths {
-                let now = Instant::now();
-                
-                if let Some(last_update) = self.last_update_times.get(&path) {
-                    if now.duration_since(*last_update) < self.debounce_duration {
-                        continue;
-                    }
+                if self.is_relevant(&path) {
+                    self.pending_changes.insert(path);
+                    self.last_event_time = Some(Instant::now());
                 }
-                
-                self.last_update_times.insert(path.clone(), now);
-                should_reload = true;
             }
         }
 
-        if !should_reload {
+        if self.force_reload {
+            self.force_reload = false;
+            self.last_event_time = None;
+            let mut changed: Vec<PathBuf> = self.pending_changes.iter().cloned().collect();
+            if changed.is_empty() {
+                changed = self.shader_paths.clone();
+            }
+            return Some(changed);
+        }
+
+        let last_event = self.last_event_time?;
+        if last_event.elapsed() < self.config.debounce {
             return None;
         }
+        self.last_event_time = None;
+        Some(self.pending_changes.iter().cloned().collect())
+    }
+
+    /// Clears the pending paths once they have been handled.
+    fn finish_reload(&mut self) {
+        self.pending_changes.clear();
+    }
+
+    /// Tries again after another debounce window, e.g. while an editor is still replacing the file.
+    fn retry_later(&mut self, forced: bool) {
+        self.force_reload = forced;
+        self.last_event_time = Some(Instant::now());
+    }
+
+    fn notify_reload(&mut self, changed: &[PathBuf]) {
+        self.subscribers.retain(|tx| tx.send(changed.to_vec()).is_ok());
+        if let Some(callback) = &mut self.callback {
+            callback(changed);
+        }
+    }
+
+    /// Forces the shaders to be re-read and rebuilt on the next check, even if their content is unchanged.
+    pub fn reload_now(&mut self) {
+        self.force_reload = true;
+    }
+
+    /// Returns a channel that receives the changed paths after every successful reload.
+    pub fn subscribe(&mut self) -> Receiver<Vec<PathBuf>> {
+        let (tx, rx) = channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Registers a callback invoked with the changed paths after every successful reload.
+    pub fn set_reload_callback(&mut self, callback: impl FnMut(&[PathBuf]) + Send + 'static) {
+        self.callback = Some(Box::new(callback));
+    }
+
+    pub fn config(&self) -> &HotReloadConfig {
+        &self.config
+    }
+
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.config.debounce = debounce;
+    }
+
+    pub fn check_and_reload(&mut self) -> Option<(&wgpu::ShaderModule, &wgpu::ShaderModule)> {
+        match self.shader_type {
+            ShaderType::RenderPair => self.reload_render_shaders(),
+            ShaderType::Compute => {
+                self.reload_compute_shader();
+                None
+            }
+        }
+    }
+
+    fn reload_render_shaders(&mut self) -> Option<(&wgpu::ShaderModule, &wgpu::ShaderModule)> {
+        let forced = self.force_reload;
+        let changed = self.poll_changes()?;
 
         let vs_content = match fs::read_to_string(&self.shader_paths[0]) {
             Ok(content) => content,
             Err(e) => {
-                eprintln!("Failed to read vertex shader: {}", e);
+                error!("Failed to read vertex shader: {}", e);
+                self.retry_later(forced);
                 return None;
             }
         };
@@ -222,73 +395,57 @@ impl ShaderHotReload {
         let fs_content = match fs::read_to_string(&self.shader_paths[1]) {
             Ok(content) => content,
             Err(e) => {
-                eprintln!("Failed to read fragment shader: {}", e);
+                error!("Failed to read fragment shader: {}", e);
+                self.retry_later(forced);
                 return None;
             }
         };
 
-        if vs_content == self.last_vs_content && fs_content == self.last_fs_content {
+        // Changes to included files (watch patterns) must rebuild even if the entry files are identical.
+        let only_entry_files = changed.iter().all(|p| self.watched_files.contains(&Self::absolute_path(p)));
+        if !forced && only_entry_files && vs_content == self.last_vs_content && fs_content == self.last_fs_content {
+            self.finish_reload();
             return None;
         }
 
-        let new_vs = match self.create_shader_module(&vs_content, "Vertex Shader") {
-            Some(module) => module,
-            None => return None,
-        };
-
-        let new_fs = match self.create_shader_module(&fs_content, "Fragment Shader") {
-            Some(module) => module,
-            None => return None,
-        };
+        let new_vs = self.create_shader_module(&vs_content, "Vertex Shader")?;
+        let new_fs = self.create_shader_module(&fs_content, "Fragment Shader")?;
         self.last_vs_content = vs_content;
         self.last_fs_content = fs_content;
         self.vs_module = new_vs;
         self.fs_module = new_fs;
+        self.finish_reload();
+        self.notify_reload(&changed);
 
         Some((&self.vs_module, &self.fs_module))
     }
     pub fn reload_compute_shader(&mut self) -> Option<&wgpu::ShaderModule> {
-        let mut should_reload = false;
-        while let Ok(event) = self.rx.try_recv() {
-            for path in event.paths {
-                let now = Instant::now();
-                
-                if let Some(last_update) = self.last_update_times.get(&path) {
-                    if now.duration_since(*last_update) < self.debounce_duration {
-                        continue;
-                    }
-                }
-                
-                self.last_update_times.insert(path.clone(), now);
-                should_reload = true;
-            }
-        }
-
-        if !should_reload {
-            return None;
-        }
+        let forced = self.force_reload;
+        let changed = self.poll_changes()?;
 
         let compute_content = match fs::read_to_string(&self.shader_paths[0]) {
             Ok(content) => content,
             Err(e) => {
-                eprintln!("Failed to read compute shader: {}", e);
+                error!("Failed to read compute shader: {}", e);
+                self.retry_later(forced);
                 return None;
             }
         };
-        
+
+        let only_entry_files = changed.iter().all(|p| self.watched_files.contains(&Self::absolute_path(p)));
         if let Some(ref last_content) = self.last_compute_content {
-            if compute_content == *last_content {
+            if !forced && only_entry_files && compute_content == *last_content {
+                self.finish_reload();
                 return None;
             }
         }
 
-        let new_compute = match self.create_shader_module(&compute_content, "Compute Shader") {
-            Some(module) => module,
-            None => return None,
-        };
-        
+        let new_compute = self.create_shader_module(&compute_content, "Compute Shader")?;
+
         self.last_compute_content = Some(compute_content);
         self.compute_module = Some(new_compute);
+        self.finish_reload();
+        self.notify_reload(&changed);
 
         self.compute_module.as_ref()
     }
@@ -296,7 +453,7 @@ impl ShaderHotReload {
     fn create_shader_module(&self, source: &str, label: &str) -> Option<wgpu::ShaderModule> {
         let desc = wgpu::ShaderModuleDescriptor {
             label: Some(label),
-            source: wgpu::ShaderSource::Wgsl(source.into()),
+            source: self.shader_cache.shader_source(source, label),
         };
 
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
@@ -307,20 +464,44 @@ impl ShaderHotReload {
             Ok(module) => Some(module),
             Err(e) => {
                 if let Some(error_msg) = e.downcast_ref::<String>() {
-                    eprintln!("Shader compilation error in {}: {}", label, error_msg);
+                    error!("Shader compilation error in {}: {}", label, error_msg);
                 } else {
-                    eprintln!("Shader compilation error in {}", label);
+                    error!("Shader compilation error in {}", label);
                 }
                 None
             }
         }
     }
-    
+
     pub fn entry_point(&self) -> Option<&str> {
         self.entry_point.as_deref()
     }
-    
+
     pub fn get_compute_module(&self) -> Option<&wgpu::ShaderModule> {
         self.compute_module.as_ref()
     }
-}
\ No newline at end of file
+}
+
+/// Minimal glob matcher supporting `*` (within a path segment), `?` and `**` (across segments).
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn match_from(p: &[u8], s: &[u8]) -> bool {
+        if p.is_empty() {
+            return s.is_empty();
+        }
+        if p.starts_with(b"**") {
+            let rest = p[2..].strip_prefix(b"/").unwrap_or(&p[2..]);
+            if rest.is_empty() {
+                return true;
+            }
+            return (0..=s.len()).any(|i| (i == 0 || s[i - 1] == b'/') && match_from(rest, &s[i..]));
+        }
+        match p[0] {
+            b'*' => (0..=s.len())
+                .take_while(|&i| i == 0 || s[i - 1] != b'/')
+                .any(|i| match_from(&p[1..], &s[i..])),
+            b'?' => !s.is_empty() && s[0] != b'/' && match_from(&p[1..], &s[1..]),
+            c => !s.is_empty() && s[0] == c && match_from(&p[1..], &s[1..]),
+        }
+    }
+    match_from(pattern.as_bytes(), path.as_bytes())
+}
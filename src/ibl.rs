@@ -0,0 +1,397 @@
+use wgpu::util::DeviceExt;
+use crate::{Core, HdriTexture, UniformProvider};
+
+const IBL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[derive(Debug, Clone, Copy)]
+pub struct IblConfig {
+    /// Face size of the environment cubemap converted from the equirect HDRI (a full mip chain is generated).
+    pub environment_size: u32,
+    /// Face size of mip 0 of the prefiltered specular cubemap.
+    pub specular_size: u32,
+    /// Number of roughness levels; mip `i` holds roughness `i / (levels - 1)`.
+    pub specular_mip_levels: u32,
+    pub irradiance_size: u32,
+    pub brdf_lut_size: u32,
+    pub specular_samples: u32,
+    pub irradiance_samples: u32,
+    pub brdf_samples: u32,
+}
+
+impl Default for IblConfig {
+    fn default() -> Self {
+        Self {
+            environment_size: 512,
+            specular_size: 128,
+            specular_mip_levels: 6,
+            irradiance_size: 32,
+            brdf_lut_size: 256,
+            specular_samples: 256,
+            irradiance_samples: 512,
+            brdf_samples: 512,
+        }
+    }
+}
+
+/// Shader-side info for sampling the prefiltered maps.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct IblUniform {
+    /// Use `roughness * (specular_mip_levels - 1)` as the lod when sampling the specular map.
+    pub specular_mip_levels: f32,
+    pub intensity: f32,
+    pub _padding: [f32; 2],
+}
+
+impl UniformProvider for IblUniform {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct IblParams {
+    face_size: u32,
+    sample_count: u32,
+    roughness: f32,
+    env_size: f32,
+}
+
+/// Layout of `IblMaps::bind_group`:
+/// - `@binding(0)`: environment `texture_cube<f32>`
+/// - `@binding(1)`: diffuse irradiance `texture_cube<f32>`
+/// - `@binding(2)`: prefiltered specular `texture_cube<f32>` (mip = roughness)
+/// - `@binding(3)`: BRDF LUT `texture_2d<f32>` (rg = scale, bias)
+/// - `@binding(4)`: linear `sampler`
+/// - `@binding(5)`: `IblUniform`
+pub fn create_ibl_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let visibility = wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE;
+    let texture_entry = |binding: u32, view_dimension: wgpu::TextureViewDimension| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Texture {
+            multisampled: false,
+            view_dimension,
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+        },
+        count: None,
+    };
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("IBL Bind Group Layout"),
+        entries: &[
+            texture_entry(0, wgpu::TextureViewDimension::Cube),
+            texture_entry(1, wgpu::TextureViewDimension::Cube),
+            texture_entry(2, wgpu::TextureViewDimension::Cube),
+            texture_entry(3, wgpu::TextureViewDimension::D2),
+            wgpu::BindGroupLayoutEntry {
+                binding: 4,
+                visibility,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 5,
+                visibility,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+/// Precomputed image-based lighting maps generated on the GPU from an equirectangular HDRI.
+pub struct IblMaps {
+    pub environment: wgpu::Texture,
+    pub environment_view: wgpu::TextureView,
+    pub irradiance: wgpu::Texture,
+    pub irradiance_view: wgpu::TextureView,
+    pub specular: wgpu::Texture,
+    pub specular_view: wgpu::TextureView,
+    pub brdf_lut: wgpu::Texture,
+    pub brdf_lut_view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub uniform: IblUniform,
+    pub uniform_buffer: wgpu::Buffer,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    pub config: IblConfig,
+}
+
+struct IblPipelines {
+    equirect_to_cube: wgpu::ComputePipeline,
+    downsample_cube: wgpu::ComputePipeline,
+    prefilter_specular: wgpu::ComputePipeline,
+    irradiance: wgpu::ComputePipeline,
+    brdf_lut: wgpu::ComputePipeline,
+}
+
+impl IblPipelines {
+    fn new(core: &Core) -> Self {
+        let module = core.create_shader_module(include_str!("../shaders/ibl.wgsl"), "IBL Shader");
+        let create = |entry_point: &str| {
+            core.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(&format!("IBL {}", entry_point)),
+                layout: None,
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: core.pipeline_cache.as_ref(),
+            })
+        };
+        Self {
+            equirect_to_cube: create("equirect_to_cube"),
+            downsample_cube: create("downsample_cube"),
+            prefilter_specular: create("prefilter_specular"),
+            irradiance: create("irradiance"),
+            brdf_lut: create("brdf_lut"),
+        }
+    }
+}
+
+fn create_cube_texture(device: &wgpu::Device, size: u32, mip_level_count: u32, label: &str) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 6,
+        },
+        mip_level_count,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: IBL_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+        view_formats: &[],
+    })
+}
+
+fn cube_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::Cube),
+        array_layer_count: Some(6),
+        ..Default::default()
+    })
+}
+
+fn mip_array_view(texture: &wgpu::Texture, mip: u32) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        base_mip_level: mip,
+        mip_level_count: Some(1),
+        array_layer_count: Some(6),
+        ..Default::default()
+    })
+}
+
+impl IblMaps {
+    pub fn from_hdri(core: &Core, hdri: &HdriTexture, config: IblConfig) -> Self {
+        Self::new(core, &hdri.view, config)
+    }
+
+    /// `equirect_view` must be a filterable float texture (e.g. `Rgba16Float`) in the
+    /// same lat-long mapping `pathtracing.wgsl` uses for its background.
+    pub fn new(core: &Core, equirect_view: &wgpu::TextureView, config: IblConfig) -> Self {
+        let device = &core.device;
+        let pipelines = IblPipelines::new(core);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("IBL Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        // The equirect wraps horizontally
+        let equirect_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("IBL Equirect Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let env_mips = config.environment_size.max(1).ilog2() + 1;
+        let specular_mips = config.specular_mip_levels.clamp(1, config.specular_size.max(1).ilog2() + 1);
+        let environment = create_cube_texture(device, config.environment_size, env_mips, "IBL Environment Cubemap");
+        let specular = create_cube_texture(device, config.specular_size, specular_mips, "IBL Specular Cubemap");
+        let irradiance = create_cube_texture(device, config.irradiance_size, 1, "IBL Irradiance Cubemap");
+        let brdf_lut = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("IBL BRDF LUT"),
+            size: wgpu::Extent3d {
+                width: config.brdf_lut_size,
+                height: config.brdf_lut_size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: IBL_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+            view_formats: &[],
+        });
+        let environment_view = cube_view(&environment);
+        let specular_view = cube_view(&specular);
+        let irradiance_view = cube_view(&irradiance);
+        let brdf_lut_view = brdf_lut.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let params_buffer = |params: IblParams| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("IBL Params"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            })
+        };
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("IBL Precompute Encoder"),
+        });
+        let mut dispatch = |pipeline: &wgpu::ComputePipeline, entries: &[wgpu::BindGroupEntry], size: u32, layers: u32| {
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("IBL Precompute Bind Group"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries,
+            });
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("IBL Precompute Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            let groups = size.div_ceil(8);
+            pass.dispatch_workgroups(groups, groups, layers);
+        };
+
+        // 1. Equirect -> environment cubemap mip 0, then box-filter the rest of the chain
+        let params = params_buffer(IblParams {
+            face_size: config.environment_size,
+            sample_count: 0,
+            roughness: 0.0,
+            env_size: config.environment_size as f32,
+        });
+        let target = mip_array_view(&environment, 0);
+        dispatch(&pipelines.equirect_to_cube, &[
+            wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(equirect_view) },
+            wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&equirect_sampler) },
+            wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&target) },
+        ], config.environment_size, 6);
+
+        for mip in 1..env_mips {
+            let size = (config.environment_size >> mip).max(1);
+            let params = params_buffer(IblParams {
+                face_size: size,
+                sample_count: 0,
+                roughness: 0.0,
+                env_size: config.environment_size as f32,
+            });
+            let source = mip_array_view(&environment, mip - 1);
+            let target = mip_array_view(&environment, mip);
+            dispatch(&pipelines.downsample_cube, &[
+                wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&target) },
+                wgpu::BindGroupEntry { binding: 5, resource: wgpu::BindingResource::TextureView(&source) },
+            ], size, 6);
+        }
+
+        // 2. GGX-prefiltered specular, one roughness per mip
+        for mip in 0..specular_mips {
+            let size = (config.specular_size >> mip).max(1);
+            let roughness = if specular_mips > 1 { mip as f32 / (specular_mips - 1) as f32 } else { 0.0 };
+            let params = params_buffer(IblParams {
+                face_size: size,
+                sample_count: config.specular_samples,
+                roughness,
+                env_size: config.environment_size as f32,
+            });
+            let target = mip_array_view(&specular, mip);
+            dispatch(&pipelines.prefilter_specular, &[
+                wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&target) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::TextureView(&environment_view) },
+            ], size, 6);
+        }
+
+        // 3. Diffuse irradiance
+        let params = params_buffer(IblParams {
+            face_size: config.irradiance_size,
+            sample_count: config.irradiance_samples,
+            roughness: 1.0,
+            env_size: config.environment_size as f32,
+        });
+        let target = mip_array_view(&irradiance, 0);
+        dispatch(&pipelines.irradiance, &[
+            wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&sampler) },
+            wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&target) },
+            wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::TextureView(&environment_view) },
+        ], config.irradiance_size, 6);
+
+        // 4. Split-sum BRDF LUT (independent of the HDRI)
+        let params = params_buffer(IblParams {
+            face_size: config.brdf_lut_size,
+            sample_count: config.brdf_samples,
+            roughness: 0.0,
+            env_size: 0.0,
+        });
+        dispatch(&pipelines.brdf_lut, &[
+            wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 6, resource: wgpu::BindingResource::TextureView(&brdf_lut_view) },
+        ], config.brdf_lut_size, 1);
+
+        core.queue.submit(std::iter::once(encoder.finish()));
+
+        let uniform = IblUniform {
+            specular_mip_levels: specular_mips as f32,
+            intensity: 1.0,
+            _padding: [0.0; 2],
+        };
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("IBL Uniform"),
+            contents: uniform.as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group_layout = create_ibl_bind_group_layout(device);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("IBL Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&environment_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&irradiance_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&specular_view) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&brdf_lut_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&sampler) },
+                wgpu::BindGroupEntry { binding: 5, resource: uniform_buffer.as_entire_binding() },
+            ],
+        });
+
+        Self {
+            environment,
+            environment_view,
+            irradiance,
+            irradiance_view,
+            specular,
+            specular_view,
+            brdf_lut,
+            brdf_lut_view,
+            sampler,
+            uniform,
+            uniform_buffer,
+            bind_group_layout,
+            bind_group,
+            config,
+        }
+    }
+
+    pub fn set_intensity(&mut self, queue: &wgpu::Queue, intensity: f32) {
+        self.uniform.intensity = intensity;
+        queue.write_buffer(&self.uniform_buffer, 0, self.uniform.as_bytes());
+    }
+}
@@ -0,0 +1,296 @@
+// Shadertoy-style keyboard input: a 256x3 texture indexed by JavaScript key code, with
+// "is down" in row 0, "pressed this frame" in row 1 and "toggled" in row 2.
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+pub const KEYBOARD_TEXTURE_WIDTH: u32 = 256;
+const KEYS: usize = KEYBOARD_TEXTURE_WIDTH as usize;
+
+/// JavaScript `keyCode` for a physical key, as used by Shadertoy's keyboard texture
+/// (`A` = 65, `0` = 48, space = 32, arrows = 37..40). Left and right modifiers share a code.
+pub fn key_code_index(key: KeyCode) -> Option<u8> {
+    use KeyCode::*;
+    let code = match key {
+        Backspace => 8,
+        Tab => 9,
+        Enter | NumpadEnter => 13,
+        ShiftLeft | ShiftRight => 16,
+        ControlLeft | ControlRight => 17,
+        AltLeft | AltRight => 18,
+        Pause => 19,
+        CapsLock => 20,
+        Escape => 27,
+        Space => 32,
+        PageUp => 33,
+        PageDown => 34,
+        End => 35,
+        Home => 36,
+        ArrowLeft => 37,
+        ArrowUp => 38,
+        ArrowRight => 39,
+        ArrowDown => 40,
+        Insert => 45,
+        Delete => 46,
+        Digit0 => 48,
+        Digit1 => 49,
+        Digit2 => 50,
+        Digit3 => 51,
+        Digit4 => 52,
+        Digit5 => 53,
+        Digit6 => 54,
+        Digit7 => 55,
+        Digit8 => 56,
+        Digit9 => 57,
+        KeyA => 65,
+        KeyB => 66,
+        KeyC => 67,
+        KeyD => 68,
+        KeyE => 69,
+        KeyF => 70,
+        KeyG => 71,
+        KeyH => 72,
+        KeyI => 73,
+        KeyJ => 74,
+        KeyK => 75,
+        KeyL => 76,
+        KeyM => 77,
+        KeyN => 78,
+        KeyO => 79,
+        KeyP => 80,
+        KeyQ => 81,
+        KeyR => 82,
+        KeyS => 83,
+        KeyT => 84,
+        KeyU => 85,
+        KeyV => 86,
+        KeyW => 87,
+        KeyX => 88,
+        KeyY => 89,
+        KeyZ => 90,
+        SuperLeft => 91,
+        SuperRight => 92,
+        ContextMenu => 93,
+        Numpad0 => 96,
+        Numpad1 => 97,
+        Numpad2 => 98,
+        Numpad3 => 99,
+        Numpad4 => 100,
+        Numpad5 => 101,
+        Numpad6 => 102,
+        Numpad7 => 103,
+        Numpad8 => 104,
+        Numpad9 => 105,
+        NumpadMultiply => 106,
+        NumpadAdd => 107,
+        NumpadSubtract => 109,
+        NumpadDecimal => 110,
+        NumpadDivide => 111,
+        F1 => 112,
+        F2 => 113,
+        F3 => 114,
+        F4 => 115,
+        F5 => 116,
+        F6 => 117,
+        F7 => 118,
+        F8 => 119,
+        F9 => 120,
+        F10 => 121,
+        F11 => 122,
+        F12 => 123,
+        NumLock => 144,
+        ScrollLock => 145,
+        Semicolon => 186,
+        Equal => 187,
+        Comma => 188,
+        Minus => 189,
+        Period => 190,
+        Slash => 191,
+        Backquote => 192,
+        BracketLeft => 219,
+        Backslash => 220,
+        BracketRight => 221,
+        Quote => 222,
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Per-key state filled from winit events. `texels` is the texture's contents: 255 where
+/// a flag is set, 0 otherwise.
+pub struct KeyboardState {
+    texels: [u8; KEYS * 3],
+    /// Physical keys held per code, so releasing one Shift doesn't drop the other
+    held: [u8; KEYS],
+}
+
+impl Default for KeyboardState {
+    fn default() -> Self {
+        Self {
+            texels: [0; KEYS * 3],
+            held: [0; KEYS],
+        }
+    }
+}
+
+impl KeyboardState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a key event. Key repeats don't count as presses.
+    pub fn handle_key_event(&mut self, event: &KeyEvent) {
+        let PhysicalKey::Code(key) = event.physical_key else {
+            return;
+        };
+        let Some(code) = key_code_index(key) else {
+            return;
+        };
+        let i = code as usize;
+        match event.state {
+            ElementState::Pressed if !event.repeat => {
+                if self.held[i] == 0 {
+                    self.texels[i] = 255;
+                    self.texels[KEYS + i] = 255;
+                    self.texels[2 * KEYS + i] ^= 255;
+                }
+                self.held[i] = self.held[i].saturating_add(1);
+            }
+            ElementState::Released => {
+                self.held[i] = self.held[i].saturating_sub(1);
+                if self.held[i] == 0 {
+                    self.texels[i] = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Feeds keyboard and focus events in. Presses egui consumed (typing into a text
+    /// field) are skipped; releases never are, so keys can't get stuck.
+    pub fn handle_window_event(&mut self, event: &WindowEvent, ui_handled: bool) {
+        match event {
+            WindowEvent::KeyboardInput { event, .. }
+                if !ui_handled || event.state == ElementState::Released =>
+            {
+                self.handle_key_event(event);
+            }
+            WindowEvent::Focused(false) => self.release_all(),
+            _ => {}
+        }
+    }
+
+    /// Releases every key (toggles are kept), e.g. when the window loses focus
+    /// and the matching release events will never arrive.
+    pub fn release_all(&mut self) {
+        self.held = [0; KEYS];
+        self.texels[..KEYS].fill(0);
+    }
+
+    /// Clears the "pressed this frame" row; call once per frame after uploading.
+    pub fn end_frame(&mut self) {
+        self.texels[KEYS..2 * KEYS].fill(0);
+    }
+
+    fn flag(&self, row: usize, key: KeyCode) -> bool {
+        key_code_index(key).is_some_and(|code| self.texels[row * KEYS + code as usize] != 0)
+    }
+
+    pub fn is_down(&self, key: KeyCode) -> bool {
+        self.flag(0, key)
+    }
+
+    /// True if the key went down since the last `end_frame`
+    pub fn was_pressed(&self, key: KeyCode) -> bool {
+        self.flag(1, key)
+    }
+
+    /// Flips on every press, starting off
+    pub fn is_toggled(&self, key: KeyCode) -> bool {
+        self.flag(2, key)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.texels
+    }
+}
+
+/// Layout for a `KeyboardTexture`: `@binding(0)` is a `texture_2d<f32>` read with `textureLoad`
+/// (there is no sampler). `visibility` picks the stages, e.g. `COMPUTE` or `FRAGMENT`.
+pub fn create_keyboard_bind_group_layout(device: &wgpu::Device, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        }],
+        label: Some("keyboard_bind_group_layout"),
+    })
+}
+
+/// `R8Unorm`, 256 x 3, holding a `KeyboardState`. In WGSL,
+/// `textureLoad(keyboard, vec2<i32>(65, 0), 0).r > 0.5` is true while `A` is held.
+pub struct KeyboardTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl KeyboardTexture {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Keyboard Texture"),
+            size: wgpu::Extent3d {
+                width: KEYBOARD_TEXTURE_WIDTH,
+                height: 3,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+            label: Some("Keyboard Texture Bind Group"),
+        });
+        Self {
+            texture,
+            view,
+            bind_group,
+        }
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, state: &KeyboardState) {
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            state.as_bytes(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(KEYBOARD_TEXTURE_WIDTH),
+                rows_per_image: Some(3),
+            },
+            wgpu::Extent3d {
+                width: KEYBOARD_TEXTURE_WIDTH,
+                height: 3,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
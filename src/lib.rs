@@ -7,6 +7,7 @@ pub use egui;
 pub use bytemuck;
 pub use anyhow;
 pub use env_logger;
+pub use serde;
 
 pub use winit::event::WindowEvent;
 pub use wgpu::SurfaceError;
@@ -28,10 +29,25 @@ mod atomic;
 pub mod gst;
 pub mod compute;
 mod spectrum;
+mod audio_features;
+#[cfg(feature = "media")]
+mod audio_texture;
 mod fps;
 mod mouse;
+mod keyboard;
+mod camera;
+mod deepzoom;
 pub mod hdri;
+mod ibl;
 mod font;
+mod glyph_atlas;
+mod sdf;
+mod cache;
+mod params;
+mod channels;
+mod sequence;
+mod midi;
+mod yuv;
 pub use renderer::*;
 pub use shader::*;
 pub use texture::*;
@@ -40,13 +56,27 @@ pub use app::*;
 pub use renderkit::*;
 pub use feedback::*;
 pub use keyinputs::KeyInputHandler;
-pub use export::{ExportSettings, ExportManager, ExportError, ExportUiState, save_frame};
-pub use hot::ShaderHotReload;
+pub use export::{ExportSettings, ExportManager, ExportError, ExportUiState, save_frame, write_wav};
+pub use hot::{ShaderHotReload, HotReloadConfig};
 pub use controls::{ControlsRequest, ShaderControls};
 pub use atomic::AtomicBuffer;
 pub use mouse::*;
+pub use deepzoom::{DeepZoomViewport, DeepZoomUniform, DeepZoomBuffers, DoubleDouble, create_deep_zoom_bind_group_layout, mandelbrot_reference_orbit, DEEP_ZOOM_WGSL};
+pub use camera::{CameraController, CameraUniform, CameraPose, CameraProjection, CameraPath, CameraKeyframe, OrbitCamera, FlyCamera, PanZoomCamera};
+pub use keyboard::{KeyboardState, KeyboardTexture, create_keyboard_bind_group_layout, key_code_index, KEYBOARD_TEXTURE_WIDTH};
 pub use hdri::*;
-pub use font::{FontSystem, FontUniforms, CharInfo};
+pub use ibl::{IblMaps, IblConfig, IblUniform, create_ibl_bind_group_layout};
+pub use audio_features::{AudioFeatures, AudioFeaturesConfig, AudioFeaturesUniform, ONSET_BANDS};
+pub use spectrum::{SpectrumAnalyzer, SpectrumConfig, FrequencyScale, BandNormalization, SpectrumBandsHeader, create_spectrum_bind_group_layout, MAX_SPECTRUM_BANDS};
+pub use font::{FontSystem, FontUniforms, CharInfo, FONT_SDF_SPREAD};
+pub use glyph_atlas::{GlyphAtlas, GlyphEntry, GlyphInstance, GLYPH_PAGE_SIZE};
+pub use sdf::coverage_to_sdf;
+pub use cache::{ShaderCache, content_hash, CACHE_DIR_ENV};
+pub use params::ParamsFile;
+pub use sequence::{ImageSequence, SequenceSource};
+pub use yuv::{YuvConverter, YuvPlanes, YuvFormat, YuvColorMatrix};
+pub use midi::{MidiFile, MidiNote, MidiEvent, MidiSequencer, MidiUniform, midi_note_frequency, MIDI_NOTES};
+pub use channels::{ChannelSet, MediaChannel, ChannelSource, ChannelSamplerConfig, ChannelUniform, MAX_CHANNELS};
 
 #[cfg(feature = "media")]
 pub mod audio {
@@ -54,6 +84,15 @@ pub mod audio {
         SynthesisManager, SynthesisUniform, SynthesisWaveform,
         AudioSynthManager, AudioWaveform, MusicalNote, AudioDataProvider, AudioSynthUniform
     };
+    pub use crate::gst::audiofile::AudioFileManager;
+    pub use crate::gst::waveform::{WaveformTap, WAVEFORM_SAMPLES};
+    pub use crate::gst::pcm::{PcmStream, PcmStreamConfig};
+    pub use crate::gst::synth_engine::{
+        SynthConfig, AdsrConfig, FilterConfig, UnisonConfig, DelayConfig, ReverbConfig,
+        EnvelopeStage, VoiceState, MAX_SYNTH_VOICES, MAX_UNISON
+    };
+    pub use crate::audio_texture::{AudioTexture, AUDIO_TEXTURE_WIDTH};
+    pub use crate::gst::analysis::{AnalysisSnapshot, LevelData, SpectrumData};
 }
 
 
@@ -61,9 +100,9 @@ pub mod prelude {
     pub use crate::{
         Core, ShaderApp, ShaderManager,
         UniformProvider, UniformBinding, 
-        RenderKit, ShaderControls, ExportManager, ShaderHotReload,
+        RenderKit, ShaderControls, ExportManager, ShaderHotReload, HotReloadConfig,
         TextureManager, Renderer, AtomicBuffer,
-        KeyInputHandler, ControlsRequest, FontSystem, FontUniforms,
+        KeyInputHandler, ControlsRequest, FontSystem, FontUniforms, ShaderCache,
         save_frame, compute::create_bind_group_layout,compute::BindGroupLayoutType
     };
     
@@ -105,6 +144,10 @@ pub struct Core {
     pub config: wgpu::SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
     pub window: Window,
+    /// Driver-level pipeline cache, present when the adapter supports `PIPELINE_CACHE`.
+    pub pipeline_cache: Option<wgpu::PipelineCache>,
+    pub shader_cache: ShaderCache,
+    pipeline_cache_path: Option<std::path::PathBuf>,
 }
 impl Core {
     pub async fn new(window: Window) -> Self {
@@ -127,10 +170,11 @@ impl Core {
             })
             .await
             .unwrap();
+        let required_features = adapter.features() & wgpu::Features::PIPELINE_CACHE;
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                required_features: wgpu::Features::empty(),
+                required_features,
                 required_limits: wgpu::Limits::default(),
                 memory_hints: Default::default(),
                 trace: wgpu::Trace::default(),
@@ -138,6 +182,27 @@ impl Core {
             .await
             .unwrap();
         let device = Arc::new(device);
+        let shader_cache = ShaderCache::from_env();
+        let pipeline_cache_path = if required_features.contains(wgpu::Features::PIPELINE_CACHE) {
+            cache::pipeline_cache_path(&shader_cache, &adapter.get_info())
+        } else {
+            None
+        };
+        let pipeline_cache = pipeline_cache_path.as_ref().map(|path| {
+            let data = std::fs::read(path).ok();
+            if data.is_some() {
+                log::info!("Loaded pipeline cache from {}", path.display());
+            }
+            // SAFETY: the data was produced by `PipelineCache::get_data` for an adapter with the
+            // same cache key, and `fallback` lets wgpu discard it if the driver rejects it.
+            unsafe {
+                device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: Some("Cuneus Pipeline Cache"),
+                    data: data.as_deref(),
+                    fallback: true,
+                })
+            }
+        });
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps
             .formats
@@ -165,6 +230,25 @@ impl Core {
             config,
             size,
             window,
+            pipeline_cache,
+            shader_cache,
+            pipeline_cache_path,
+        }
+    }
+    /// Creates a shader module through the on-disk shader cache.
+    pub fn create_shader_module(&self, source: &str, label: &str) -> wgpu::ShaderModule {
+        self.shader_cache.create_shader_module(&self.device, source, label)
+    }
+    /// Writes the driver pipeline cache to disk so the next run skips backend compilation.
+    pub fn save_pipeline_cache(&self) {
+        let (Some(cache), Some(path)) = (&self.pipeline_cache, &self.pipeline_cache_path) else {
+            return;
+        };
+        if let Some(data) = cache.get_data() {
+            match cache::write_atomic(path, &data) {
+                Ok(()) => log::info!("Saved pipeline cache to {}", path.display()),
+                Err(e) => log::warn!("Failed to save pipeline cache: {}", e),
+            }
         }
     }
     pub fn window(&self) -> &Window {
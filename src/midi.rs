@@ -0,0 +1,601 @@
+// Standard MIDI File playback. Notes are resolved to seconds when the file is loaded, so which
+// notes sound at a given time is a pure function of that time: pausing, resetting, seeking and
+// exporting all land on exactly the same notes.
+use anyhow::{anyhow, bail, Result};
+use log::info;
+use std::collections::VecDeque;
+use std::path::Path;
+use crate::UniformProvider;
+
+/// Note numbers span 0..128
+pub const MIDI_NOTES: usize = 128;
+
+/// Equal-tempered frequency of a note number, A4 (69) = 440 Hz
+pub fn midi_note_frequency(note: u8) -> f64 {
+    440.0 * 2f64.powf((note as f64 - 69.0) / 12.0)
+}
+
+/// One note from the file, with its times already converted through the tempo map
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiNote {
+    pub channel: u8,
+    pub key: u8,
+    pub velocity: u8,
+    /// Seconds from the start of the file
+    pub start: f64,
+    pub end: f64,
+}
+
+/// A change the synth should apply, as produced by `MidiSequencer::update`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MidiEvent {
+    /// Start (or retrigger) `key` with velocity 0..1
+    NoteOn { key: u8, velocity: f32 },
+    NoteOff { key: u8 },
+}
+
+/// Tempo in effect from `tick` on
+#[derive(Debug, Clone, Copy)]
+struct TempoSegment {
+    tick: u64,
+    seconds: f64,
+    seconds_per_tick: f64,
+}
+
+enum TrackEvent {
+    NoteOn { channel: u8, key: u8, velocity: u8 },
+    NoteOff { channel: u8, key: u8 },
+    /// Note-on and note-off on the same tick of one track, e.g. a drum hit
+    ZeroLength { channel: u8, key: u8, velocity: u8 },
+    /// Microseconds per quarter note
+    Tempo(u32),
+}
+
+/// Big-endian cursor over the file bytes
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len())
+            .ok_or_else(|| anyhow!("MIDI data ends unexpectedly at byte {}", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Variable-length quantity, 7 bits per byte
+    fn varlen(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("Invalid variable-length value at byte {}", self.pos)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+/// A parsed Standard MIDI File (format 0, 1 or 2; all tracks are merged)
+#[derive(Debug, Clone)]
+pub struct MidiFile {
+    /// Sorted by start time
+    notes: Vec<MidiNote>,
+    tempo_map: Vec<TempoSegment>,
+    /// `None` for SMPTE-timed files, which have no beats
+    ticks_per_beat: Option<u16>,
+    duration: f64,
+}
+
+impl MidiFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| anyhow!("Failed to read MIDI file {}: {}", path.display(), e))?;
+        let file = Self::parse(&data)
+            .map_err(|e| anyhow!("Failed to parse MIDI file {}: {}", path.display(), e))?;
+        info!("Loaded MIDI file {}: {} notes, {:.1}s", path.display(), file.notes.len(), file.duration);
+        Ok(file)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = Reader { data, pos: 0 };
+        if reader.bytes(4)? != b"MThd" {
+            bail!("Not a Standard MIDI File");
+        }
+        let header_len = reader.u32()? as usize;
+        let header = reader.bytes(header_len)?;
+        if header.len() < 6 {
+            bail!("MIDI header is too short");
+        }
+        let division = u16::from_be_bytes([header[4], header[5]]);
+
+        let mut events = Vec::new();
+        while !reader.is_empty() {
+            let id = reader.bytes(4)?;
+            let len = reader.u32()? as usize;
+            let chunk = reader.bytes(len)?;
+            // Unknown chunks are allowed by the spec and skipped
+            if id == b"MTrk" {
+                Self::parse_track(chunk, &mut events)?;
+            }
+        }
+        // Stable, so every track keeps its own order within a tick
+        events.sort_by_key(|(tick, _)| *tick);
+
+        let (ticks_per_beat, tempo_map) = if division & 0x8000 != 0 {
+            // SMPTE: -frames per second in the high byte, ticks per frame in the low byte
+            let fps = match (division >> 8) as u8 as i8 {
+                -29 => 29.97,
+                fps => -(fps as f64),
+            };
+            let ticks_per_frame = (division & 0xff).max(1) as f64;
+            (None, vec![TempoSegment { tick: 0, seconds: 0.0, seconds_per_tick: 1.0 / (fps * ticks_per_frame) }])
+        } else {
+            let ticks_per_beat = division.max(1);
+            let per_tick = |tempo: u32| tempo as f64 * 1e-6 / ticks_per_beat as f64;
+            // 120 BPM until the file says otherwise
+            let mut map = vec![TempoSegment { tick: 0, seconds: 0.0, seconds_per_tick: per_tick(500_000) }];
+            for (tick, event) in &events {
+                if let TrackEvent::Tempo(tempo) = event {
+                    let last = *map.last().unwrap();
+                    let segment = TempoSegment {
+                        tick: *tick,
+                        seconds: last.seconds + (*tick - last.tick) as f64 * last.seconds_per_tick,
+                        seconds_per_tick: per_tick(*tempo),
+                    };
+                    if last.tick == *tick {
+                        *map.last_mut().unwrap() = segment;
+                    } else {
+                        map.push(segment);
+                    }
+                }
+            }
+            (Some(ticks_per_beat), map)
+        };
+
+        let mut file = Self { notes: Vec::new(), tempo_map, ticks_per_beat, duration: 0.0 };
+        let end_tick = events.last().map(|(tick, _)| *tick).unwrap_or(0);
+        let end = file.tick_to_seconds(end_tick);
+        // Overlapping notes on the same key are paired first-in, first-out
+        let mut held: Vec<VecDeque<(f64, u8)>> = (0..16 * MIDI_NOTES).map(|_| VecDeque::new()).collect();
+        for (tick, event) in &events {
+            let seconds = file.tick_to_seconds(*tick);
+            match *event {
+                TrackEvent::NoteOn { channel, key, velocity } => {
+                    held[channel as usize * MIDI_NOTES + key as usize].push_back((seconds, velocity));
+                }
+                TrackEvent::NoteOff { channel, key } => {
+                    if let Some((start, velocity)) = held[channel as usize * MIDI_NOTES + key as usize].pop_front() {
+                        file.notes.push(MidiNote { channel, key, velocity, start, end: seconds });
+                    }
+                }
+                TrackEvent::ZeroLength { channel, key, velocity } => {
+                    file.notes.push(MidiNote { channel, key, velocity, start: seconds, end: seconds });
+                }
+                TrackEvent::Tempo(_) => {}
+            }
+        }
+        // Notes never released last until the end of the file
+        for (index, queue) in held.iter().enumerate() {
+            for &(start, velocity) in queue {
+                let (channel, key) = ((index / MIDI_NOTES) as u8, (index % MIDI_NOTES) as u8);
+                file.notes.push(MidiNote { channel, key, velocity, start, end: end.max(start) });
+            }
+        }
+        file.notes.sort_by(|a, b| a.start.total_cmp(&b.start));
+        file.duration = file.notes.iter().map(|n| n.end).fold(end, f64::max);
+        Ok(file)
+    }
+
+    fn parse_track(data: &[u8], events: &mut Vec<(u64, TrackEvent)>) -> Result<()> {
+        let mut reader = Reader { data, pos: 0 };
+        let track_start = events.len();
+        let mut tick = 0u64;
+        let mut running_status = 0u8;
+        while !reader.is_empty() {
+            tick += reader.varlen()? as u64;
+            let mut status = reader.u8()?;
+            match status {
+                0xff => {
+                    let kind = reader.u8()?;
+                    let len = reader.varlen()? as usize;
+                    let body = reader.bytes(len)?;
+                    match kind {
+                        0x2f => break,
+                        0x51 if len == 3 => {
+                            let tempo = u32::from_be_bytes([0, body[0], body[1], body[2]]);
+                            events.push((tick, TrackEvent::Tempo(tempo.max(1))));
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+                0xf0 | 0xf7 => {
+                    let len = reader.varlen()? as usize;
+                    reader.bytes(len)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Data bytes without a status byte reuse the previous one
+            let first = if status & 0x80 == 0 {
+                if running_status == 0 {
+                    bail!("Running status without a previous status byte");
+                }
+                let data = status;
+                status = running_status;
+                data
+            } else {
+                running_status = status;
+                reader.u8()?
+            };
+            let channel = status & 0x0f;
+            match status & 0xf0 {
+                0x80 => {
+                    reader.u8()?;
+                    Self::push_note_off(events, track_start, tick, channel, first & 0x7f);
+                }
+                0x90 => {
+                    let velocity = reader.u8()? & 0x7f;
+                    let key = first & 0x7f;
+                    // Note-on with velocity 0 is a note-off
+                    if velocity == 0 {
+                        Self::push_note_off(events, track_start, tick, channel, key);
+                    } else {
+                        events.push((tick, TrackEvent::NoteOn { channel, key, velocity }));
+                    }
+                }
+                // Aftertouch, controllers and pitch bend take two data bytes
+                0xa0 | 0xb0 | 0xe0 => {
+                    reader.u8()?;
+                }
+                // Program change and channel pressure take one
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Pairs a note-off with a note-on struck earlier on the same tick of this track, which the
+    /// merged, tick-sorted event list could otherwise not tell apart from a retrigger
+    fn push_note_off(events: &mut Vec<(u64, TrackEvent)>, track_start: usize, tick: u64, channel: u8, key: u8) {
+        let same_tick = events[track_start..].iter().rev().take_while(|(t, _)| *t == tick).count();
+        let len = events.len();
+        for (_, event) in events[len - same_tick..].iter_mut().rev() {
+            if let TrackEvent::NoteOn { channel: c, key: k, velocity } = *event {
+                if c == channel && k == key {
+                    *event = TrackEvent::ZeroLength { channel, key, velocity };
+                    return;
+                }
+            }
+        }
+        events.push((tick, TrackEvent::NoteOff { channel, key }));
+    }
+
+    fn segment_at_tick(&self, tick: u64) -> &TempoSegment {
+        let index = self.tempo_map.partition_point(|s| s.tick <= tick).saturating_sub(1);
+        &self.tempo_map[index]
+    }
+
+    fn segment_at_time(&self, time: f64) -> &TempoSegment {
+        let index = self.tempo_map.partition_point(|s| s.seconds <= time).saturating_sub(1);
+        &self.tempo_map[index]
+    }
+
+    fn tick_to_seconds(&self, tick: u64) -> f64 {
+        let segment = self.segment_at_tick(tick);
+        segment.seconds + (tick - segment.tick) as f64 * segment.seconds_per_tick
+    }
+
+    /// All notes, sorted by start time
+    pub fn notes(&self) -> &[MidiNote] {
+        &self.notes
+    }
+
+    /// Seconds until the last note ends
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Position in quarter notes at `time`, following tempo changes (0 for SMPTE-timed files)
+    pub fn beat_at(&self, time: f64) -> f64 {
+        let Some(ticks_per_beat) = self.ticks_per_beat else {
+            return 0.0;
+        };
+        let segment = self.segment_at_time(time.max(0.0));
+        let ticks = segment.tick as f64 + (time.max(0.0) - segment.seconds) / segment.seconds_per_tick;
+        ticks / ticks_per_beat as f64
+    }
+
+    /// Tempo at `time` (0 for SMPTE-timed files)
+    pub fn bpm_at(&self, time: f64) -> f64 {
+        let Some(ticks_per_beat) = self.ticks_per_beat else {
+            return 0.0;
+        };
+        60.0 / (self.segment_at_time(time.max(0.0)).seconds_per_tick * ticks_per_beat as f64)
+    }
+}
+
+/// Per-note state for shaders, indexed by note number (note `n` is `[n / 4][n % 4]`):
+/// ```wgsl
+/// struct MidiState {
+///     velocities: array<vec4<f32>, 32>,
+///     ages: array<vec4<f32>, 32>,
+///     released: array<vec4<f32>, 32>,
+///     active_count: u32, time: f32, beat: f32, bpm: f32,
+/// }
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MidiUniform {
+    /// Velocity 0..1 while the note is held, 0 otherwise
+    pub velocities: [[f32; 4]; 32],
+    /// Seconds since the note was last struck, -1 if it hasn't been yet
+    pub ages: [[f32; 4]; 32],
+    /// Seconds since the note was last released, -1 while held or before its first release
+    pub released: [[f32; 4]; 32],
+    pub active_count: u32,
+    /// Sequencer position in seconds
+    pub time: f32,
+    /// Position in quarter notes
+    pub beat: f32,
+    pub bpm: f32,
+}
+
+impl Default for MidiUniform {
+    fn default() -> Self {
+        Self {
+            velocities: [[0.0; 4]; 32],
+            ages: [[-1.0; 4]; 32],
+            released: [[-1.0; 4]; 32],
+            active_count: 0,
+            time: 0.0,
+            beat: 0.0,
+            bpm: 0.0,
+        }
+    }
+}
+
+impl UniformProvider for MidiUniform {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+/// Held note on a key, merged across channels
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HeldKey {
+    start: f64,
+    velocity: f32,
+}
+
+/// Plays a `MidiFile` against an external clock. Feed it `ShaderControls` time (or export
+/// time) every frame and apply the returned events to a synth; since the sounding notes are
+/// derived from the time alone, pause, reset and export stay in step with the visuals.
+pub struct MidiSequencer {
+    file: MidiFile,
+    looping: bool,
+    /// Bit per channel, set if the channel plays
+    channel_mask: u16,
+    held: [Option<HeldKey>; MIDI_NOTES],
+    last_position: Option<f64>,
+}
+
+impl MidiSequencer {
+    pub fn new(file: MidiFile) -> Self {
+        Self {
+            file,
+            looping: false,
+            channel_mask: u16::MAX,
+            held: [None; MIDI_NOTES],
+            last_position: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(MidiFile::load(path)?))
+    }
+
+    pub fn file(&self) -> &MidiFile {
+        &self.file
+    }
+
+    /// Wrap time around the file's duration
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Mutes or unmutes a channel (0-15; drums are usually 9). Takes effect on the next update.
+    pub fn set_channel_enabled(&mut self, channel: u8, enabled: bool) {
+        if channel < 16 {
+            if enabled {
+                self.channel_mask |= 1 << channel;
+            } else {
+                self.channel_mask &= !(1 << channel);
+            }
+        }
+    }
+
+    pub fn channel_enabled(&self, channel: u8) -> bool {
+        channel < 16 && self.channel_mask & (1 << channel) != 0
+    }
+
+    /// Position in the file for a clock time, wrapped when looping
+    pub fn position(&self, time: f64) -> f64 {
+        let duration = self.file.duration;
+        if self.looping && duration > 0.0 {
+            time.rem_euclid(duration)
+        } else {
+            time.max(0.0)
+        }
+    }
+
+    /// Notes of enabled channels that have started by `position`
+    fn started(&self, position: f64) -> impl Iterator<Item = &MidiNote> {
+        let count = self.file.notes.partition_point(|n| n.start <= position);
+        self.file.notes[..count].iter().filter(|n| self.channel_enabled(n.channel))
+    }
+
+    /// Keys sounding at `position`; the latest note wins when channels overlap on a key
+    fn held_at(&self, position: f64) -> [Option<HeldKey>; MIDI_NOTES] {
+        let mut held = [None; MIDI_NOTES];
+        for note in self.started(position).filter(|n| n.end > position) {
+            held[note.key as usize] = Some(HeldKey { start: note.start, velocity: note.velocity as f32 / 127.0 });
+        }
+        held
+    }
+
+    /// Advances to clock `time` and returns what changed since the last call. Notes that
+    /// started and ended in `[last, time)` come out as an on/off pair so short hits aren't
+    /// lost, counting from 0 on the first call; jumping backwards or far ahead (reset, seek,
+    /// loop) just resyncs held notes.
+    pub fn update(&mut self, time: f64) -> Vec<MidiEvent> {
+        let position = self.position(time);
+        let held = self.held_at(position);
+        let mut events = Vec::new();
+
+        for (key, (previous, now)) in self.held.iter().zip(&held).enumerate() {
+            let key = key as u8;
+            match (previous, now) {
+                (Some(_), None) => events.push(MidiEvent::NoteOff { key }),
+                (previous, Some(now)) if previous.map(|p| p.start) != Some(now.start) => {
+                    events.push(MidiEvent::NoteOn { key, velocity: now.velocity });
+                }
+                _ => {}
+            }
+        }
+
+        let last = self.last_position.unwrap_or(0.0);
+        if last <= position && position - last < 1.0 {
+            for note in self.started(position).filter(|n| n.start >= last && n.start < position && n.end <= position) {
+                if held[note.key as usize].is_none() {
+                    events.push(MidiEvent::NoteOn { key: note.key, velocity: note.velocity as f32 / 127.0 });
+                    events.push(MidiEvent::NoteOff { key: note.key });
+                }
+            }
+        }
+
+        self.held = held;
+        self.last_position = Some(position);
+        events
+    }
+
+    /// Forgets the playback position and returns note-offs for everything held
+    pub fn reset(&mut self) -> Vec<MidiEvent> {
+        let events = self.active_notes()
+            .into_iter()
+            .map(|(key, _)| MidiEvent::NoteOff { key })
+            .collect();
+        self.held = [None; MIDI_NOTES];
+        self.last_position = None;
+        events
+    }
+
+    /// Keys held as of the last `update`, with velocity 0..1
+    pub fn active_notes(&self) -> Vec<(u8, f32)> {
+        self.held
+            .iter()
+            .enumerate()
+            .filter_map(|(key, held)| held.map(|h| (key as u8, h.velocity)))
+            .collect()
+    }
+
+    /// Fills `uniform` with the state at clock `time`. It doesn't depend on `update`, so
+    /// exported frames match no matter how audio was rendered around them.
+    pub fn fill_uniform(&self, time: f64, uniform: &mut MidiUniform) {
+        let position = self.position(time);
+        *uniform = MidiUniform::default();
+        for note in self.started(position) {
+            let (i, c) = (note.key as usize / 4, note.key as usize % 4);
+            let age = (position - note.start) as f32;
+            // Notes are sorted by start, so the last one seen on a key is the most recent
+            uniform.ages[i][c] = age;
+            if note.end > position {
+                uniform.velocities[i][c] = note.velocity as f32 / 127.0;
+                uniform.released[i][c] = -1.0;
+            } else {
+                uniform.released[i][c] = (position - note.end) as f32;
+            }
+        }
+        // An earlier, longer note on the same key may still be held
+        for note in self.started(position).filter(|n| n.end > position) {
+            let (i, c) = (note.key as usize / 4, note.key as usize % 4);
+            if uniform.velocities[i][c] == 0.0 {
+                uniform.velocities[i][c] = note.velocity as f32 / 127.0;
+                uniform.released[i][c] = -1.0;
+            }
+        }
+        uniform.active_count = uniform.velocities.iter().flatten().filter(|&&v| v > 0.0).count() as u32;
+        uniform.time = position as f32;
+        uniform.beat = self.file.beat_at(position) as f32;
+        uniform.bpm = self.file.bpm_at(position) as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Format 0, 96 ticks per beat, default tempo (one beat = 0.5 s)
+    fn smf(track: &[u8]) -> Vec<u8> {
+        let mut data = b"MThd".to_vec();
+        data.extend_from_slice(&[0, 0, 0, 6, 0, 0, 0, 1, 0, 96]);
+        data.extend_from_slice(b"MTrk");
+        data.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        data.extend_from_slice(track);
+        data
+    }
+
+    #[test]
+    fn zero_length_notes_do_not_hang() {
+        let data = smf(&[
+            0x00, 0x99, 36, 100, // kick on
+            0x00, 0x89, 36, 0, // kick off on the same tick
+            0x60, 0x99, 38, 90, // snare one beat later
+            0x00, 0x99, 38, 0, // note-on with velocity 0 releases it
+            0x60, 0xff, 0x2f, 0x00,
+        ]);
+        let file = MidiFile::parse(&data).unwrap();
+        let notes = file.notes();
+        assert_eq!(notes.len(), 2);
+        assert_eq!((notes[0].key, notes[0].start, notes[0].end), (36, 0.0, 0.0));
+        assert_eq!((notes[1].key, notes[1].velocity), (38, 90));
+        assert!((notes[1].start - 0.5).abs() < 1e-9 && notes[1].end == notes[1].start);
+
+        let mut sequencer = MidiSequencer::new(file);
+        // Hits before the first update and exactly on an update's position still play once
+        assert_eq!(
+            sequencer.update(0.25),
+            vec![MidiEvent::NoteOn { key: 36, velocity: 100.0 / 127.0 }, MidiEvent::NoteOff { key: 36 }]
+        );
+        assert!(sequencer.update(0.5).is_empty());
+        assert_eq!(
+            sequencer.update(0.6),
+            vec![MidiEvent::NoteOn { key: 38, velocity: 90.0 / 127.0 }, MidiEvent::NoteOff { key: 38 }]
+        );
+        assert!(sequencer.active_notes().is_empty());
+    }
+}
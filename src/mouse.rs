@@ -1,5 +1,6 @@
+use std::time::Instant;
 use crate::UniformProvider;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, MouseButton, TouchPhase, WindowEvent};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -27,10 +28,95 @@ impl UniformProvider for MouseUniform {
     }
 }
 
+pub const MAX_TOUCHES: usize = 10;
+
+/// Modifier bits in `PointerUniform::modifiers`
+pub const MODIFIER_SHIFT: u32 = 1;
+pub const MODIFIER_CTRL: u32 = 2;
+pub const MODIFIER_ALT: u32 = 4;
+pub const MODIFIER_SUPER: u32 = 8;
+
+/// Richer pointer state next to `MouseUniform`. Positions are 0..1 like `MouseUniform`
+/// except `imouse`, which follows Shadertoy: pixels with y up, `xy` the position while the
+/// left button is held, `zw` the click position with `z < 0` once released and `w < 0`
+/// after the click's first frame. "This frame" means since the last `end_frame`.
+/// ```wgsl
+/// struct Pointer {
+///     imouse: vec4<f32>,
+///     position: vec2<f32>, delta: vec2<f32>,           // delta: movement this frame
+///     drag_start: vec2<f32>, drag_end: vec2<f32>,      // left-button drags
+///     wheel_delta: vec2<f32>, buttons: u32, pressed: u32,  // button masks as in MouseUniform
+///     released: u32, double_clicked: u32, modifiers: u32, touch_count: u32,
+///     dragging: u32, _pad0: u32, _pad1: u32, _pad2: u32,
+///     touches: array<vec4<f32>, 10>,                   // xy position, z touch id, w force (1 if unknown)
+/// }
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointerUniform {
+    pub imouse: [f32; 4],
+    pub position: [f32; 2],
+    pub delta: [f32; 2],
+    pub drag_start: [f32; 2],
+    pub drag_end: [f32; 2],
+    pub wheel_delta: [f32; 2],
+    /// Buttons held, same bits as `MouseUniform::buttons[0]`
+    pub buttons: u32,
+    /// Buttons that went down this frame
+    pub pressed: u32,
+    /// Buttons that went up this frame
+    pub released: u32,
+    pub double_clicked: u32,
+    /// `MODIFIER_*` bits
+    pub modifiers: u32,
+    pub touch_count: u32,
+    /// 1 while the left button is held and the pointer has moved since it went down
+    pub dragging: u32,
+    pub _padding: [u32; 3],
+    pub touches: [[f32; 4]; MAX_TOUCHES],
+}
+
+impl Default for PointerUniform {
+    fn default() -> Self {
+        bytemuck::Zeroable::zeroed()
+    }
+}
+
+impl UniformProvider for PointerUniform {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+/// Two presses of the same button closer than this (seconds) make a double click
+const DOUBLE_CLICK_TIME: f32 = 0.35;
+/// ...and closer than this (pixels)
+const DOUBLE_CLICK_DISTANCE: f32 = 6.0;
+
+fn button_mask(button: &MouseButton) -> u32 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Right => 2,
+        MouseButton::Middle => 4,
+        MouseButton::Back => 8,
+        MouseButton::Forward => 16,
+        MouseButton::Other(b) => if *b < 27 { 1 << (b + 5) } else { 0 },
+    }
+}
+
 pub struct MouseTracker {
     pub uniform: MouseUniform,
     pub raw_position: [f32; 2],
     pub is_inside_window: bool,
+    /// Edges, deltas, drags, modifiers and touches; see `end_frame`
+    pub pointer: PointerUniform,
+    window_size: [f32; 2],
+    /// Shadertoy click position in pixels (y up)
+    click_pixels: [f32; 2],
+    last_click: Option<(u32, Instant, [f32; 2])>,
+    touch_ids: Vec<u64>,
+    /// Vertical wheel travel since creation; unlike `uniform.wheel`, `reset_wheel` leaves it alone
+    pub(crate) wheel_travel: f32,
 }
 
 impl Default for MouseTracker {
@@ -39,6 +125,12 @@ impl Default for MouseTracker {
             uniform: MouseUniform::default(),
             raw_position: [0.0, 0.0],
             is_inside_window: false,
+            pointer: PointerUniform::default(),
+            window_size: [1.0, 1.0],
+            click_pixels: [0.0, 0.0],
+            last_click: None,
+            touch_ids: Vec::new(),
+            wheel_travel: 0.0,
         }
     }
 }
@@ -54,6 +146,9 @@ impl MouseTracker {
         window_size: [f32; 2],
         ui_handled: bool
     ) -> bool {
+        self.window_size = window_size;
+        let touch = self.handle_pointer_event(event, ui_handled);
+
         // If UI already handled the event, don't update mouse for shader
         if ui_handled {
             return false;
@@ -70,17 +165,8 @@ impl MouseTracker {
                 true
             },
             WindowEvent::MouseInput { state, button, .. } => {
-                use winit::event::{ElementState, MouseButton};
-                
                 let pressed = *state == ElementState::Pressed;
-                let bit_mask = match button {
-                    MouseButton::Left => 1,
-                    MouseButton::Right => 2,
-                    MouseButton::Middle => 4,
-                    MouseButton::Back => 8,
-                    MouseButton::Forward => 16,
-                    MouseButton::Other(b) => if *b < 27 { 1 << (b + 5) } else { 0 },
-                };
+                let bit_mask = button_mask(button);
                 
                 if pressed {
                     self.uniform.buttons[0] |= bit_mask;
@@ -113,9 +199,134 @@ impl MouseTracker {
                 self.is_inside_window = true;
                 true
             },
+            _ => touch,
+        }
+    }
+
+    /// Updates `pointer`; returns true for touch events. Presses over the UI are ignored but
+    /// releases never are, so buttons and touches can't get stuck.
+    fn handle_pointer_event(&mut self, event: &WindowEvent, ui_handled: bool) -> bool {
+        let [width, height] = self.window_size;
+        let pointer = &mut self.pointer;
+        match event {
+            WindowEvent::CursorMoved { position, .. } if !ui_handled => {
+                let (x, y) = (position.x as f32, position.y as f32);
+                let normalized = [x / width, y / height];
+                pointer.delta[0] += normalized[0] - pointer.position[0];
+                pointer.delta[1] += normalized[1] - pointer.position[1];
+                pointer.position = normalized;
+                if pointer.buttons & 1 != 0 {
+                    pointer.dragging = 1;
+                    pointer.imouse[0] = x;
+                    pointer.imouse[1] = height - y;
+                }
+                false
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let mask = button_mask(button);
+                match state {
+                    ElementState::Pressed if !ui_handled => {
+                        pointer.buttons |= mask;
+                        pointer.pressed |= mask;
+                        let pixels = self.raw_position;
+                        let now = Instant::now();
+                        let double = self.last_click.is_some_and(|(last_mask, time, at)| {
+                            last_mask == mask
+                                && now.duration_since(time).as_secs_f32() < DOUBLE_CLICK_TIME
+                                && (pixels[0] - at[0]).hypot(pixels[1] - at[1]) < DOUBLE_CLICK_DISTANCE
+                        });
+                        if double {
+                            pointer.double_clicked |= mask;
+                            self.last_click = None;
+                        } else {
+                            self.last_click = Some((mask, now, pixels));
+                        }
+                        if mask == 1 {
+                            pointer.drag_start = pointer.position;
+                            pointer.dragging = 0;
+                            self.click_pixels = [pixels[0], height - pixels[1]];
+                            let [cx, cy] = self.click_pixels;
+                            pointer.imouse = [cx, cy, cx, cy];
+                        }
+                    }
+                    ElementState::Released if pointer.buttons & mask != 0 => {
+                        pointer.buttons &= !mask;
+                        pointer.released |= mask;
+                        if mask == 1 {
+                            pointer.drag_end = pointer.position;
+                            pointer.dragging = 0;
+                            pointer.imouse[2] = -self.click_pixels[0].abs();
+                            pointer.imouse[3] = -self.click_pixels[1].abs();
+                        }
+                    }
+                    _ => {}
+                }
+                false
+            }
+            WindowEvent::MouseWheel { delta, .. } if !ui_handled => {
+                use winit::event::MouseScrollDelta;
+                let [x, y] = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => [*x, *y],
+                    MouseScrollDelta::PixelDelta(pos) => [pos.x as f32 / 100.0, pos.y as f32 / 100.0],
+                };
+                pointer.wheel_delta[0] += x;
+                pointer.wheel_delta[1] += y;
+                self.wheel_travel += y;
+                false
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                let state = modifiers.state();
+                pointer.modifiers = (state.shift_key() as u32 * MODIFIER_SHIFT)
+                    | (state.control_key() as u32 * MODIFIER_CTRL)
+                    | (state.alt_key() as u32 * MODIFIER_ALT)
+                    | (state.super_key() as u32 * MODIFIER_SUPER);
+                false
+            }
+            WindowEvent::Touch(touch) => {
+                let index = self.touch_ids.iter().position(|id| *id == touch.id);
+                let force = touch.force.map_or(1.0, |force| force.normalized() as f32);
+                let point = [
+                    touch.location.x as f32 / width,
+                    touch.location.y as f32 / height,
+                    touch.id as f32,
+                    force,
+                ];
+                match (touch.phase, index) {
+                    (TouchPhase::Started, None) if !ui_handled && self.touch_ids.len() < MAX_TOUCHES => {
+                        pointer.touches[self.touch_ids.len()] = point;
+                        self.touch_ids.push(touch.id);
+                    }
+                    (TouchPhase::Moved, Some(i)) => pointer.touches[i] = point,
+                    (TouchPhase::Ended | TouchPhase::Cancelled, Some(i)) => {
+                        self.touch_ids.remove(i);
+                        pointer.touches.copy_within(i + 1.., i);
+                        pointer.touches[MAX_TOUCHES - 1] = [0.0; 4];
+                    }
+                    _ => {}
+                }
+                pointer.touch_count = self.touch_ids.len() as u32;
+                true
+            }
             _ => false,
         }
     }
+
+    /// Clears this frame's deltas and edges and ages the Shadertoy click. Call once per
+    /// frame after uploading `pointer` (`RenderKit::update_pointer_uniform` does).
+    pub fn end_frame(&mut self) {
+        let pointer = &mut self.pointer;
+        pointer.delta = [0.0, 0.0];
+        pointer.wheel_delta = [0.0, 0.0];
+        pointer.pressed = 0;
+        pointer.released = 0;
+        pointer.double_clicked = 0;
+        pointer.imouse[3] = -pointer.imouse[3].abs();
+    }
+
+    /// Shadertoy's `iMouse`
+    pub fn shadertoy_mouse(&self) -> [f32; 4] {
+        self.pointer.imouse
+    }
     
     pub fn reset_wheel(&mut self) {
         self.uniform.wheel = [0.0, 0.0];
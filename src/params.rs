@@ -0,0 +1,236 @@
+use notify::{Watcher, RecursiveMode, Event, EventKind};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+use log::{info, warn};
+
+/// A `.toml` file next to a shader that holds its Rust-side parameter values.
+///
+/// Fields present in the file override the live values; fields missing from the
+/// file keep whatever the application currently uses, so the file can hold just
+/// the handful of values an artist cares about.
+pub struct ParamsFile {
+    path: PathBuf,
+    #[allow(dead_code)]
+    watcher: notify::RecommendedWatcher,
+    rx: Receiver<notify::Event>,
+    last_content: Option<String>,
+    pending_since: Option<Instant>,
+    debounce: Duration,
+    /// Content queued by `queue_save` and when it was last changed
+    unsaved: Option<(String, Instant)>,
+    save_delay: Duration,
+    /// When false, `save` is a no-op and the file is treated as read-only.
+    pub write_back: bool,
+}
+
+impl ParamsFile {
+    /// `shaders/currents.wgsl` -> `shaders/currents.toml`
+    pub fn sidecar_path(shader_path: &Path) -> PathBuf {
+        shader_path.with_extension("toml")
+    }
+
+    pub fn new(path: impl Into<PathBuf>) -> notify::Result<Self> {
+        let path = path.into();
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                    tx.send(event).unwrap_or_default();
+                }
+            }
+        })?;
+
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        watcher.watch(&parent, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            path,
+            watcher,
+            rx,
+            last_content: None,
+            // Load the file on the first poll
+            pending_since: Some(Instant::now()),
+            debounce: Duration::from_millis(100),
+            unsaved: None,
+            save_delay: Duration::from_millis(500),
+            write_back: true,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn is_sidecar(&self, path: &Path) -> bool {
+        path.file_name() == self.path.file_name()
+    }
+
+    /// Applies on-disk changes to `params` and writes out values queued by `queue_save` once
+    /// they have settled. Returns true if any value changed.
+    pub fn poll<T: Serialize + DeserializeOwned>(&mut self, params: &mut T) -> bool {
+        if self.unsaved.as_ref().is_some_and(|(_, since)| since.elapsed() >= self.save_delay) {
+            if let Err(e) = self.flush() {
+                warn!("Failed to save params file {}: {}", self.path.display(), e);
+            }
+        }
+        while let Ok(event) = self.rx.try_recv() {
+            if event.paths.iter().any(|p| self.is_sidecar(p)) {
+                self.pending_since = Some(Instant::now());
+            }
+        }
+
+        match self.pending_since {
+            Some(since) if since.elapsed() >= self.debounce => self.pending_since = None,
+            _ => return false,
+        }
+
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // Seed the file with the current values so there is something to edit
+                if let Err(e) = self.save(params) {
+                    warn!("Failed to create params file {}: {}", self.path.display(), e);
+                }
+                return false;
+            }
+            Err(e) => {
+                warn!("Failed to read params file {}: {}", self.path.display(), e);
+                return false;
+            }
+        };
+
+        // Ignore the echo of our own writes
+        if self.last_content.as_deref() == Some(content.as_str()) {
+            return false;
+        }
+        self.last_content = Some(content.clone());
+
+        match Self::merge(params, &content) {
+            Ok(changed) => {
+                if changed {
+                    info!("Reloaded parameters from {}", self.path.display());
+                    // The edit on disk wins over values still waiting to be saved
+                    self.unsaved = None;
+                }
+                changed
+            }
+            Err(e) => {
+                warn!("Invalid params file {}: {}", self.path.display(), e);
+                false
+            }
+        }
+    }
+
+    fn merge<T: Serialize + DeserializeOwned>(params: &mut T, content: &str) -> Result<bool, String> {
+        let overrides: toml::Table = content.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let current = to_value(&*params)?;
+        let toml::Value::Table(mut merged) = current.clone() else {
+            return Err("parameters must serialize to a table".to_string());
+        };
+
+        for (key, value) in overrides {
+            match merged.get_mut(&key) {
+                Some(slot) => *slot = value,
+                None => warn!("Unknown parameter '{}' ignored", key),
+            }
+        }
+
+        let merged = toml::Value::Table(merged);
+        if merged == current {
+            return Ok(false);
+        }
+        *params = merged.try_into().map_err(|e: toml::de::Error| e.to_string())?;
+        Ok(true)
+    }
+
+    /// Writes `params` to the file (when `write_back` is enabled).
+    pub fn save<T: Serialize>(&mut self, params: &T) -> Result<(), String> {
+        if !self.write_back {
+            return Ok(());
+        }
+        let content = Self::to_content(params)?;
+        self.write(content)
+    }
+
+    /// Like `save`, but writes on a later `poll` once `params` has stopped changing for
+    /// half a second, so dragging a slider doesn't write the file every frame.
+    pub fn queue_save<T: Serialize>(&mut self, params: &T) -> Result<(), String> {
+        if !self.write_back {
+            return Ok(());
+        }
+        let content = Self::to_content(params)?;
+        self.unsaved = if self.last_content.as_deref() == Some(content.as_str()) {
+            None
+        } else {
+            Some((content, Instant::now()))
+        };
+        Ok(())
+    }
+
+    /// Writes values queued by `queue_save` right away
+    pub fn flush(&mut self) -> Result<(), String> {
+        match self.unsaved.take() {
+            Some((content, _)) => self.write(content),
+            None => Ok(()),
+        }
+    }
+
+    fn to_content<T: Serialize>(params: &T) -> Result<String, String> {
+        toml::to_string_pretty(&to_value(params)?).map_err(|e| e.to_string())
+    }
+
+    fn write(&mut self, content: String) -> Result<(), String> {
+        self.unsaved = None;
+        if self.last_content.as_deref() == Some(content.as_str()) {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+        }
+        fs::write(&self.path, &content).map_err(|e| e.to_string())?;
+        self.last_content = Some(content);
+        Ok(())
+    }
+}
+
+impl Drop for ParamsFile {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to save params file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Serializes `params` with f32 fields written as the shortest decimal that reads back to the
+/// same f32 (`0.2`, not `0.20000000298023224`), so saved files stay readable and compare equal
+/// to hand-written values.
+fn to_value<T: Serialize>(params: &T) -> Result<toml::Value, String> {
+    let mut value = toml::Value::try_from(params).map_err(|e| e.to_string())?;
+    shorten_floats(&mut value);
+    Ok(value)
+}
+
+fn shorten_floats(value: &mut toml::Value) {
+    match value {
+        toml::Value::Float(f) => {
+            let narrow = *f as f32;
+            // Only widened f32s; genuine f64 values keep their precision
+            if narrow as f64 == *f {
+                if let Ok(short) = narrow.to_string().parse() {
+                    *f = short;
+                }
+            }
+        }
+        toml::Value::Array(items) => items.iter_mut().for_each(shorten_floats),
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, v)| shorten_floats(v)),
+        _ => {}
+    }
+}
@@ -32,6 +32,7 @@ impl Renderer {
         format: wgpu::TextureFormat,
         layout: &wgpu::PipelineLayout,
         fragment_entry: Option<&str>,
+        cache: Option<&wgpu::PipelineCache>,
     ) -> Self {
         const VERTICES: &[Vertex] = &[
             Vertex { position: [-1.0, -1.0] },
@@ -85,7 +86,7 @@ impl Renderer {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
-            cache: None,
+            cache,
         };
 
         let render_pipeline = device.create_render_pipeline(&pipeline_desc);
@@ -4,19 +4,28 @@ use egui::ViewportId;
 #[cfg(feature = "media")]
 use crate::gst::video::VideoTextureManager;
 #[cfg(feature = "media")]
-use crate::gst::webcam::WebcamTextureManager;
+use crate::gst::webcam::{WebcamTextureManager, WebcamConfig, WebcamSource};
+#[cfg(feature = "media")]
+use crate::gst::audiofile::AudioFileManager;
+#[cfg(feature = "media")]
+use crate::audio_texture::AudioTexture;
 use std::path::Path;
 use log::{info, error};
 #[cfg(feature = "media")]
 use log::warn;
 use crate::spectrum::SpectrumAnalyzer;
+use crate::audio_features::{AudioFeatures, AudioFeaturesUniform};
 use crate::compute::ComputeShader;
 use crate::{Core,fps, Renderer, TextureManager, UniformProvider, UniformBinding,KeyInputHandler,ExportManager,ShaderControls,ControlsRequest,ResolutionUniform};
-use crate::mouse::MouseUniform;
+use crate::mouse::{MouseUniform, PointerUniform};
 use crate::mouse::MouseTracker;
+use crate::keyboard::{KeyboardState, KeyboardTexture, create_keyboard_bind_group_layout};
 use winit::event::WindowEvent;
 use crate::HdriMetadata;
-use crate::load_hdri_texture;
+use crate::HdriTexture;
+use crate::ParamsFile;
+use crate::ChannelSet;
+use crate::sequence::{ImageSequence, is_animated};
 #[cfg(target_os = "macos")]
 pub const CAPTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
 #[cfg(not(target_os = "macos"))]
@@ -42,6 +51,16 @@ pub struct RenderKit {
     pub webcam_texture_manager: Option<WebcamTextureManager>,
     #[cfg(feature = "media")]
     pub using_webcam_texture: bool,
+    /// Source, capture mode and mirror/crop used by `start_webcam`. Defaults to the first
+    /// camera, or the virtual camera named by `CUNEUS_WEBCAM`.
+    #[cfg(feature = "media")]
+    pub webcam_config: WebcamConfig,
+    /// Standalone audio file driving `audio_data`/`bpm`; takes priority over a video's audio track.
+    #[cfg(feature = "media")]
+    pub audio_file_manager: Option<AudioFileManager>,
+    /// Shadertoy-style 512x2 spectrum/waveform texture, see `setup_audio_texture`.
+    #[cfg(feature = "media")]
+    pub audio_texture: Option<AudioTexture>,
     pub texture_manager: Option<TextureManager>,
     pub egui_renderer: egui_wgpu::Renderer,
     pub egui_state: egui_winit::State,
@@ -54,14 +73,34 @@ pub struct RenderKit {
     pub export_manager: ExportManager,
     pub controls: ShaderControls,
     pub spectrum_analyzer: SpectrumAnalyzer,
+    /// Onsets, beat phase, loudness and centroid, updated by `update_audio_spectrum`
+    pub audio_features: AudioFeatures,
+    pub audio_features_uniform: Option<UniformBinding<AudioFeaturesUniform>>,
+    pub audio_features_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub compute_shader: Option<ComputeShader>,
     pub fps_tracker: fps::FpsTracker,
     pub mouse_tracker: MouseTracker,
     pub mouse_uniform: Option<UniformBinding<MouseUniform>>,
     pub mouse_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    /// `mouse_tracker.pointer` (edges, drags, iMouse, touches), see `setup_pointer_uniform`
+    pub pointer_uniform: Option<UniformBinding<PointerUniform>>,
+    pub pointer_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    /// Per-key down/pressed/toggled flags, fed by `handle_keyboard_state`
+    pub keyboard: KeyboardState,
+    /// Shadertoy-style 256x3 keyboard texture, see `setup_keyboard_texture`.
+    pub keyboard_texture: Option<KeyboardTexture>,
+    pub keyboard_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub using_hdri_texture: bool,
     pub hdri_metadata: Option<HdriMetadata>,
+    /// Raw `.hdr`/`.exr` bytes of the loaded HDRI
     pub hdri_file_data: Option<Vec<u8>>,
+    /// Linear float HDRI (full dynamic range); `texture_manager` holds its exposure-mapped display copy.
+    pub hdri_texture: Option<HdriTexture>,
+    pub params_file: Option<ParamsFile>,
+    /// Independent input slots (iChannel0..3), separate from the single drag & drop texture above.
+    pub channels: ChannelSet,
+    /// Image sequence / animated GIF playing into `texture_manager`.
+    pub image_sequence: Option<ImageSequence>,
 }
 
 impl RenderKit {
@@ -121,14 +160,8 @@ impl RenderKit {
             &resolution_bind_group_layout,
             0,
         );
-        let vs_shader = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Vertex Shader"),
-            source: wgpu::ShaderSource::Wgsl(vs_source.into()),
-        });
-        let fs_shader = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Fragment Shader"),
-            source: wgpu::ShaderSource::Wgsl(fs_source.into()),
-        });
+        let vs_shader = core.create_shader_module(vs_source, "Vertex Shader");
+        let fs_shader = core.create_shader_module(fs_source, "Fragment Shader");
         let texture_bind_group_layout = core.device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
                 entries: &[
@@ -163,7 +196,8 @@ impl RenderKit {
             &fs_shader,
             core.config.format,
             &pipeline_layout,
-            fragment_entry, 
+            fragment_entry,
+            core.pipeline_cache.as_ref(),
         );
         let context = egui::Context::default();
         let egui_state = egui_winit::State::new(
@@ -186,6 +220,7 @@ impl RenderKit {
         let texture_manager = Self::create_default_texture_manager(core, &texture_bind_group_layout);
         let fps_tracker = fps::FpsTracker::new();
         let mouse_tracker = MouseTracker::new();
+        let channels = ChannelSet::new(&core.device, &core.queue, &texture_bind_group_layout);
 
         Self {
             renderer,
@@ -197,6 +232,12 @@ impl RenderKit {
             webcam_texture_manager: None,
             #[cfg(feature = "media")]
             using_webcam_texture: false,
+            #[cfg(feature = "media")]
+            webcam_config: WebcamConfig::default(),
+            #[cfg(feature = "media")]
+            audio_file_manager: None,
+            #[cfg(feature = "media")]
+            audio_texture: None,
             texture_manager: Some(texture_manager),
             egui_renderer,
             egui_state,
@@ -209,14 +250,26 @@ impl RenderKit {
             export_manager: ExportManager::new(),
             controls: ShaderControls::new(),
             spectrum_analyzer: SpectrumAnalyzer::new(),
+            audio_features: AudioFeatures::default(),
+            audio_features_uniform: None,
+            audio_features_bind_group_layout: None,
             compute_shader: None,
             fps_tracker,
             mouse_tracker,
             mouse_uniform: None,
             mouse_bind_group_layout: None,
+            pointer_uniform: None,
+            pointer_bind_group_layout: None,
+            keyboard: KeyboardState::new(),
+            keyboard_texture: None,
+            keyboard_bind_group_layout: None,
             using_hdri_texture: false,
             hdri_metadata: None,
             hdri_file_data: None,
+            hdri_texture: None,
+            params_file: None,
+            channels,
+            image_sequence: None,
         }
     }
 
@@ -349,6 +402,11 @@ impl RenderKit {
             .and_then(|ext| ext.to_str())
             .map(|ext| ext.to_lowercase());
         
+        // Directories, `frame_%04d.png` patterns and animated GIF/WebP play as sequences
+        if path_ref.is_dir() || path_ref.to_string_lossy().contains('%') || is_animated(path_ref) {
+            return self.load_image_sequence(core, path_ref, None);
+        }
+        
         match extension {
             // Image formats
             Some(ext) if ["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"].contains(&ext.as_str()) => {
@@ -362,6 +420,7 @@ impl RenderKit {
                         &self.texture_bind_group_layout,
                     );
                     self.texture_manager = Some(new_texture_manager);
+                    self.image_sequence = None;
                     #[cfg(feature = "media")]
                     {
                         self.using_video_texture = false;
@@ -379,15 +438,18 @@ impl RenderKit {
                 let file_data = std::fs::read(path_ref)?;
                 self.hdri_file_data = Some(file_data.clone());
                 let default_exposure = 1.0;
-                match load_hdri_texture(
-                    &core.device,
-                    &core.queue,
+                match HdriTexture::new(
+                    core,
                     &file_data,
                     &self.texture_bind_group_layout,
-                    default_exposure
+                    default_exposure,
+                    wgpu::TextureFormat::Rgba16Float,
                 ) {
-                    Ok((texture_manager, metadata)) => {
-                        self.texture_manager = Some(texture_manager);
+                    Ok(hdri) => {
+                        let metadata = hdri.metadata;
+                        self.texture_manager = Some(hdri.display.clone());
+                        self.image_sequence = None;
+                        self.hdri_texture = Some(hdri);
                         #[cfg(feature = "media")]
                         {
                             self.using_video_texture = false;
@@ -406,17 +468,22 @@ impl RenderKit {
                 }
             },
             #[cfg(feature = "media")]
+            Some(ext) if ["wav", "flac", "mp3", "ogg", "oga"].contains(&ext.as_str()) => {
+                self.load_audio_file(path_ref)
+            },
+            #[cfg(feature = "media")]
             Some(ext) if ["mp4", "avi", "mkv", "mov", "webm"].contains(&ext.as_str()) => {
                 info!("Loading video: {:?}", path_ref);
                 match VideoTextureManager::new(
-                    &core.device,
-                    &core.queue,
+                    core,
                     &self.texture_bind_group_layout,
                     path_ref,
                 ) {
                     Ok(video_manager) => {
                         self.video_texture_manager = Some(video_manager);
                         self.using_video_texture = true;
+                        self.audio_features.reset();
+                        self.image_sequence = None;
                         self.using_webcam_texture = false;
                         self.webcam_texture_manager = None;
                         if let Err(e) = self.play_video() {
@@ -454,6 +521,12 @@ impl RenderKit {
     }
     #[cfg(feature = "media")]
     pub fn play_video(&mut self) -> anyhow::Result<()> {
+        if let Some(audio) = &mut self.audio_file_manager {
+            audio.play()?;
+        }
+        if let Some(sequence) = &mut self.image_sequence {
+            sequence.play();
+        }
         if let Some(video_manager) = &mut self.video_texture_manager {
             video_manager.play()?;
         }
@@ -461,6 +534,12 @@ impl RenderKit {
     }
     #[cfg(feature = "media")]
     pub fn pause_video(&mut self) -> anyhow::Result<()> {
+        if let Some(audio) = &mut self.audio_file_manager {
+            audio.pause()?;
+        }
+        if let Some(sequence) = &mut self.image_sequence {
+            sequence.pause();
+        }
         if let Some(video_manager) = &mut self.video_texture_manager {
             video_manager.pause()?;
         }
@@ -468,6 +547,12 @@ impl RenderKit {
     }
     #[cfg(feature = "media")]
     pub fn seek_video(&mut self, position_seconds: f64) -> anyhow::Result<()> {
+        if let Some(audio) = &mut self.audio_file_manager {
+            audio.seek(gstreamer::ClockTime::from_nseconds((position_seconds.max(0.0) * 1e9) as u64))?;
+        }
+        if let Some(sequence) = &mut self.image_sequence {
+            sequence.seek(position_seconds as f32);
+        }
         if let Some(video_manager) = &mut self.video_texture_manager {
             let position = gstreamer::ClockTime::from_seconds(position_seconds as u64);
             video_manager.seek(position)?;
@@ -475,21 +560,112 @@ impl RenderKit {
         Ok(())
     }
     
+    /// Frame-accurate video for export: blocks until the frame at `time` seconds is
+    /// decoded and uploaded, and lines the audio spectrum up with it.
+    #[cfg(feature = "media")]
+    pub fn step_video_to(&mut self, core: &Core, time: f32) -> bool {
+        if self.using_video_texture {
+            if let Some(video_manager) = &mut self.video_texture_manager {
+                let position = gstreamer::ClockTime::from_nseconds((time.max(0.0) as f64 * 1e9) as u64);
+                match video_manager.step_to(&core.device, &core.queue, &self.texture_bind_group_layout, position) {
+                    Ok(updated) => return updated,
+                    Err(e) => warn!("Failed to step video: {}", e),
+                }
+            }
+        }
+        false
+    }
+    /// Decodes the loaded video's or audio file's track for the whole export range into the
+    /// export's audio, sample-aligned with the frames. Call right after `start_export`.
+    #[cfg(feature = "media")]
+    pub fn render_media_export_audio(&mut self) -> bool {
+        let settings = self.export_manager.settings().clone();
+        if !settings.export_audio {
+            return false;
+        }
+        let path = match (&self.audio_file_manager, &self.video_texture_manager) {
+            (Some(audio), _) => audio.path().to_string(),
+            (None, Some(video)) if self.using_video_texture && video.has_audio() => video.path().to_string(),
+            _ => return false,
+        };
+        let frames = self.export_manager.audio_frames_for(self.export_manager.total_frames()).start;
+        let duration = frames as f64 / settings.audio_sample_rate as f64;
+        match crate::gst::offline::decode_audio(
+            &path,
+            settings.start_time as f64,
+            duration,
+            settings.audio_sample_rate,
+            settings.audio_channels as u32,
+        ) {
+            Ok(samples) => {
+                self.export_manager.push_audio(&samples);
+                true
+            }
+            Err(e) => {
+                warn!("Failed to render export audio: {}", e);
+                false
+            }
+        }
+    }
+    /// Renders the audio `synth` plays during export frame `frame` offline and appends it to
+    /// the export. Set the voices for the frame first; call once per frame.
+    #[cfg(feature = "media")]
+    pub fn push_synth_export_audio(&mut self, synth: &mut crate::gst::audio::SynthesisManager, frame: u32) {
+        let settings = self.export_manager.settings();
+        if !settings.export_audio {
+            return;
+        }
+        let channels = settings.audio_channels as usize;
+        let range = self.export_manager.audio_frames_for(frame);
+        let samples = synth.render_offline((range.end - range.start) as usize, channels);
+        self.export_manager.push_audio(&samples);
+    }
+    /// Returns the video to real-time playback after an export
+    #[cfg(feature = "media")]
+    pub fn end_video_stepping(&mut self) {
+        if let Some(video_manager) = &mut self.video_texture_manager {
+            if let Err(e) = video_manager.set_stepped(false) {
+                warn!("Failed to leave stepped video mode: {}", e);
+            }
+        }
+    }
+    
     #[cfg(feature = "media")]
     pub fn set_video_loop(&mut self, should_loop: bool) {
+        if let Some(audio) = &mut self.audio_file_manager {
+            audio.set_loop(should_loop);
+        }
+        if let Some(sequence) = &mut self.image_sequence {
+            sequence.set_loop(should_loop);
+        }
         if let Some(video_manager) = &mut self.video_texture_manager {
             video_manager.set_loop(should_loop);
         }
     }
     
+    /// Loads an audio file as the audio-reactive source. The current image/video stays as is.
+    #[cfg(feature = "media")]
+    pub fn load_audio_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        let mut audio = AudioFileManager::new(path)?;
+        audio.set_loop(true);
+        audio.play()?;
+        self.audio_file_manager = Some(audio);
+        self.audio_features.reset();
+        Ok(())
+    }
+    
+    /// Starts capture with `webcam_config`; a `device_index` overrides its source
     #[cfg(feature = "media")]
     pub fn start_webcam(&mut self, core: &Core, device_index: Option<u32>) -> anyhow::Result<()> {
         info!("Starting webcam");
-        let webcam_manager = WebcamTextureManager::new(
-            &core.device,
-            &core.queue,
+        let mut config = self.webcam_config.clone();
+        if device_index.is_some() {
+            config.source = WebcamSource::Device(device_index);
+        }
+        let webcam_manager = WebcamTextureManager::with_config(
+            core,
             &self.texture_bind_group_layout,
-            device_index,
+            config,
         )?;
         
         let mut manager = webcam_manager;
@@ -500,6 +676,8 @@ impl RenderKit {
         self.using_video_texture = false;
         self.video_texture_manager = None;
         self.using_hdri_texture = false;
+        self.hdri_texture = None;
+        self.image_sequence = None;
         
         Ok(())
     }
@@ -530,6 +708,39 @@ impl RenderKit {
         }
         false
     }
+    /// Loads an image sequence into `texture_manager`; `fps` of `None` uses the file's own timing (or 24 fps).
+    pub fn load_image_sequence(&mut self, core: &Core, path: &Path, fps: Option<f32>) -> anyhow::Result<()> {
+        info!("Loading image sequence: {:?}", path);
+        let sequence = ImageSequence::new(
+            &core.device,
+            &core.queue,
+            &self.texture_bind_group_layout,
+            path,
+            fps,
+        )?;
+        self.texture_manager = Some(sequence.texture_manager().clone());
+        self.image_sequence = Some(sequence);
+        self.using_hdri_texture = false;
+        self.hdri_texture = None;
+        #[cfg(feature = "media")]
+        {
+            self.using_video_texture = false;
+            self.video_texture_manager = None;
+            self.using_webcam_texture = false;
+            self.webcam_texture_manager = None;
+        }
+        Ok(())
+    }
+    /// Advances the image sequence, if one is loaded. Returns true when `texture_manager` got a new frame.
+    pub fn update_image_sequence(&mut self, core: &Core) -> bool {
+        if let Some(sequence) = &mut self.image_sequence {
+            if sequence.update(&core.device, &core.queue, &self.texture_bind_group_layout) {
+                self.texture_manager = Some(sequence.texture_manager().clone());
+                return true;
+            }
+        }
+        false
+    }
     pub fn load_image(&mut self, core: &Core, path: std::path::PathBuf) {
         if let Ok(img) = image::open(path) {
             let rgba_image = img.into_rgba8();
@@ -540,6 +751,7 @@ impl RenderKit {
                 &self.texture_bind_group_layout,
             );
             self.texture_manager = Some(new_texture_manager);
+            self.image_sequence = None;
             #[cfg(feature = "media")]
             {
                 self.using_video_texture = false;
@@ -590,12 +802,38 @@ impl RenderKit {
     }
     #[cfg(feature = "media")]
     pub fn update_audio_spectrum(&mut self, queue: &wgpu::Queue) {
-        self.spectrum_analyzer.update_spectrum(
-            queue,
-            &mut self.resolution_uniform,
-            &self.video_texture_manager,
-            self.using_video_texture,
-        );
+        let (source, level) = if let Some(audio) = &mut self.audio_file_manager {
+            audio.update();
+            (Some((audio.spectrum_data(), audio.get_bpm())), Some(audio.level_data()))
+        } else {
+            match self.video_texture_manager.as_ref().filter(|vm| self.using_video_texture && vm.has_audio()) {
+                Some(vm) => (Some((vm.spectrum_data(), vm.get_bpm())), Some(vm.level_data())),
+                None => (None, None),
+            }
+        };
+        if let Some((spectrum, bpm)) = &source {
+            self.audio_features.update(spectrum, level.as_ref(), *bpm);
+            self.update_audio_features_uniform(queue);
+        }
+        if let Some(audio_texture) = &mut self.audio_texture {
+            let (left, right, rate) = if let Some(audio) = &self.audio_file_manager {
+                let (left, right) = audio.waveform_channels();
+                (left, right, audio.audio_sample_rate())
+            } else {
+                match self.video_texture_manager.as_ref().filter(|vm| self.using_video_texture && vm.has_audio()) {
+                    Some(vm) => {
+                        let (left, right) = vm.waveform_channels();
+                        (left, right, vm.audio_sample_rate())
+                    }
+                    None => (Vec::new(), Vec::new(), None),
+                }
+            };
+            if let Some(rate) = rate {
+                audio_texture.sample_rate = rate as f32;
+            }
+            audio_texture.update(queue, source.as_ref().map(|(spectrum, _)| spectrum), &left, &right);
+        }
+        self.spectrum_analyzer.update_from_source(queue, &mut self.resolution_uniform, source);
     }
     #[cfg(feature = "media")]
     pub fn handle_video_requests(&mut self, core: &Core, request: &ControlsRequest) {
@@ -631,17 +869,26 @@ impl RenderKit {
             if let Some(vm) = &mut self.video_texture_manager {
                 let _ = vm.set_volume(volume);
             }
+            if let Some(audio) = &mut self.audio_file_manager {
+                let _ = audio.set_volume(volume);
+            }
         }
         
         if let Some(muted) = request.mute_audio {
             if let Some(vm) = &mut self.video_texture_manager {
                 let _ = vm.set_mute(muted);
             }
+            if let Some(audio) = &mut self.audio_file_manager {
+                let _ = audio.set_mute(muted);
+            }
         }
         if request.toggle_mute {
             if let Some(vm) = &mut self.video_texture_manager {
                 let _ = vm.toggle_mute();
             }
+            if let Some(audio) = &mut self.audio_file_manager {
+                let _ = audio.toggle_mute();
+            }
         }
     }
     
@@ -658,44 +905,35 @@ impl RenderKit {
                 error!("Failed to stop webcam: {}", e);
             }
         }
+
+        if request.toggle_webcam_mirror {
+            self.webcam_config.mirror = !self.webcam_config.mirror;
+            if let Some(webcam_manager) = &mut self.webcam_texture_manager {
+                webcam_manager.set_mirror(self.webcam_config.mirror);
+            }
+        }
     }
     pub fn handle_hdri_requests(&mut self, core: &Core, request: &ControlsRequest) -> bool {
         if !self.using_hdri_texture {
             return false;
         }
         let mut updated = false;
-        let mut new_exposure = None;
-        let mut new_gamma = None;
         if let (Some(exposure), Some(hdri_meta)) = (request.hdri_exposure, &mut self.hdri_metadata) {
             if (exposure - hdri_meta.exposure).abs() > 0.001 {
                 hdri_meta.exposure = exposure;
-                new_exposure = Some(exposure);
                 updated = true;
             }
         }
         if let (Some(gamma), Some(hdri_meta)) = (request.hdri_gamma, &mut self.hdri_metadata) {
             if (gamma - hdri_meta.gamma).abs() > 0.001 {
                 hdri_meta.gamma = gamma;
-                new_gamma = Some(gamma);
                 updated = true;
             }
         }
         if updated {
-            if let (Some(hdri_data), Some(texture_manager)) = (&self.hdri_file_data, &mut self.texture_manager) {
-                let exposure = new_exposure.unwrap_or_else(|| {
-                    self.hdri_metadata.map(|meta| meta.exposure).unwrap_or(1.0)
-                });
-                if let Err(e) = crate::update_hdri_exposure(
-                    &core.device,
-                    &core.queue,
-                    hdri_data,
-                    &self.texture_bind_group_layout,
-                    texture_manager,
-                    exposure,
-                    new_gamma
-                ) {
-                    error!("Failed to update HDRI parameters: {}", e);
-                }
+            // Exposure/gamma live in a uniform; only the GPU display pass is re-run
+            if let (Some(hdri), Some(meta)) = (&mut self.hdri_texture, self.hdri_metadata) {
+                hdri.set_exposure_gamma(&core.device, &core.queue, meta.exposure, meta.gamma);
             }
         }
         updated
@@ -730,10 +968,7 @@ impl RenderKit {
     pub fn enable_compute_hot_reload(&mut self, core: &Core, shader_path: &Path) -> Result<(), notify::Error> {
         if let Some(compute_shader) = &mut self.compute_shader {
             let shader_source = std::fs::read_to_string(shader_path)?;
-            let shader_module = core.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("Compute Shader Hot Reload"),
-                source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-            });
+            let shader_module = core.create_shader_module(&shader_source, "Compute Shader Hot Reload");
             compute_shader.enable_hot_reload(
                 core.device.clone(),
                 shader_path.to_path_buf(),
@@ -772,6 +1007,18 @@ impl RenderKit {
     /// Get video information if a video texture is loaded
     #[cfg(feature = "media")]
     pub fn get_video_info(&self) -> Option<(Option<f32>, f32, (u32, u32), Option<f32>, bool, bool, f64, bool)> {
+        if let Some(sequence) = &self.image_sequence {
+            return Some((
+                sequence.duration(),
+                sequence.position(),
+                sequence.dimensions(),
+                Some(sequence.fps()),
+                sequence.is_looping(),
+                false,
+                0.0,
+                false,
+            ));
+        }
         if self.using_video_texture {
             if let Some(vm) = &self.video_texture_manager {
                 Some((
@@ -787,6 +1034,18 @@ impl RenderKit {
             } else {
                 None
             }
+        } else if let Some(audio) = &self.audio_file_manager {
+            // Audio-only input shares the video timeline/volume controls
+            Some((
+                audio.duration().map(|d| d.mseconds() as f32 / 1000.0),
+                audio.position().mseconds() as f32 / 1000.0,
+                (0, 0),
+                None,
+                audio.is_looping(),
+                true,
+                audio.volume(),
+                audio.is_muted()
+            ))
         } else {
             None
         }
@@ -833,6 +1092,52 @@ impl RenderKit {
         }
     }
     
+    /// Creates `audio_texture`, bound like any other input texture (`texture_bind_group_layout`).
+    /// `update_audio_spectrum` refreshes it every frame.
+    #[cfg(feature = "media")]
+    pub fn setup_audio_texture(&mut self, core: &Core) {
+        if self.audio_texture.is_none() {
+            self.audio_texture = Some(AudioTexture::new(&core.device, &self.texture_bind_group_layout));
+        }
+    }
+
+    /// Creates the `AudioFeaturesUniform` binding (`@binding(0)`), kept up to date by `update_audio_spectrum`
+    pub fn setup_audio_features_uniform(&mut self, core: &Core) {
+        if self.audio_features_uniform.is_none() {
+            let layout = core.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("audio_features_bind_group_layout"),
+            });
+
+            let uniform = UniformBinding::new(
+                &core.device,
+                "Audio Features Uniform",
+                self.audio_features.uniform,
+                &layout,
+                0,
+            );
+
+            self.audio_features_bind_group_layout = Some(layout);
+            self.audio_features_uniform = Some(uniform);
+        }
+    }
+
+    pub fn update_audio_features_uniform(&mut self, queue: &wgpu::Queue) {
+        if let Some(uniform) = &mut self.audio_features_uniform {
+            uniform.data = self.audio_features.uniform;
+            uniform.update(queue);
+        }
+    }
+
     pub fn update_mouse_uniform(&mut self, queue: &wgpu::Queue) {
         if let Some(mouse_uniform) = &mut self.mouse_uniform {
             mouse_uniform.data = self.mouse_tracker.uniform;
@@ -840,6 +1145,45 @@ impl RenderKit {
         }
     }
     
+    /// Creates the `PointerUniform` binding (`@binding(0)`); `update_pointer_uniform` uploads it
+    pub fn setup_pointer_uniform(&mut self, core: &Core) {
+        if self.pointer_uniform.is_none() {
+            let layout = core.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("pointer_bind_group_layout"),
+            });
+
+            let uniform = UniformBinding::new(
+                &core.device,
+                "Pointer Uniform",
+                self.mouse_tracker.pointer,
+                &layout,
+                0,
+            );
+
+            self.pointer_bind_group_layout = Some(layout);
+            self.pointer_uniform = Some(uniform);
+        }
+    }
+
+    /// Uploads the pointer state, then clears its per-frame deltas and edges. Call once per frame.
+    pub fn update_pointer_uniform(&mut self, queue: &wgpu::Queue) {
+        if let Some(pointer_uniform) = &mut self.pointer_uniform {
+            pointer_uniform.data = self.mouse_tracker.pointer;
+            pointer_uniform.update(queue);
+        }
+        self.mouse_tracker.end_frame();
+    }
+
     pub fn handle_mouse_input(&mut self, core: &Core, event: &WindowEvent, ui_handled: bool) -> bool {
         let window_size = [
             core.size.width as f32,
@@ -848,4 +1192,75 @@ impl RenderKit {
         
         self.mouse_tracker.handle_mouse_input(event, window_size, ui_handled)
     }
+
+    /// Creates `keyboard_texture` and its layout (`keyboard_bind_group_layout`, visible to
+    /// fragment and compute stages). `update_keyboard_texture` uploads it every frame.
+    pub fn setup_keyboard_texture(&mut self, core: &Core) {
+        if self.keyboard_texture.is_none() {
+            let layout = create_keyboard_bind_group_layout(
+                &core.device,
+                wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+            );
+            self.keyboard_texture = Some(KeyboardTexture::new(&core.device, &layout));
+            self.keyboard_bind_group_layout = Some(layout);
+        }
+    }
+
+    /// Uploads `keyboard` and starts a new frame, so "pressed" lasts exactly one upload
+    pub fn update_keyboard_texture(&mut self, queue: &wgpu::Queue) {
+        if let Some(keyboard_texture) = &self.keyboard_texture {
+            keyboard_texture.update(queue, &self.keyboard);
+        }
+        self.keyboard.end_frame();
+    }
+
+    /// Records key and focus events into `keyboard`. Never consumes the event, so the
+    /// `key_handler` shortcuts and a bin's own key handling keep working.
+    pub fn handle_keyboard_state(&mut self, event: &WindowEvent, ui_handled: bool) {
+        self.keyboard.handle_window_event(event, ui_handled);
+    }
+
+    /// Loads media into `iChannel{index}` without touching the other channels.
+    pub fn load_channel_media<P: AsRef<Path>>(&mut self, core: &Core, index: usize, path: P) -> anyhow::Result<()> {
+        self.channels.load_media(core, index, path)
+    }
+
+    /// Refreshes channel textures and their resolution/time uniforms; call once per frame.
+    pub fn update_channels(&mut self, core: &Core) {
+        let time = self.controls.get_time(&self.start_time);
+        self.channels.update(core, time);
+    }
+
+    /// Watch `<shader>.toml` next to the shader for parameter values.
+    pub fn enable_params_hot_reload(&mut self, shader_path: &Path) -> notify::Result<()> {
+        let params_path = ParamsFile::sidecar_path(shader_path);
+        info!("Watching parameters file: {}", params_path.display());
+        self.params_file = Some(ParamsFile::new(params_path)?);
+        Ok(())
+    }
+
+    /// Applies on-disk parameter edits to the uniform and uploads it. Returns true if anything changed.
+    pub fn sync_params<T>(&mut self, queue: &wgpu::Queue, binding: &mut UniformBinding<T>) -> bool
+    where
+        T: UniformProvider + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let Some(params_file) = &mut self.params_file else {
+            return false;
+        };
+        if params_file.poll(&mut binding.data) {
+            binding.update(queue);
+            return true;
+        }
+        false
+    }
+
+    /// Writes UI-edited parameters back to the sidecar file. Cheap to call every frame: the
+    /// write happens in `sync_params` once the values have settled.
+    pub fn save_params<T: serde::Serialize>(&mut self, params: &T) {
+        if let Some(params_file) = &mut self.params_file {
+            if let Err(e) = params_file.queue_save(params) {
+                error!("Failed to save parameters to {}: {}", params_file.path().display(), e);
+            }
+        }
+    }
 }
\ No newline at end of file
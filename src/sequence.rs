@@ -0,0 +1,429 @@
+// Image sequences (numbered files or a directory) and animated GIF/WebP, played back like a video.
+// Frames are decoded on a worker thread one at a time (plus one frame of prefetch), so long
+// sequences never sit in memory all at once.
+use anyhow::{anyhow, Result};
+use image::AnimationDecoder;
+use log::{info, warn};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::time::Instant;
+use crate::TextureManager;
+
+const SEQUENCE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "tiff", "tif", "webp", "exr", "hdr", "tga"];
+
+#[derive(Debug, Clone)]
+pub enum SequenceSource {
+    /// One file per frame, in playback order
+    Files(Vec<PathBuf>),
+    /// Animated GIF or WebP, decoded frame by frame
+    Animated(PathBuf),
+}
+
+impl SequenceSource {
+    /// Resolves a directory, a printf-style pattern (`frames/frame_%04d.png`) or an animated GIF/WebP.
+    pub fn resolve(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && has_sequence_extension(p))
+                .collect();
+            files.sort();
+            if files.is_empty() {
+                return Err(anyhow!("No image files in {}", path.display()));
+            }
+            return Ok(Self::Files(files));
+        }
+
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if file_name.contains('%') {
+            return Self::from_pattern(path, file_name);
+        }
+
+        if is_animated(path) {
+            return Ok(Self::Animated(path.to_path_buf()));
+        }
+        Err(anyhow!("Not an image sequence: {}", path.display()))
+    }
+
+    /// Matches every file in the pattern's directory against `prefix%0Nd suffix`, sorted by frame number.
+    /// Any start frame and gaps are allowed.
+    fn from_pattern(path: &Path, file_name: &str) -> Result<Self> {
+        let percent = file_name.find('%').unwrap_or(0);
+        let prefix = &file_name[..percent];
+        let spec = &file_name[percent + 1..];
+        let d = spec.find('d').ok_or_else(|| anyhow!("Unsupported sequence pattern: {}", file_name))?;
+        if !spec[..d].chars().all(|c| c.is_ascii_digit()) {
+            return Err(anyhow!("Unsupported sequence pattern: {}", file_name));
+        }
+        let suffix = &spec[d + 1..];
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let mut frames: Vec<(u64, PathBuf)> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let digits = name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                Some((digits.parse().ok()?, entry.path()))
+            })
+            .collect();
+        frames.sort_by_key(|(number, _)| *number);
+        if frames.is_empty() {
+            return Err(anyhow!("No files match {}", path.display()));
+        }
+        Ok(Self::Files(frames.into_iter().map(|(_, p)| p).collect()))
+    }
+
+    /// Frame count if it is known without decoding (animated files learn it on the first pass)
+    fn known_len(&self) -> Option<usize> {
+        match self {
+            Self::Files(files) => Some(files.len()),
+            Self::Animated(_) => None,
+        }
+    }
+}
+
+fn has_sequence_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SEQUENCE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn extension_of(path: &Path) -> String {
+    path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase()
+}
+
+/// GIFs always go through the sequence player; WebPs only when they carry an animation.
+pub fn is_animated(path: &Path) -> bool {
+    match extension_of(path).as_str() {
+        "gif" => true,
+        "webp" => File::open(path)
+            .ok()
+            .and_then(|f| image::codecs::webp::WebPDecoder::new(BufReader::new(f)).ok())
+            .map(|d| d.has_animation())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+type AnimatedFrames = image::Frames<'static>;
+
+fn open_animation(path: &Path) -> Result<AnimatedFrames> {
+    let reader = BufReader::new(File::open(path)?);
+    let frames = match extension_of(path).as_str() {
+        "gif" => image::codecs::gif::GifDecoder::new(reader)?.into_frames(),
+        "webp" => image::codecs::webp::WebPDecoder::new(reader)?.into_frames(),
+        ext => return Err(anyhow!("Unsupported animation format: {}", ext)),
+    };
+    Ok(frames)
+}
+
+enum WorkerReply {
+    Frame(usize, image::RgbaImage),
+    /// The animation ended before the requested frame; it has this many frames
+    Length(usize),
+    Error(usize, String),
+}
+
+/// Decodes requested frames off the render thread and prefetches the one after.
+struct FrameWorker {
+    source: SequenceSource,
+    animation: Option<AnimatedFrames>,
+    next_animated: usize,
+    prefetched: Option<(usize, image::RgbaImage)>,
+}
+
+impl FrameWorker {
+    fn decode(&mut self, index: usize) -> std::result::Result<Option<image::RgbaImage>, String> {
+        if let Some((prefetched, _)) = &self.prefetched {
+            if *prefetched == index {
+                return Ok(self.prefetched.take().map(|(_, img)| img));
+            }
+        }
+        match &self.source {
+            SequenceSource::Files(files) => match files.get(index) {
+                Some(path) => image::open(path)
+                    .map(|img| Some(img.into_rgba8()))
+                    .map_err(|e| format!("{}: {}", path.display(), e)),
+                None => Ok(None),
+            },
+            SequenceSource::Animated(path) => {
+                // Scrubbing backwards restarts the decoder
+                if self.animation.is_none() || index < self.next_animated {
+                    self.animation = Some(open_animation(path).map_err(|e| e.to_string())?);
+                    self.next_animated = 0;
+                }
+                let frames = self.animation.as_mut().unwrap();
+                while self.next_animated <= index {
+                    let frame = match frames.next() {
+                        Some(frame) => frame.map_err(|e| e.to_string())?,
+                        None => return Ok(None),
+                    };
+                    self.next_animated += 1;
+                    if self.next_animated > index {
+                        return Ok(Some(frame.into_buffer()));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn run(mut self, requests: Receiver<usize>, replies: Sender<WorkerReply>) {
+        loop {
+            // Only the newest request matters when scrubbing
+            let mut index = match requests.recv() {
+                Ok(index) => index,
+                Err(_) => return,
+            };
+            while let Ok(newer) = requests.try_recv() {
+                index = newer;
+            }
+
+            let reply = match self.decode(index) {
+                Ok(Some(frame)) => WorkerReply::Frame(index, frame),
+                Ok(None) => WorkerReply::Length(match &self.source {
+                    SequenceSource::Files(files) => files.len(),
+                    SequenceSource::Animated(_) => self.next_animated,
+                }),
+                Err(e) => WorkerReply::Error(index, e),
+            };
+            let decoded = matches!(reply, WorkerReply::Frame(..));
+            if replies.send(reply).is_err() {
+                return;
+            }
+
+            if decoded {
+                match self.decode(index + 1) {
+                    Ok(Some(frame)) => self.prefetched = Some((index + 1, frame)),
+                    _ => self.prefetched = None,
+                }
+            }
+        }
+    }
+}
+
+/// Plays an image sequence or animated image into a `TextureManager`.
+pub struct ImageSequence {
+    source: SequenceSource,
+    texture_manager: TextureManager,
+    dimensions: (u32, u32),
+    fps: f32,
+    frame_count: Option<usize>,
+    position: f32,
+    is_playing: bool,
+    loop_playback: bool,
+    last_tick: Instant,
+    shown_frame: usize,
+    requested_frame: Option<usize>,
+    request_tx: Sender<usize>,
+    reply_rx: Receiver<WorkerReply>,
+}
+
+impl ImageSequence {
+    /// Opens `path` (directory, `%0Nd` pattern, or animated GIF/WebP). `fps` of `None` uses the
+    /// animation's own frame delay, or 24 fps for file sequences.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        path: &Path,
+        fps: Option<f32>,
+    ) -> Result<Self> {
+        let source = SequenceSource::resolve(path)?;
+
+        // Decode the first frame up front so the texture starts at the right size
+        let (first, native_fps) = match &source {
+            SequenceSource::Files(files) => (image::open(&files[0])?.into_rgba8(), None),
+            SequenceSource::Animated(path) => {
+                let frame = open_animation(path)?
+                    .next()
+                    .ok_or_else(|| anyhow!("Animation has no frames"))??;
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let native = (numer > 0).then(|| 1000.0 * denom as f32 / numer as f32);
+                (frame.into_buffer(), native)
+            }
+        };
+        let fps = fps.or(native_fps).unwrap_or(24.0).max(0.001);
+        let texture_manager = TextureManager::new(device, queue, &first, bind_group_layout);
+        info!(
+            "Loaded image sequence {} ({}x{}, {} frames, {:.2} fps)",
+            path.display(),
+            first.width(),
+            first.height(),
+            source.known_len().map(|n| n.to_string()).unwrap_or_else(|| "?".to_string()),
+            fps
+        );
+
+        let (request_tx, request_rx) = channel();
+        let (reply_tx, reply_rx) = channel();
+        let worker_source = source.clone();
+        // The animation decoder isn't Send, so the worker is built on its own thread
+        std::thread::spawn(move || {
+            let worker = FrameWorker {
+                source: worker_source,
+                animation: None,
+                next_animated: 0,
+                prefetched: None,
+            };
+            worker.run(request_rx, reply_tx)
+        });
+
+        Ok(Self {
+            frame_count: source.known_len(),
+            source,
+            texture_manager,
+            dimensions: first.dimensions(),
+            fps,
+            position: 0.0,
+            is_playing: true,
+            loop_playback: true,
+            last_tick: Instant::now(),
+            shown_frame: 0,
+            requested_frame: Some(0),
+            request_tx,
+            reply_rx,
+        })
+    }
+
+    /// Advances playback and uploads any newly decoded frame. Returns true if the texture changed.
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> bool {
+        let now = Instant::now();
+        if self.is_playing {
+            self.position += now.duration_since(self.last_tick).as_secs_f32();
+        }
+        self.last_tick = now;
+        self.wrap_position();
+
+        let mut updated = false;
+        loop {
+            match self.reply_rx.try_recv() {
+                Ok(WorkerReply::Frame(index, frame)) => {
+                    if self.dimensions != frame.dimensions() {
+                        self.texture_manager = TextureManager::new(device, queue, &frame, bind_group_layout);
+                        self.dimensions = frame.dimensions();
+                    } else {
+                        self.texture_manager.update(queue, &frame);
+                    }
+                    self.shown_frame = index;
+                    updated = true;
+                }
+                Ok(WorkerReply::Length(len)) => {
+                    info!("Image sequence has {} frames", len);
+                    self.frame_count = Some(len.max(1));
+                    self.requested_frame = None;
+                    self.wrap_position();
+                }
+                Ok(WorkerReply::Error(index, e)) => {
+                    warn!("Failed to decode sequence frame {}: {}", index, e);
+                    self.shown_frame = index;
+                }
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+
+        let target = self.current_frame();
+        if self.requested_frame != Some(target) {
+            self.requested_frame = Some(target);
+            if target != self.shown_frame {
+                let _ = self.request_tx.send(target);
+            }
+        }
+        updated
+    }
+
+    fn wrap_position(&mut self) {
+        let Some(duration) = self.duration() else {
+            return;
+        };
+        if self.position >= duration {
+            if self.loop_playback {
+                self.position %= duration;
+            } else {
+                self.position = (duration - 0.5 / self.fps).max(0.0);
+                self.is_playing = false;
+            }
+        }
+    }
+
+    /// Index of the frame at the current playback position
+    pub fn current_frame(&self) -> usize {
+        let index = (self.position * self.fps) as usize;
+        match self.frame_count {
+            Some(count) => index.min(count.saturating_sub(1)),
+            None => index,
+        }
+    }
+
+    pub fn texture_manager(&self) -> &TextureManager {
+        &self.texture_manager
+    }
+
+    pub fn play(&mut self) {
+        self.is_playing = true;
+        self.last_tick = Instant::now();
+    }
+
+    pub fn pause(&mut self) {
+        self.is_playing = false;
+    }
+
+    pub fn seek(&mut self, position_seconds: f32) {
+        self.position = position_seconds.max(0.0);
+        self.wrap_position();
+    }
+
+    pub fn set_loop(&mut self, should_loop: bool) {
+        self.loop_playback = should_loop;
+    }
+
+    pub fn set_fps(&mut self, fps: f32) {
+        self.fps = fps.max(0.001);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.is_playing
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.loop_playback
+    }
+
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
+    /// Known once the frame count is (immediately for file sequences, after one pass for animations)
+    pub fn duration(&self) -> Option<f32> {
+        self.frame_count.map(|count| count as f32 / self.fps)
+    }
+
+    pub fn frame_count(&self) -> Option<usize> {
+        self.frame_count
+    }
+
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    pub fn source(&self) -> &SequenceSource {
+        &self.source
+    }
+}
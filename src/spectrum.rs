@@ -6,23 +6,233 @@ use log::info;
 use wgpu;
 
 #[cfg(feature = "media")]
-use crate::gst::video::VideoTextureManager;
+use crate::gst::video::{SpectrumData, VideoTextureManager};
 #[cfg(feature = "media")]
 use crate::UniformBinding;
 #[cfg(feature = "media")]
 use crate::ResolutionUniform;
 
+/// Size of the band buffer; `SpectrumConfig::bands` is clamped to this.
+pub const MAX_SPECTRUM_BANDS: usize = 1024;
+
+/// Bands always covered by `ResolutionUniform::audio_data[0..16]`
+const UNIFORM_BANDS: usize = 64;
+
+/// How output bands are spread between `min_hz` and `max_hz`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrequencyScale {
+    Linear,
+    Log,
+    Mel,
+    Bark,
+}
+
+#[cfg(feature = "media")]
+impl FrequencyScale {
+    fn hz_to_scale(self, hz: f32) -> f32 {
+        match self {
+            Self::Linear => hz,
+            Self::Log => hz.max(1.0).ln(),
+            Self::Mel => 2595.0 * (1.0 + hz / 700.0).log10(),
+            // Traunmüller's approximation
+            Self::Bark => 26.81 * hz / (1960.0 + hz) - 0.53,
+        }
+    }
+
+    fn scale_to_hz(self, value: f32) -> f32 {
+        match self {
+            Self::Linear => value,
+            Self::Log => value.exp(),
+            Self::Mel => 700.0 * (10f32.powf(value / 2595.0) - 1.0),
+            Self::Bark => 1960.0 * (value + 0.53) / (26.28 - value),
+        }
+    }
+
+    fn index(self) -> u32 {
+        match self {
+            Self::Linear => 0,
+            Self::Log => 1,
+            Self::Mel => 2,
+            Self::Bark => 3,
+        }
+    }
+}
+
+/// Per-band gain applied after mapping dB to 0..1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BandNormalization {
+    /// Raw levels
+    None,
+    /// Fixed curves that tame the bass and lift the highs (the original look)
+    Tilt,
+    /// Each band is divided by its own running peak, which falls by `decay` per update,
+    /// so quiet bands use the full range too
+    Adaptive { decay: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpectrumConfig {
+    /// Number of output bands (up to `MAX_SPECTRUM_BANDS`)
+    pub bands: usize,
+    pub scale: FrequencyScale,
+    /// Lowest frequency of the first band (the log scale starts at 1 Hz or above)
+    pub min_hz: f32,
+    pub max_hz: f32,
+    /// Rate used to map Hz onto bins when the spectrum doesn't carry its negotiated rate
+    pub sample_rate: f32,
+    /// Levels at or below this map to 0
+    pub min_db: f32,
+    /// Levels at or above this map to 1
+    pub max_db: f32,
+    /// Smoothing factor (0..1) for rising bands, 1 follows the input immediately
+    pub attack: f32,
+    /// Smoothing factor (0..1) for falling bands
+    pub release: f32,
+    pub normalization: BandNormalization,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            bands: UNIFORM_BANDS,
+            scale: FrequencyScale::Linear,
+            min_hz: 0.0,
+            max_hz: 11025.0,
+            sample_rate: 44100.0,
+            min_db: -60.0,
+            max_db: 0.0,
+            attack: 0.6,
+            release: 0.3,
+            normalization: BandNormalization::Tilt,
+        }
+    }
+}
+
+/// Header of the band storage buffer, followed by `band_count` f32 values:
+/// ```wgsl
+/// struct SpectrumBands { band_count: u32, scale: u32, min_db: f32, max_db: f32, values: array<f32> }
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpectrumBandsHeader {
+    pub band_count: u32,
+    /// 0 linear, 1 log, 2 mel, 3 bark
+    pub scale: u32,
+    pub min_db: f32,
+    pub max_db: f32,
+}
+
+/// Layout for `SpectrumAnalyzer::bind_group`: `@binding(0)` is a read-only `SpectrumBands` storage buffer.
+pub fn create_spectrum_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+        label: Some("spectrum_bands_bind_group_layout"),
+    })
+}
+
+#[cfg(feature = "media")]
+struct SpectrumBuffer {
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
 pub struct SpectrumAnalyzer {
     #[cfg(feature = "media")]
-    prev_audio_data: [[f32; 4]; 32],
+    config: SpectrumConfig,
+    /// Smoothed output bands, also the previous frame for attack/release
+    #[cfg(feature = "media")]
+    bands: Vec<f32>,
+    /// Running per-band peaks for `BandNormalization::Adaptive`
+    #[cfg(feature = "media")]
+    band_peaks: Vec<f32>,
+    #[cfg(feature = "media")]
+    gpu_output: Option<SpectrumBuffer>,
 }
 
 #[cfg(feature = "media")]
 impl SpectrumAnalyzer {
     pub fn new() -> Self {
-        Self {
-            prev_audio_data: [[0.0; 4]; 32],
+        Self::with_config(SpectrumConfig::default())
+    }
+
+    pub fn with_config(config: SpectrumConfig) -> Self {
+        let mut analyzer = Self {
+            config,
+            bands: Vec::new(),
+            band_peaks: Vec::new(),
+            gpu_output: None,
+        };
+        analyzer.set_config(config);
+        analyzer
+    }
+
+    pub fn config(&self) -> &SpectrumConfig {
+        &self.config
+    }
+
+    /// Changes the analysis; smoothing state is reset.
+    pub fn set_config(&mut self, mut config: SpectrumConfig) {
+        config.bands = config.bands.clamp(1, MAX_SPECTRUM_BANDS);
+        config.attack = config.attack.clamp(0.0, 1.0);
+        config.release = config.release.clamp(0.0, 1.0);
+        if config.max_db <= config.min_db {
+            config.max_db = config.min_db + 1.0;
         }
+        self.config = config;
+        self.bands = vec![0.0; config.bands];
+        self.band_peaks = vec![0.0; config.bands];
+    }
+
+    /// Smoothed 0..1 level of each configured band
+    pub fn bands(&self) -> &[f32] {
+        &self.bands
+    }
+
+    /// Creates the band storage buffer; from then on every update also writes it.
+    /// Bind `bind_group()` with a layout from `create_spectrum_bind_group_layout`.
+    pub fn enable_gpu_output(&mut self, device: &wgpu::Device) {
+        if self.gpu_output.is_some() {
+            return;
+        }
+        let size = std::mem::size_of::<SpectrumBandsHeader>() + MAX_SPECTRUM_BANDS * std::mem::size_of::<f32>();
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Spectrum Bands Buffer"),
+            size: size as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = create_spectrum_bind_group_layout(device);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("Spectrum Bands Bind Group"),
+        });
+        self.gpu_output = Some(SpectrumBuffer {
+            buffer,
+            bind_group_layout,
+            bind_group,
+        });
+    }
+
+    pub fn bind_group_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.gpu_output.as_ref().map(|output| &output.bind_group_layout)
+    }
+
+    pub fn bind_group(&self) -> Option<&wgpu::BindGroup> {
+        self.gpu_output.as_ref().map(|output| &output.bind_group)
     }
 
     pub fn update_spectrum(
@@ -31,6 +241,96 @@ impl SpectrumAnalyzer {
         resolution_uniform: &mut UniformBinding<ResolutionUniform>,
         video_texture_manager: &Option<VideoTextureManager>,
         using_video_texture: bool,
+    ) {
+        let source = if using_video_texture {
+            video_texture_manager.as_ref()
+                .filter(|vm| vm.has_audio())
+                .map(|vm| (vm.spectrum_data(), vm.get_bpm()))
+        } else {
+            None
+        };
+        self.update_from_source(queue, resolution_uniform, source);
+    }
+
+    /// Source bin range `[start, end)` covered by output band `band`
+    fn bin_range(&self, band: usize, source_bands: usize, sample_rate: f32) -> (usize, usize) {
+        let config = &self.config;
+        let scale = config.scale;
+        let lo = scale.hz_to_scale(config.min_hz);
+        let hi = scale.hz_to_scale(config.max_hz);
+        let edge = |i: usize| scale.scale_to_hz(lo + (hi - lo) * i as f32 / config.bands as f32);
+        let bin_hz = (sample_rate / 2.0) / source_bands as f32;
+        let start = ((edge(band) / bin_hz) as usize).min(source_bands - 1);
+        let end = ((edge(band + 1) / bin_hz) as usize).clamp(start + 1, source_bands);
+        (start, end)
+    }
+
+    /// Maps the analyzer's dB bins onto the configured bands and applies normalization
+    fn map_bands(&mut self, spectrum_data: &SpectrumData) -> Vec<f32> {
+        let config = self.config;
+        let source_bands = spectrum_data.magnitudes.len();
+        let range = config.max_db - config.min_db;
+        let sample_rate = spectrum_data.sample_rate.map_or(config.sample_rate, |rate| rate as f32);
+        (0..config.bands)
+            .map(|i| {
+                let (start, end) = self.bin_range(i, source_bands, sample_rate);
+                // Peak of the bins in this band
+                let peak = spectrum_data.magnitudes[start..end]
+                    .iter()
+                    .fold(-120.0f32, |acc, &v| acc.max(v));
+                let normalized = ((peak - config.min_db) / range).clamp(0.0, 1.0);
+                let band_percent = i as f32 / config.bands as f32;
+                match config.normalization {
+                    BandNormalization::None => normalized,
+                    BandNormalization::Tilt => {
+                        // Lower boost for bass, higher boost for treble
+                        if band_percent < 0.2 {
+                            (normalized.powf(0.75) * 0.85).min(1.0)
+                        } else if band_percent < 0.4 {
+                            normalized.powf(0.7).min(1.0)
+                        } else if band_percent < 0.6 {
+                            (normalized.powf(0.65) * 1.1).min(1.0)
+                        } else if band_percent < 0.8 {
+                            (normalized.powf(0.55) * 1.6).min(1.0)
+                        } else {
+                            // The critical adjustment for high frequency sensitivity
+                            (normalized.powf(0.4) * 3.0).min(1.0)
+                        }
+                    }
+                    BandNormalization::Adaptive { decay } => {
+                        let peak = (self.band_peaks[i] * decay).max(normalized).max(1e-3);
+                        self.band_peaks[i] = peak;
+                        normalized / peak
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn write_gpu_output(&self, queue: &wgpu::Queue) {
+        let Some(output) = &self.gpu_output else {
+            return;
+        };
+        let header = SpectrumBandsHeader {
+            band_count: self.config.bands as u32,
+            scale: self.config.scale.index(),
+            min_db: self.config.min_db,
+            max_db: self.config.max_db,
+        };
+        let mut data = Vec::with_capacity(std::mem::size_of::<SpectrumBandsHeader>() + self.bands.len() * 4);
+        data.extend_from_slice(bytemuck::bytes_of(&header));
+        data.extend_from_slice(bytemuck::cast_slice(&self.bands));
+        queue.write_buffer(&output.buffer, 0, &data);
+    }
+
+    /// Shared processing for any audio source (video audio track or an audio file).
+    /// The configured bands go to `bands()` and the storage buffer; `audio_data[0..16]`
+    /// gets them resampled to 64 bars. The waveform is exposed through `AudioTexture`.
+    pub fn update_from_source(
+        &mut self,
+        queue: &wgpu::Queue,
+        resolution_uniform: &mut UniformBinding<ResolutionUniform>,
+        source: Option<(SpectrumData, f32)>,
     ) {
         // Initialize audio data arrays to zero
         for i in 0..32 {
@@ -38,138 +338,77 @@ impl SpectrumAnalyzer {
                 resolution_uniform.data.audio_data[i][j] = 0.0;
             }
         }
-        
-        if using_video_texture {
-            if let Some(video_manager) = video_texture_manager {
-                if video_manager.has_audio() {
-                    let spectrum_data = video_manager.spectrum_data();
-                    resolution_uniform.data.bpm = video_manager.get_bpm();
-                    info!("BPM: {}", resolution_uniform.data.bpm);
-                    
-                    if !spectrum_data.magnitudes.is_empty() {
-                        let bands = spectrum_data.bands;
-                        // Highly sensitive threshold for detecting subtle high frequencies
-                        let threshold: f32 = -60.0;
-                        
-                        // Process only first 64 bands (note that, we actually have 128 but its expensiive)
-                        for i in 0..64 {
-                            let band_percent = i as f32 / 64.0;
-                            // Map to source index with slight emphasis on higher frequencies
-                            let source_idx = (band_percent * (bands as f32 / 2.0)) as usize;
-                            // Use narrow width for all frequencies for accuracy
-                            let width = 1;
-                            let end_idx = (source_idx + width).min(bands);
-                            
-                            if source_idx < bands {
-                                // Get peak value in this range
-                                let mut peak: f32 = -120.0;
-                                for j in source_idx..end_idx {
-                                    if j < bands {
-                                        let val = spectrum_data.magnitudes[j];
-                                        peak = peak.max(val);
-                                    }
-                                }
-                                // Map from dB scale to 0-1
-                                let normalized = ((peak - threshold) / -threshold).max(0.0).min(1.0);
-                                // Apply frequency-specific processing that's balanced
-                                // Lower boost for bass, higher boost for treble
-                                let enhanced = if band_percent < 0.2 {
-                                    // Bass - slightly reduced
-                                    (normalized.powf(0.75) * 0.85).min(1.0)
-                                } else if band_percent < 0.4 {
-                                    // Low-mids - neutral
-                                    normalized.powf(0.7).min(1.0)
-                                } else if band_percent < 0.6 {
-                                    // Mids - slight boost
-                                    (normalized.powf(0.65) * 1.1).min(1.0)
-                                } else if band_percent < 0.8 {
-                                    // Upper-mids - moderate boost
-                                    (normalized.powf(0.55) * 1.6).min(1.0)
-                                } else {
-                                    // Highs - significant boost with lower power
-                                    // The critical adjustment for high frequency sensitivity
-                                    (normalized.powf(0.4) * 3.0).min(1.0)
-                                };
-                                
-                                // No minimum thresholds - let silent frequencies be silent
-                                // Temporal smoothing with frequency-specific parameters
-                                let vec_idx = i / 4;
-                                let vec_component = i % 4;
-                                if vec_idx < 32 {
-                                    let prev_value = self.prev_audio_data[vec_idx][vec_component];
-                                    // Fast attack for all frequencies - slightly faster for highs
-                                    let attack = if band_percent < 0.6 {
-                                        0.6 
-                                    } else {
-                                        0.7 
-                                    };
-                                    let decay = if band_percent < 0.6 {
-                                        0.3 
-                                    } else {
-                                        0.25 
-                                    };
-                                    
-                                    // Apply smoothing
-                                    let smoothing_factor = if enhanced > prev_value {
-                                        attack  // Rising
-                                    } else {
-                                        decay   // Falling
-                                    };
-                                    // Calculate smoothed value
-                                    let smoothed = prev_value * (1.0 - smoothing_factor) + 
-                                                  enhanced * smoothing_factor;
-                                    // Store the result
-                                    resolution_uniform.data.audio_data[vec_idx][vec_component] = smoothed;
-                                    // Store for next frame
-                                    self.prev_audio_data[vec_idx][vec_component] = smoothed;
-                                }
-                            }
-                        }
-                        
-                        // Beat detection with balanced boost across frequency spectrum
-                        let mut bass_energy: f32 = 0.0;
-                        let bass_bands = 64 / 16;
-                        for i in 0..(bass_bands / 4) {
-                            for j in 0..4 {
-                                bass_energy += resolution_uniform.data.audio_data[i][j];
-                            }
-                        }
-                        bass_energy /= bass_bands as f32;
-                        
-                        // If we detect a beat, provide progressive boost to mid/high frequencies
-                        if bass_energy > 0.5 {
-                            // First quarter - bass 
-                            let q1 = 16 / 4;
-                            // Second quarter - low-mids
-                            let q2 = 16 / 2;
-                            // Third quarter - upper-mids
-                            let q3 = 3 * 16 / 4;
-                            
-                            for i in 0..16 {
-                                for j in 0..4 {
-                                    if i < q1 {
-                                        // No boost for bass (prevent dominance)
-                                        // Actually reduce bass slightly on beats
-                                        resolution_uniform.data.audio_data[i][j] *= 0.9;
-                                    } else if i < q2 {
-                                        // Small boost for low-mids
-                                        resolution_uniform.data.audio_data[i][j] *= 1.1;
-                                    } else if i < q3 {
-                                        // Moderate boost for upper-mids
-                                        resolution_uniform.data.audio_data[i][j] *= 1.3;
-                                    } else {
-                                        // Strong boost for highs during beats
-                                        resolution_uniform.data.audio_data[i][j] *= 1.7;
-                                    }
-                                }
+
+        if let Some((spectrum_data, bpm)) = source {
+            resolution_uniform.data.bpm = bpm;
+            info!("BPM: {}", resolution_uniform.data.bpm);
+
+            if !spectrum_data.magnitudes.is_empty() {
+                let targets = self.map_bands(&spectrum_data);
+                // Temporal smoothing: fast attack, slower release
+                for (value, target) in self.bands.iter_mut().zip(targets) {
+                    let smoothing_factor = if target > *value {
+                        self.config.attack
+                    } else {
+                        self.config.release
+                    };
+                    *value = *value * (1.0 - smoothing_factor) + target * smoothing_factor;
+                }
+
+                // 64 bars for the uniform, peak of the bands each one covers
+                let bands = self.bands.len();
+                for i in 0..UNIFORM_BANDS {
+                    let start = i * bands / UNIFORM_BANDS;
+                    let end = ((i + 1) * bands / UNIFORM_BANDS).max(start + 1).min(bands);
+                    let value = self.bands[start.min(bands - 1)..end]
+                        .iter()
+                        .fold(0.0f32, |acc, &v| acc.max(v));
+                    resolution_uniform.data.audio_data[i / 4][i % 4] = value;
+                }
+
+                // Beat detection with balanced boost across frequency spectrum
+                let mut bass_energy: f32 = 0.0;
+                let bass_bands = 64 / 16;
+                for i in 0..(bass_bands / 4) {
+                    for j in 0..4 {
+                        bass_energy += resolution_uniform.data.audio_data[i][j];
+                    }
+                }
+                bass_energy /= bass_bands as f32;
+
+                // If we detect a beat, provide progressive boost to mid/high frequencies
+                if bass_energy > 0.5 {
+                    // First quarter - bass
+                    let q1 = 16 / 4;
+                    // Second quarter - low-mids
+                    let q2 = 16 / 2;
+                    // Third quarter - upper-mids
+                    let q3 = 3 * 16 / 4;
+
+                    for i in 0..16 {
+                        for j in 0..4 {
+                            if i < q1 {
+                                // No boost for bass (prevent dominance)
+                                // Actually reduce bass slightly on beats
+                                resolution_uniform.data.audio_data[i][j] *= 0.9;
+                            } else if i < q2 {
+                                // Small boost for low-mids
+                                resolution_uniform.data.audio_data[i][j] *= 1.1;
+                            } else if i < q3 {
+                                // Moderate boost for upper-mids
+                                resolution_uniform.data.audio_data[i][j] *= 1.3;
+                            } else {
+                                // Strong boost for highs during beats
+                                resolution_uniform.data.audio_data[i][j] *= 1.7;
                             }
                         }
                     }
                 }
             }
-            
-            resolution_uniform.update(queue);
         }
+
+        resolution_uniform.update(queue);
+        self.write_gpu_output(queue);
     }
 }
 
@@ -178,4 +417,4 @@ impl SpectrumAnalyzer {
     pub fn new() -> Self {
         Self {}
     }
-}
\ No newline at end of file
+}
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct TextureManager {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
@@ -83,6 +84,11 @@ impl TextureManager {
     }
     pub fn update(&self, queue: &wgpu::Queue, image: &image::RgbaImage) {
         let dimensions = image.dimensions();
+        self.update_bytes(queue, image, dimensions.0, dimensions.1, 4 * dimensions.0);
+    }
+
+    /// Uploads RGBA pixels straight from a decoder buffer whose rows may be padded to `bytes_per_row`
+    pub fn update_bytes(&self, queue: &wgpu::Queue, data: &[u8], width: u32, height: u32, bytes_per_row: u32) {
         queue.write_texture(
             wgpu::TexelCopyTextureInfo {
                 texture: &self.texture,
@@ -90,15 +96,15 @@ impl TextureManager {
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            image,
+            data,
             wgpu::TexelCopyBufferLayout {
                 offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: Some(dimensions.1),
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
             },
             wgpu::Extent3d {
-                width: dimensions.0,
-                height: dimensions.1,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
         );
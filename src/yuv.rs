@@ -0,0 +1,423 @@
+// Planar YUV to RGB on the GPU. Decoded video is uploaded as it comes out of the decoder
+// (one R8 texture per plane, or R8 + RG8 for NV12) and a small render pass converts it into a
+// regular `Rgba8UnormSrgb` texture, so no per-frame colour conversion or copy runs on the CPU.
+use log::{info, warn};
+use crate::{ShaderCache, TextureManager};
+
+const YUV_SHADER: &str = r#"
+struct YuvParams {
+    r: vec4<f32>,
+    g: vec4<f32>,
+    b: vec4<f32>,
+    chroma_layout: u32,
+    _padding: vec3<u32>,
+};
+@group(0) @binding(0) var<uniform> params: YuvParams;
+@group(0) @binding(1) var y_plane: texture_2d<f32>;
+@group(0) @binding(2) var u_plane: texture_2d<f32>;
+@group(0) @binding(3) var v_plane: texture_2d<f32>;
+@group(0) @binding(4) var plane_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let y = textureSampleLevel(y_plane, plane_sampler, in.uv, 0.0).r;
+    var chroma: vec2<f32>;
+    if params.chroma_layout == 0u {
+        chroma = textureSampleLevel(u_plane, plane_sampler, in.uv, 0.0).rg;
+    } else {
+        chroma = vec2<f32>(
+            textureSampleLevel(u_plane, plane_sampler, in.uv, 0.0).r,
+            textureSampleLevel(v_plane, plane_sampler, in.uv, 0.0).r,
+        );
+    }
+    let yuv = vec4<f32>(y, chroma, 1.0);
+    let rgb = vec3<f32>(dot(params.r, yuv), dot(params.g, yuv), dot(params.b, yuv));
+    return vec4<f32>(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
+"#;
+
+/// How chroma is laid out in memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YuvFormat {
+    /// Y plane, then one plane of interleaved U/V at half resolution
+    Nv12,
+    /// Y, U and V planes, chroma at half resolution
+    I420,
+}
+
+/// Which YUV to RGB matrix the source was encoded with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YuvColorMatrix {
+    Bt601,
+    Bt709,
+    Bt2020,
+}
+
+impl YuvColorMatrix {
+    /// HD and larger is almost always BT.709 when the stream doesn't say
+    pub fn guess(height: u32) -> Self {
+        if height >= 720 { Self::Bt709 } else { Self::Bt601 }
+    }
+
+    /// (Kr, Kb) luma coefficients
+    fn coefficients(self) -> (f32, f32) {
+        match self {
+            Self::Bt601 => (0.299, 0.114),
+            Self::Bt709 => (0.2126, 0.0722),
+            Self::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+}
+
+/// One decoded frame's planes, borrowed from the decoder's buffer
+#[derive(Debug, Clone, Copy)]
+pub struct YuvPlanes<'a> {
+    pub format: YuvFormat,
+    pub width: u32,
+    pub height: u32,
+    pub matrix: YuvColorMatrix,
+    /// Full 0-255 range instead of the usual 16-235 video range
+    pub full_range: bool,
+    /// Y, U (or interleaved UV) and V; the V plane is unused for NV12
+    pub planes: [&'a [u8]; 3],
+    /// Bytes per row of each plane
+    pub strides: [u32; 3],
+}
+
+/// Conversion matrix rows (one per RGB channel, applied to `(y, u, v, 1)`) and chroma layout
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct YuvParams {
+    r: [f32; 4],
+    g: [f32; 4],
+    b: [f32; 4],
+    chroma_layout: u32,
+    _padding: [u32; 3],
+}
+
+impl YuvParams {
+    fn new(format: YuvFormat, matrix: YuvColorMatrix, full_range: bool) -> Self {
+        let (kr, kb) = matrix.coefficients();
+        let kg = 1.0 - kr - kb;
+        // Expand video range to 0..1 (luma) and -0.5..0.5 (chroma)
+        let (y_scale, y_offset, c_scale) = if full_range {
+            (1.0, 0.0, 1.0)
+        } else {
+            (255.0 / 219.0, -16.0 / 219.0, 255.0 / 224.0)
+        };
+        let c_offset = -128.0 / 255.0 * c_scale;
+        let (cr_r, cb_b) = (2.0 * (1.0 - kr), 2.0 * (1.0 - kb));
+        let (cb_g, cr_g) = (-2.0 * kb * (1.0 - kb) / kg, -2.0 * kr * (1.0 - kr) / kg);
+        Self {
+            r: [y_scale, 0.0, cr_r * c_scale, y_offset + cr_r * c_offset],
+            g: [y_scale, cb_g * c_scale, cr_g * c_scale, y_offset + (cb_g + cr_g) * c_offset],
+            b: [y_scale, cb_b * c_scale, 0.0, y_offset + cb_b * c_offset],
+            chroma_layout: match format {
+                YuvFormat::Nv12 => 0,
+                YuvFormat::I420 => 1,
+            },
+            _padding: [0; 3],
+        }
+    }
+}
+
+/// Plane textures and output for one frame size and format, reused until either changes
+struct YuvTargets {
+    width: u32,
+    height: u32,
+    format: YuvFormat,
+    planes: [wgpu::Texture; 3],
+    bind_group: wgpu::BindGroup,
+    output: TextureManager,
+    output_target: wgpu::TextureView,
+}
+
+/// Uploads `YuvPlanes` and converts them into `texture_manager()`
+pub struct YuvConverter {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params: wgpu::Buffer,
+    targets: Option<YuvTargets>,
+}
+
+impl YuvConverter {
+    pub fn new(device: &wgpu::Device, shader_cache: &ShaderCache) -> Self {
+        let plane_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("YUV Planes Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                plane_entry(1),
+                plane_entry(2),
+                plane_entry(3),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("YUV Plane Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("YUV Params"),
+            size: std::mem::size_of::<YuvParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let shader = shader_cache.create_shader_module(device, YUV_SHADER, "YUV Convert Shader");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("YUV Convert Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("YUV Convert Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self { pipeline, layout, sampler, params, targets: None }
+    }
+
+    /// The converted frame; replaced (new bind group) whenever `upload` returns true
+    pub fn texture_manager(&self) -> Option<&TextureManager> {
+        self.targets.as_ref().map(|t| &t.output)
+    }
+
+    /// Uploads the planes and converts them. Textures are only reallocated when the frame size
+    /// or format changes, in which case this returns true and `texture_manager` has a new texture.
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture_layout: &wgpu::BindGroupLayout,
+        frame: &YuvPlanes,
+    ) -> bool {
+        let recreate = !self.targets.as_ref().is_some_and(|t| {
+            (t.width, t.height, t.format) == (frame.width, frame.height, frame.format)
+        });
+        if recreate {
+            self.targets = Some(self.create_targets(device, texture_layout, frame));
+        }
+        let Some(targets) = &self.targets else {
+            return false;
+        };
+
+        let chroma = (frame.width.div_ceil(2), frame.height.div_ceil(2));
+        let (plane_count, chroma_bytes) = match frame.format {
+            YuvFormat::Nv12 => (2, 2),
+            YuvFormat::I420 => (3, 1),
+        };
+        for (i, texture) in targets.planes.iter().enumerate().take(plane_count) {
+            let (width, height, bytes) = if i == 0 { (frame.width, frame.height, 1) } else { (chroma.0, chroma.1, chroma_bytes) };
+            let needed = frame.strides[i] as usize * (height as usize - 1) + (width * bytes) as usize;
+            if frame.planes[i].len() < needed {
+                warn!("YUV plane {} is too short ({} < {} bytes), skipping frame", i, frame.planes[i].len(), needed);
+                return recreate;
+            }
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                frame.planes[i],
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(frame.strides[i]),
+                    rows_per_image: Some(height),
+                },
+                wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            );
+        }
+        let params = YuvParams::new(frame.format, frame.matrix, frame.full_range);
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("YUV Convert Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("YUV Convert Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &targets.output_target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &targets.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        queue.submit(Some(encoder.finish()));
+        recreate
+    }
+
+    fn create_targets(&self, device: &wgpu::Device, texture_layout: &wgpu::BindGroupLayout, frame: &YuvPlanes) -> YuvTargets {
+        info!("Creating {:?} plane textures: {}x{}", frame.format, frame.width, frame.height);
+        let chroma = (frame.width.div_ceil(2), frame.height.div_ceil(2));
+        let plane = |label: &str, (width, height): (u32, u32), format: wgpu::TextureFormat| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            })
+        };
+        let planes = match frame.format {
+            YuvFormat::Nv12 => [
+                plane("Y Plane", (frame.width, frame.height), wgpu::TextureFormat::R8Unorm),
+                plane("UV Plane", chroma, wgpu::TextureFormat::Rg8Unorm),
+                // Unused, but the layout always has three planes
+                plane("V Plane", (1, 1), wgpu::TextureFormat::R8Unorm),
+            ],
+            YuvFormat::I420 => [
+                plane("Y Plane", (frame.width, frame.height), wgpu::TextureFormat::R8Unorm),
+                plane("U Plane", chroma, wgpu::TextureFormat::R8Unorm),
+                plane("V Plane", chroma, wgpu::TextureFormat::R8Unorm),
+            ],
+        };
+        let views: Vec<wgpu::TextureView> = planes
+            .iter()
+            .map(|p| p.create_view(&wgpu::TextureViewDescriptor::default()))
+            .collect();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("YUV Planes"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: self.params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&views[0]) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&views[1]) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&views[2]) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+            ],
+        });
+
+        // Written through a non-sRGB view so the stored bytes are the gamma-encoded values,
+        // same as uploading RGBA pixels
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("YUV Output Texture"),
+            size: wgpu::Extent3d { width: frame.width, height: frame.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let output_target = texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(wgpu::TextureFormat::Rgba8Unorm),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let output_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("YUV Output Bind Group"),
+        });
+
+        YuvTargets {
+            width: frame.width,
+            height: frame.height,
+            format: frame.format,
+            planes,
+            bind_group,
+            output: TextureManager { texture, view, sampler, bind_group: output_bind_group },
+            output_target,
+        }
+    }
+}